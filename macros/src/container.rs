@@ -23,10 +23,11 @@ use syn::{Error, GenericArgument, Type, TypePath, Visibility};
 #[segment(reserve(MappedArray::size_with_records(3)))]
 #[segment(resize, reserve(MappedArray::size_with_records(3)))]
 #[segment(resize = true, reserve(MappedArray::size_with_records(3)))]
+#[segment(max_len = 64)]
 */
 
-const SEGMENT_ATTRIBUTES: &[&str] = &["fixed", "reserve", "flex"];
-const COLLECTION_ATTRIBUTES: &[&str] = &["seed", "container", "container_type"];
+const SEGMENT_ATTRIBUTES: &[&str] = &["fixed", "reserve", "flex", "max_len"];
+const COLLECTION_ATTRIBUTES: &[&str] = &["seed", "container", "container_type", "index"];
 
 #[derive(Debug)]
 pub struct SegmentArgs {
@@ -38,6 +39,8 @@ pub struct SegmentArgs {
 pub struct CollectionArgs {
     pub seed: Option<Value>,
     pub container: Option<Value>,
+    /// `CollectionIndex` segment maintained along with the collection
+    pub index: Option<Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -81,15 +84,49 @@ impl Segment {
 struct ContainerAttributes {
     container_type: Expr,
     index_size_type: TokenStream2,
+    version: TokenStream2,
+    program_id: Option<Expr>,
 }
 
 impl Parse for ContainerAttributes {
     fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
         let parsed = Punctuated::<Expr, Token![,]>::parse_terminated(input).unwrap();
+
+        // named arguments (`version = N`, `program_id = <Pubkey>`) follow the positional arguments
+        let mut version: TokenStream2 = quote! { 0 };
+        let mut program_id: Option<Expr> = None;
+        let mut positional: Punctuated<Expr, Token![,]> = Punctuated::new();
+        for expr in parsed.iter() {
+            match expr {
+                Expr::Assign(assign) => {
+                    let name = assign.left.to_token_stream().to_string();
+                    let value = &assign.right;
+                    match name.as_str() {
+                        "version" => {
+                            version = quote! { #value };
+                        }
+                        "program_id" => {
+                            program_id = Some((**value).clone());
+                        }
+                        _ => {
+                            return Err(Error::new_spanned(
+                                assign.left.clone(),
+                                format!("unsupported container argument '{}'", name),
+                            ));
+                        }
+                    }
+                }
+                _ => {
+                    positional.push(expr.clone());
+                }
+            }
+        }
+        let parsed = positional;
+
         if parsed.len() < 1 || parsed.len() > 2 {
             return Err(Error::new_spanned(
-                parsed[2].clone(),
-                format!("usage: #[container(<container type id>, <index size: u16 or u32>)]"),
+                parsed.clone(),
+                format!("usage: #[container(<container type id>, <index size: u16 or u32>, version = <n>, program_id = <program id>)]"),
             ));
         }
 
@@ -115,6 +152,8 @@ impl Parse for ContainerAttributes {
         Ok(ContainerAttributes {
             container_type,
             index_size_type: index_size_type.into(),
+            version,
+            program_id,
         })
     }
 }
@@ -240,6 +279,15 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
             None
         };
 
+        let collection_index = if let Some(args) = &args.collection {
+            match args.get_value_or("index", field_name.clone(), "missing index value") {
+                Ok(value) => value,
+                Err(err) => return err.into(),
+            }
+        } else {
+            None
+        };
+
         let type_name = field.ty.clone();
         let visibility = field.vis.clone();
         let type_name_for_ident = type_name.clone();
@@ -255,6 +303,10 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                                 GenericArgument::Type(arg_type) => {
                                     types.push(arg_type.to_token_stream().to_string());
                                 }
+                                // const generic arguments (e.g. ring collection capacity)
+                                GenericArgument::Const(arg_const) => {
+                                    types.push(arg_const.to_token_stream().to_string());
+                                }
                                 _ => {}
                             }
                         }
@@ -274,6 +326,7 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
             Some(CollectionArgs {
                 seed: collection_seed,
                 container: collection_container,
+                index: collection_index,
             })
         } else {
             if args.collection.is_some() {
@@ -377,6 +430,133 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
         }
     }
 
+    // #[collection(index(<field>))] generates functions maintaining the
+    // `CollectionIndex` segment <field> along with the collection entries
+    let mut collection_index_fns = Vec::new();
+    for segment in segments.iter() {
+        let index = match &segment.collection {
+            Some(CollectionArgs {
+                index: Some(index), ..
+            }) => index.to_token_stream().to_string(),
+            _ => continue,
+        };
+
+        if segment_type_ident(&segment.type_name).as_deref() != Some("PdaCollection") {
+            return Error::new_spanned(
+                segment.field_name.clone(),
+                format!("#[collection()]: index attribute is only supported by PdaCollection"),
+            )
+            .to_compile_error()
+            .into();
+        }
+
+        let key = match segments.iter().find(|segment| segment.name == index) {
+            Some(index_segment) => match record_type_args(&index_segment.type_name) {
+                Some((ident, types)) if ident == "CollectionIndex" && types.len() == 1 => {
+                    types[0].to_token_stream()
+                }
+                _ => {
+                    return Error::new_spanned(
+                        index_segment.field_name.clone(),
+                        format!(
+                            "#[collection()]: index segment '{}' must be a CollectionIndex",
+                            index
+                        ),
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            },
+            None => {
+                return Error::new_spanned(
+                    segment.field_name.clone(),
+                    format!("#[collection()]: missing index segment '{}'", index),
+                )
+                .to_compile_error()
+                .into();
+            }
+        };
+
+        let field_name = &segment.field_name;
+        let index_field_name = Ident::new(&index, Span::call_site());
+        let try_create_fn = Ident::new(
+            &format!("try_create_{}_container", segment.name),
+            Span::call_site(),
+        );
+        let try_insert_fn = Ident::new(
+            &format!("try_insert_{}_container", segment.name),
+            Span::call_site(),
+        );
+        let try_remove_fn = Ident::new(
+            &format!("try_remove_{}_container", segment.name),
+            Span::call_site(),
+        );
+        collection_index_fns.push(quote! {
+
+            /// Creates a collection entry from the collection template candidates,
+            /// initializing it using `init` before indexing it.
+            ///
+            /// # Safety
+            ///
+            /// May cause segment data displacement. Must not retain or must re-acquire
+            /// any segment memory referencing data structures (references, slices or pointers).
+            ///
+            pub unsafe fn #try_create_fn<T, F>(
+                &mut self,
+                ctx: &kaizen::context::ContextReference<'info,'refs,'_,'_>,
+                data_len: Option<usize>,
+                init: F,
+            ) -> kaizen::result::Result<T>
+            where
+                T: kaizen::container::Container<'info,'refs, T = T> + kaizen::container::IndexKey<#key>,
+                F: FnOnce(&mut T) -> kaizen::result::Result<()>,
+            {
+                self.#field_name.try_create_container_from_candidates_with_index::<T,#key,F>(ctx, data_len, &self.#index_field_name, init)
+            }
+
+            /// Inserts the container into the collection, indexing it.
+            ///
+            /// # Safety
+            ///
+            /// May cause segment data displacement. Must not retain or must re-acquire
+            /// any segment memory referencing data structures (references, slices or pointers).
+            ///
+            pub unsafe fn #try_insert_fn<T>(
+                &mut self,
+                ctx: &kaizen::context::ContextReference<'info,'refs,'_,'_>,
+                seed_bump: u8,
+                container: &T,
+            ) -> kaizen::result::Result<()>
+            where
+                T: kaizen::container::Container<'info,'refs> + kaizen::container::IndexKey<#key>,
+            {
+                self.#field_name.try_insert_container_with_index(ctx, seed_bump, container, &self.#index_field_name)
+            }
+
+            /// Removes the collection entry at `index` along with its index record.
+            ///
+            /// # Safety
+            ///
+            /// May cause segment data displacement. Must not retain or must re-acquire
+            /// any segment memory referencing data structures (references, slices or pointers).
+            ///
+            pub unsafe fn #try_remove_fn<T>(
+                &mut self,
+                ctx: &kaizen::context::ContextReference<'info,'refs,'_,'_>,
+                index: u64,
+                seed_bump: u8,
+                account: &'refs solana_program::account_info::AccountInfo<'info>,
+                recipient: &'refs solana_program::account_info::AccountInfo<'info>,
+            ) -> kaizen::result::Result<()>
+            where
+                T: kaizen::container::Container<'info,'refs>,
+                <T as kaizen::container::Container<'info,'refs>>::T: kaizen::container::IndexKey<#key>,
+            {
+                self.#field_name.try_remove_container_with_index::<T,#key>(ctx, index, seed_bump, account, recipient, &self.#index_field_name)
+            }
+        });
+    }
+
     let mut inits = Vec::new();
     let mut loads = Vec::new();
 
@@ -525,13 +705,35 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                 )?;
             });
         } else {
+            // #[segment(max_len = N)] limits the length of string segments
+            let max_len = match &segment.args.segment {
+                Some(args) => match args.map.get(&Ident::new("max_len", Span::call_site())) {
+                    Some(Some(max_len)) => {
+                        let max_len = max_len.to_token_stream();
+                        quote! { .with_max_len(#max_len) }
+                    }
+                    Some(None) => {
+                        return Error::new_spanned(
+                            segment.field_name.clone(),
+                            format!(
+                                "#[segment()]: max_len attribute for segment '{}' must contain a value",
+                                segment.name
+                            ),
+                        )
+                        .to_compile_error()
+                        .into();
+                    }
+                    None => quote! {},
+                },
+                None => quote! {},
+            };
             inits.push(quote! {
                 let segment = #store_field_name.try_get_segment_at(#idx)?;
-                let #field_name : #type_name  = #type_ident::try_create_from_segment(segment)?;
+                let #field_name : #type_name  = #type_ident::try_create_from_segment(segment)? #max_len;
             });
             loads.push(quote! {
                 let segment = #store_field_name.try_get_segment_at(#idx)?;
-                let #field_name : #type_name  = #type_ident::try_load_from_segment(segment)?;
+                let #field_name : #type_name  = #type_ident::try_load_from_segment(segment)? #max_len;
             });
         }
     }
@@ -633,7 +835,86 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
         }
     };
 
+    let mut segment_descriptors = Vec::new();
+    for segment in segments.iter() {
+        if segment.is_meta() || segment.is_store() {
+            continue;
+        }
+
+        let name = segment.name.clone();
+        let type_name = type_name_to_string(&segment.type_name);
+        let (fixed, reserve) = match &segment.args.segment {
+            Some(args) => {
+                let reserve = match args.map.get(&Ident::new("reserve", Span::call_site())) {
+                    Some(Some(reserve)) => {
+                        let reserve = reserve.to_token_stream().to_string();
+                        quote! { Some(#reserve) }
+                    }
+                    _ => quote! { None },
+                };
+                (args.get("fixed").is_some(), reserve)
+            }
+            None => (false, quote! { None }),
+        };
+        let flex = segment.flex;
+        let collection = match &segment.collection {
+            Some(CollectionArgs {
+                seed: Some(seed),
+                container,
+                ..
+            }) => {
+                let seed = seed.to_token_stream();
+                let container = match container {
+                    Some(container) => {
+                        let container = container.to_token_stream().to_string();
+                        quote! { Some(#container) }
+                    }
+                    None => quote! { None },
+                };
+                quote! { .with_collection(#seed, #container) }
+            }
+            _ => quote! {},
+        };
+
+        let record_size = match record_type(&segment.type_name) {
+            Some(record_type) => quote! { .with_record_size(std::mem::size_of::<#record_type>()) },
+            None => quote! {},
+        };
+
+        segment_descriptors.push(quote! {
+            kaizen::container::SegmentDescriptor::new(#name, #type_name)
+                .with_attributes(#fixed, #flex, #reserve)
+                #collection
+                #record_size
+        });
+    }
+
+    let meta_descriptor = match &meta_type_path {
+        Some(type_path) => {
+            let type_path_str = type_name_to_string(&Type::Path(type_path.clone()));
+            quote! {
+                Some(kaizen::container::MetaDescriptor::new(
+                    #type_path_str,
+                    std::mem::size_of::<#type_path>(),
+                ))
+            }
+        }
+        None => quote! { None },
+    };
+
+    // containers declared without a program id are not scoped to a program
+    let program_id = match &cattr.program_id {
+        Some(program_id) => quote! { .with_program_id(#program_id) },
+        None => quote! {},
+    };
+
+    let segment_descriptors_const = Ident::new(
+        &format!("{}_SEGMENT_DESCRIPTORS", struct_name).to_uppercase(),
+        struct_name.span(),
+    );
+
     let container_type = cattr.container_type;
+    let container_version = cattr.version;
     let index_unit_size = cattr.index_size_type;
     let struct_name_str = struct_name.to_string();
     let module_declaration = Ident::new(
@@ -689,9 +970,12 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
         impl #struct_params #struct_name #struct_params #where_clause {
 
             pub const CONTAINER_TYPE: u32 = #container_type as u32;
+            pub const CONTAINER_VERSION: u16 = #container_version as u16;
 
             #(#collection_inits)*
 
+            #(#collection_index_fns)*
+
             pub fn try_allocate_default<'pid,'instr>(
                 ctx: &std::rc::Rc<std::boxed::Box<kaizen::context::Context<'info,'refs,'pid,'instr>>>,
                 allocation_args : &kaizen::context::AccountAllocationArgs<'info,'refs,'_>,
@@ -720,6 +1004,7 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                 let #store_field_name = kaizen::container::segment::SegmentStore::try_create(
                     &account, segment_store_offset, &layout,
                 )?;
+                #store_field_name.set_container_version(Self::CONTAINER_VERSION);
                 #inits_ts2
 
                 {
@@ -738,6 +1023,7 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                 let #store_field_name = kaizen::container::segment::SegmentStore::try_create(
                     &account, segment_store_offset, &layout,
                 )?;
+                #store_field_name.set_container_version(Self::CONTAINER_VERSION);
 
                 #inits_ts2
 
@@ -789,11 +1075,63 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                     &account, segment_store_offset,
                 )?;
 
+                if #store_field_name.get_container_version() != Self::CONTAINER_VERSION {
+                    return Err(
+                        kaizen::error::Error::new()
+                            .with_code(kaizen::error::ErrorCode::ContainerVersionMismatch)
+                            .with_account(account.key)
+                            .with_source(file!(),line!())
+                    );
+                }
+
                 #loads_ts2
 
                 Ok(#loads_create)
             }
 
+            /// Verifies the container type and the segment store layout of the account.
+            pub fn try_verify(account : &'refs solana_program::account_info::AccountInfo<'info>) -> kaizen::result::Result<()> {
+
+                #init_offset
+                if kaizen::container::try_get_container_type(account)? != Self::CONTAINER_TYPE {
+                    return Err(
+                        kaizen::error::Error::new()
+                            .with_code(kaizen::error::ErrorCode::ContainerTypeMismatch)
+                            .with_account(account.key)
+                            .with_source(file!(),line!())
+                    );
+                }
+
+                kaizen::container::segment::SegmentStore::try_load(
+                    &account, segment_store_offset,
+                )?.verify()
+            }
+
+            /// Loads the container, first upgrading account data created
+            /// under a previous container version using the supplied migrations.
+            pub fn try_load_with_migrations(
+                account : &'refs solana_program::account_info::AccountInfo<'info>,
+                migrations : &kaizen::container::Migrations,
+            ) -> kaizen::result::Result<#struct_name #struct_params> {
+
+                #init_offset
+                if kaizen::container::try_get_container_type(account)? != Self::CONTAINER_TYPE {
+                    return Err(
+                        kaizen::error::Error::new()
+                            .with_code(kaizen::error::ErrorCode::ContainerTypeMismatch)
+                            .with_account(account.key)
+                            .with_source(file!(),line!())
+                    );
+                }
+
+                let store = kaizen::container::segment::SegmentStore::try_load(
+                    &account, segment_store_offset,
+                )?;
+                migrations.try_migrate(&store, Self::CONTAINER_VERSION)?;
+
+                Self::try_load(account)
+            }
+
             #try_create_with_meta
 
             #[inline]
@@ -860,6 +1198,10 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                 #container_type as u32
             }
 
+            fn container_version() -> u16 {
+                #container_version as u16
+            }
+
             fn initial_data_len() -> usize {
                 #struct_path_with_generics :: initial_data_len()
             }
@@ -888,14 +1230,26 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                 #struct_name :: #struct_params :: try_load(account)
             }
 
+            fn try_verify(account : &'refs solana_program::account_info::AccountInfo<'info>) -> kaizen::result::Result<()> {
+                #struct_name :: #struct_params :: try_verify(account)
+            }
+
         }
 
+        #[cfg(not(target_os = "solana"))]
+        const #segment_descriptors_const: &[kaizen::container::SegmentDescriptor] = &[
+            #( #segment_descriptors ),*
+        ];
+
         #[cfg(not(any(target_os = "solana",target_arch = "wasm32")))]
         kaizen::inventory::submit! {
             kaizen::container::registry::ContainerDeclaration::new(
                 #container_type as u32,
                 #struct_name_str,
             )
+            .with_version(#container_version as u16)
+            .with_layout(#meta_descriptor, #segment_descriptors_const)
+            #program_id
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -908,7 +1262,10 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
                 let container_declaration = kaizen::container::registry::ContainerDeclaration::new(
                     #container_type as u32,
                     #struct_name_str,
-                );
+                )
+                .with_version(#container_version as u16)
+                .with_layout(#meta_descriptor, #segment_descriptors_const)
+                #program_id;
                 kaizen::container::registry::register_container_declaration(
                     container_declaration
                 )?;
@@ -919,3 +1276,55 @@ pub fn container_attribute_handler(attr: TokenStream, item: TokenStream) -> Toke
 
     init.into()
 }
+
+/// Returns the name of the segment type (`PdaCollection`)
+fn segment_type_ident(type_name: &Type) -> Option<String> {
+    match type_name {
+        Type::Path(type_path) => Some(type_path.path.segments.last()?.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the name and the type arguments of a generic segment type
+fn record_type_args(type_name: &Type) -> Option<(String, Vec<&Type>)> {
+    let target = match type_name {
+        Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+    let types = match &target.arguments {
+        PathArguments::AngleBracketed(params) => params
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(arg_type) => Some(arg_type),
+                _ => None,
+            })
+            .collect::<Vec<_>>(),
+        _ => return None,
+    };
+    Some((target.ident.to_string(), types))
+}
+
+/// Returns the record type of record-based segments (`Array`, `Map`, `CollectionIndex`)
+fn record_type(type_name: &Type) -> Option<TokenStream2> {
+    let (ident, types) = record_type_args(type_name)?;
+    match (ident.as_str(), types.as_slice()) {
+        ("Array", [record]) => Some(quote! { #record }),
+        ("Map", [key, value]) => Some(quote! { kaizen::container::MapEntry<#key, #value> }),
+        ("CollectionIndex", [key]) => Some(quote! { kaizen::container::IndexRecord<#key> }),
+        _ => None,
+    }
+}
+
+/// Renders a type as a compact string (`Array<'info, 'refs, u64>`)
+fn type_name_to_string(type_name: &Type) -> String {
+    type_name
+        .to_token_stream()
+        .to_string()
+        .replace(" < ", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" , ", ", ")
+        .replace(" :: ", "::")
+        .replace("& ", "&")
+}
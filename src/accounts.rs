@@ -115,6 +115,44 @@ mod client {
         MetalplexNFT,
     }
 
+    /// Commitment level at which account data has been fetched
+    #[derive(
+        Copy,
+        Clone,
+        Debug,
+        Hash,
+        BorshSerialize,
+        BorshDeserialize,
+        Serialize,
+        Deserialize,
+        PartialEq,
+        Eq,
+    )]
+    pub enum Commitment {
+        Processed,
+        Confirmed,
+        Finalized,
+    }
+
+    /// Origin of account data fetched from the network: the slot
+    /// (if known), the commitment level and the time of the fetch.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct FetchStamp {
+        pub slot: Option<u64>,
+        pub commitment: Commitment,
+        pub timestamp: Instant,
+    }
+
+    impl FetchStamp {
+        pub fn try_new(slot: Option<u64>, commitment: Commitment) -> Result<FetchStamp> {
+            Ok(FetchStamp {
+                slot,
+                commitment,
+                timestamp: Instant::now()?,
+            })
+        }
+    }
+
     #[derive(Debug)]
     pub struct AccountDataReference {
         pub key: Arc<Pubkey>,
@@ -124,6 +162,10 @@ mod client {
         pub data_len: usize,
         pub lock: AtomicBool,
         pub account_data: Arc<Mutex<AccountData>>,
+        pub fetch_stamp: Option<FetchStamp>,
+        /// Data restored from the persistent client cache; possibly stale
+        /// until refreshed from the network.
+        pub restored: bool,
     }
 
     impl AccountDataReference {
@@ -146,9 +188,27 @@ mod client {
                 data_len,
                 lock: AtomicBool::new(false),
                 account_data: Arc::new(Mutex::new(account_data)),
+                fetch_stamp: None,
+                restored: false,
             }
         }
 
+        pub fn with_fetch_stamp(mut self, fetch_stamp: FetchStamp) -> Self {
+            self.fetch_stamp = Some(fetch_stamp);
+            self
+        }
+
+        pub fn with_restored(mut self, restored: bool) -> Self {
+            self.restored = restored;
+            self
+        }
+
+        /// Returns `true` if the data has been restored from the persistent
+        /// client cache and has not yet been refreshed from the network.
+        pub fn is_restored(&self) -> bool {
+            self.restored
+        }
+
         pub fn pubkey(&self) -> &Pubkey {
             &self.key
         }
@@ -184,6 +244,8 @@ mod client {
                 data_len: self.data_len,
                 lock: AtomicBool::new(false),
                 account_data: Arc::new(Mutex::new(account_data)),
+                fetch_stamp: self.fetch_stamp,
+                restored: self.restored,
             };
             Ok(Arc::new(replica))
         }
@@ -206,10 +268,32 @@ mod client {
             self.try_into_container_replica::<T>(false)
         }
 
+        /// Loads the container after verifying its layout
+        /// (see [`SegmentStore::verify`](kaizen::container::segment::SegmentStore::verify))
+        pub fn try_into_container_verified<'this, T>(
+            self: &Arc<Self>,
+        ) -> Result<ContainerReference<'this, T>>
+        where
+            T: kaizen::container::Container<'this, 'this>,
+        {
+            self.try_into_container_with_options::<T>(true, true)
+        }
+
         pub fn try_into_container_replica<'this, T>(
             self: &Arc<Self>,
             replicate: bool,
         ) -> Result<ContainerReference<'this, T>>
+        where
+            T: kaizen::container::Container<'this, 'this>,
+        {
+            self.try_into_container_with_options::<T>(replicate, false)
+        }
+
+        fn try_into_container_with_options<'this, T>(
+            self: &Arc<Self>,
+            replicate: bool,
+            verify: bool,
+        ) -> Result<ContainerReference<'this, T>>
         where
             T: kaizen::container::Container<'this, 'this>,
         {
@@ -277,7 +361,11 @@ mod client {
             >::new_with_fn(account_info, |x| {
                 Box::new(unsafe {
                     let account_info: &'this AccountInfo<'this> = x.as_ref().unwrap();
-                    let t = T::try_load(account_info);
+                    let t = if verify {
+                        T::try_verify(account_info).and_then(|_| T::try_load(account_info))
+                    } else {
+                        T::try_load(account_info)
+                    };
                     UnsafeCell::new(Some(t))
                 })
             });
@@ -431,9 +519,10 @@ mod client {
                 (73, 4, "6"),                   //data length : 4
                 (77, container_type_size, "8"), //container type : 4
                 (81, 4, "168"),                 //store magic : 4
-                (85, 4, "169"),                 //store version : 4
-                (87, 2, "161"),                 //store payload_len : 2
-                (89, 2, "cyan"),                //store index_unit_size : 2
+                (85, 2, "169"),                 //store version : 2
+                (87, 2, "161"),                 //container version : 2
+                (89, 2, "cyan"),                //store payload_len : 2
+                (91, 2, "162"),                 //store index_unit_size : 2
                 (93, 4, "blue"),                //store segments count : 4
             ];
 
@@ -581,7 +670,10 @@ mod client {
             };
 
             let (container_type, container_type_name) = match self.container_type {
-                Some(container_type) => match kaizen::container::registry::lookup(container_type) {
+                Some(container_type) => match kaizen::container::registry::lookup_for_program(
+                    &self.owner,
+                    container_type,
+                ) {
                     Ok(Some(declaration)) => {
                         let container_type = format!("0x{container_type:08x}");
                         (container_type, declaration.name)
@@ -137,7 +137,8 @@ pub struct Inner {
 
     // TODO
     generic_template_account_descriptors: Vec<GenericTemplateAccountDescriptor>,
-    collection_template_account_descriptors: Vec<CollectionTemplateAccountDescriptor>,
+    // collection templates along with the size of the candidate group they belong to
+    collection_template_account_descriptors: Vec<(CollectionTemplateAccountDescriptor, Option<u8>)>,
 
     // Reference to an external seed that should be used during PDA creation (allowing PDA seed
     // sequence value to be tracked in an external object such as `InstructionBufferConfig`)
@@ -304,8 +305,7 @@ impl InstructionBuilder {
     }
 
     pub fn collection_template_accounts(&self) -> Vec<AccountMeta> {
-        // &self.generic_template_accounts
-        self.inner().generic_template_accounts.clone()
+        self.inner().collection_template_accounts.clone()
     }
 
     pub fn collection_template_account_at(&self, idx: usize) -> AccountMeta {
@@ -594,15 +594,48 @@ impl InstructionBuilder {
     where
         A: PdaCollectionCreatorInterface,
     {
-        for _ in 0..number_of_accounts {
-            let collection_account_descriptors = pda_collection
-                .creator(&self.program_id(), 1)?
-                .writable_accounts_meta()
-                .await?;
-            self.inner()
-                .collection_template_account_descriptors
-                .extend_from_slice(&collection_account_descriptors);
+        let collection_account_descriptors = pda_collection
+            .creator(&self.program_id(), number_of_accounts)?
+            .writable_accounts_meta()
+            .await?;
+        self.inner().collection_template_account_descriptors.extend(
+            collection_account_descriptors
+                .into_iter()
+                .map(|descriptor| (descriptor, None)),
+        );
+        Ok(self)
+    }
+
+    /// Supplies a group of candidate templates for a single collection entry.
+    /// Candidates are derived from the current collection length, allowing the
+    /// program to select the template matching the collection length at the
+    /// time of execution (see `PdaCollectionInterface::try_create_container_from_candidates()`).
+    /// This prevents collisions when multiple users create collection entries in
+    /// parallel, as long as the number of concurrent creations does not exceed
+    /// the number of candidates.
+    pub async fn with_collection_template_candidates<A>(
+        self: Arc<Self>,
+        pda_collection: &A,
+        candidates: usize,
+    ) -> Result<Arc<Self>>
+    where
+        A: PdaCollectionCreatorInterface,
+    {
+        if candidates == 0 || candidates > u8::MAX as usize {
+            return Err(error!(
+                "InstructionBuilder::with_collection_template_candidates(): invalid number of candidates {candidates}"
+            ));
         }
+
+        let collection_account_descriptors = pda_collection
+            .creator(&self.program_id(), candidates)?
+            .writable_accounts_meta()
+            .await?;
+        self.inner().collection_template_account_descriptors.extend(
+            collection_account_descriptors
+                .into_iter()
+                .map(|descriptor| (descriptor, Some(candidates as u8))),
+        );
         Ok(self)
     }
 
@@ -849,9 +882,14 @@ impl InstructionBuilder {
 
             let collection_template_account_descriptors =
                 inner.collection_template_account_descriptors.clone();
-            for (meta, bump) in collection_template_account_descriptors.iter() {
+            for ((meta, bump), candidates) in collection_template_account_descriptors.iter() {
                 inner.collection_template_accounts.push(meta.clone());
-                inner.collection_template_address_data.push(vec![*bump]);
+                match candidates {
+                    Some(candidates) => inner
+                        .collection_template_address_data
+                        .push(vec![*bump, *candidates]),
+                    None => inner.collection_template_address_data.push(vec![*bump]),
+                }
             }
 
             inner.generic_template_instruction_data =
@@ -1,12 +1,24 @@
 //!
 //! Application-level in-memory account data cache backed by [`Moka Cache`](moka::sync::Cache).
 //!
+//! In addition to capacity-based eviction, the cache supports time-based expiry
+//! ([`CacheConfig::time_to_live`], [`CacheConfig::time_to_idle`]) and commitment-aware
+//! freshness: entries carrying a [`FetchStamp`](crate::accounts::FetchStamp) are considered
+//! fresh for the period configured for their [`Commitment`] level, then stale (usable while
+//! being revalidated) for [`CacheConfig::stale_while_revalidate`], after which they are
+//! discarded. Entries restored from the [`PersistentCache`](crate::store::PersistentCache)
+//! are always reported as stale until refreshed.
+//!
 
-use crate::accounts::AccountDataReference;
+use crate::accounts::{AccountDataReference, Commitment};
 use crate::result::Result;
+use crate::store::Notifier;
 use cfg_if::cfg_if;
+use kaizen::time::Instant;
 use solana_program::pubkey::Pubkey;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use workflow_log::log_trace;
 
 #[cfg(target_arch = "wasm32")]
@@ -23,60 +35,270 @@ cfg_if! {
 const DEFAULT_CAPACITY: u64 = 1024u64 * 1024u64 * 64u64; // 64 megabytes
                                                          // const DEFAULT_CAPACITY : u64 = 1024u64 * 1024u64 * 256u64; // 256 megabytes
 
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Cache capacity in bytes of account data
+    pub capacity: u64,
+    /// Maximum lifetime of an entry since its insertion
+    pub time_to_live: Option<Duration>,
+    /// Maximum lifetime of an entry since its last access
+    pub time_to_idle: Option<Duration>,
+    /// Freshness period of entries fetched at [`Commitment::Processed`]
+    pub processed_freshness: Option<Duration>,
+    /// Freshness period of entries fetched at [`Commitment::Confirmed`]
+    pub confirmed_freshness: Option<Duration>,
+    /// Freshness period of entries fetched at [`Commitment::Finalized`]
+    pub finalized_freshness: Option<Duration>,
+    /// Period following expiry of freshness during which a stale entry
+    /// is returned while being refreshed in the background
+    pub stale_while_revalidate: Duration,
+}
+
+impl CacheConfig {
+    pub fn new_with_capacity(capacity: u64) -> CacheConfig {
+        CacheConfig {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn freshness(&self, commitment: Commitment) -> Option<Duration> {
+        match commitment {
+            Commitment::Processed => self.processed_freshness,
+            Commitment::Confirmed => self.confirmed_freshness,
+            Commitment::Finalized => self.finalized_freshness,
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            capacity: DEFAULT_CAPACITY,
+            time_to_live: None,
+            time_to_idle: None,
+            processed_freshness: None,
+            confirmed_freshness: None,
+            finalized_freshness: None,
+            stale_while_revalidate: Duration::from_secs(0),
+        }
+    }
+}
+
+/// Cache diagnostics counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub stale_hits: u64,
+    pub misses: u64,
+    pub expirations: u64,
+    pub inserts: u64,
+    pub invalidations: u64,
+    pub entries: u64,
+    pub weighted_size: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    expirations: AtomicU64,
+    inserts: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+/// Result of a freshness-aware cache lookup
+pub enum CacheLookup {
+    Fresh(Arc<AccountDataReference>),
+    /// Entry is past its freshness period and should be revalidated
+    Stale(Arc<AccountDataReference>),
+    Miss,
+}
+
+enum Freshness {
+    Fresh,
+    Stale,
+    Expired,
+}
+
 cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         pub struct Cache {
-            cache_impl : Arc<Mutex<MokaCache<Pubkey,Arc<AccountDataReference>>>>
+            cache_impl : Arc<Mutex<MokaCache<Pubkey,Arc<AccountDataReference>>>>,
+            config : CacheConfig,
+            counters : Counters,
         }
     } else {
         pub struct Cache {
-            cache_impl : MokaCache<Pubkey,Arc<AccountDataReference>>
+            cache_impl : MokaCache<Pubkey,Arc<AccountDataReference>>,
+            config : CacheConfig,
+            counters : Counters,
         }
     }
 }
 
 impl Cache {
     pub fn new_with_capacity(capacity: u64) -> Cache {
+        Self::new_with_config(CacheConfig::new_with_capacity(capacity))
+    }
+
+    pub fn new_with_default_capacity() -> Self {
+        Self::new_with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn new_with_config(config: CacheConfig) -> Cache {
         log_trace!(
             "init account data cache with {} MiB capacity",
-            capacity / 1024 / 1024
+            config.capacity / 1024 / 1024
         );
-        let cache_impl = MokaCache::builder()
+        let mut builder = MokaCache::builder()
             .weigher(|_key, reference: &Arc<AccountDataReference>| -> u32 {
                 reference.data_len as u32
             })
-            .max_capacity(capacity)
-            .build();
+            .max_capacity(config.capacity);
+        if let Some(time_to_live) = config.time_to_live {
+            builder = builder.time_to_live(time_to_live);
+        }
+        if let Some(time_to_idle) = config.time_to_idle {
+            builder = builder.time_to_idle(time_to_idle);
+        }
+        let cache_impl = builder.build();
+        let counters = Counters::default();
 
         cfg_if! {
             if #[cfg(target_arch = "wasm32")] {
-                Self { cache_impl : Arc::new(Mutex::new(cache_impl)) }
+                Self { cache_impl : Arc::new(Mutex::new(cache_impl)), config, counters }
             } else {
-                Self { cache_impl }
+                Self { cache_impl, config, counters }
             }
         }
     }
 
-    pub fn new_with_default_capacity() -> Self {
-        Self::new_with_capacity(DEFAULT_CAPACITY)
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Invalidates cache entries for accounts reported as changed by the store [`Notifier`].
+    pub fn bind_store_notifier(self: &Arc<Self>, notifier: &Notifier) {
+        let (_, receiver) = notifier.register_event_channel();
+        let cache = self.clone();
+        workflow_core::task::spawn(async move {
+            while let Ok(event) = receiver.recv().await {
+                if let Err(err) = cache.purge(Some(event.pubkey())) {
+                    log_trace!("unable to invalidate cache entry {}: {err}", event.pubkey());
+                }
+            }
+        });
+    }
+
+    fn freshness(&self, reference: &AccountDataReference) -> Result<Freshness> {
+        // restored data remains usable while offline but is always subject to revalidation
+        if reference.is_restored() {
+            return Ok(Freshness::Stale);
+        }
+        let fetch_stamp = match &reference.fetch_stamp {
+            Some(fetch_stamp) => fetch_stamp,
+            None => return Ok(Freshness::Fresh),
+        };
+        let period = match self.config.freshness(fetch_stamp.commitment) {
+            Some(period) => period,
+            None => return Ok(Freshness::Fresh),
+        };
+
+        // stamps ahead of the local clock (clock skew or records restored from the
+        // persistent tier) are treated as fresh
+        let age = fetch_stamp.timestamp.elapsed_since(&Instant::now()?).0;
+        if age <= period.as_secs() {
+            Ok(Freshness::Fresh)
+        } else if age <= (period + self.config.stale_while_revalidate).as_secs() {
+            Ok(Freshness::Stale)
+        } else {
+            Ok(Freshness::Expired)
+        }
+    }
+
+    /// Freshness-aware lookup. Expired entries are discarded and reported as [`CacheLookup::Miss`].
+    pub fn lookup_with_freshness(&self, pubkey: &Pubkey) -> Result<CacheLookup> {
+        let reference = match self.get(pubkey)? {
+            Some(reference) => reference,
+            None => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(CacheLookup::Miss);
+            }
+        };
+
+        match self.freshness(&reference)? {
+            Freshness::Fresh => {
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(CacheLookup::Fresh(reference))
+            }
+            Freshness::Stale => {
+                self.counters.stale_hits.fetch_add(1, Ordering::Relaxed);
+                Ok(CacheLookup::Stale(reference))
+            }
+            Freshness::Expired => {
+                self.counters.expirations.fetch_add(1, Ordering::Relaxed);
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                self.invalidate(Some(pubkey))?;
+                Ok(CacheLookup::Miss)
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn lookup(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
+        let reference = self.get(pubkey)?;
+        if reference.is_some() {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.counters.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(reference)
+    }
+
+    #[inline(always)]
+    pub fn store(&self, reference: &Arc<AccountDataReference>) -> Result<()> {
+        self.counters.inserts.fetch_add(1, Ordering::Relaxed);
+        self.insert(reference)
+    }
+
+    #[inline(always)]
+    pub fn purge(&self, pubkey: Option<&Pubkey>) -> Result<()> {
+        self.counters.invalidations.fetch_add(1, Ordering::Relaxed);
+        self.invalidate(pubkey)
+    }
+
+    pub fn stats(&self) -> Result<CacheStats> {
+        let (entries, weighted_size) = self.size()?;
+        Ok(CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            stale_hits: self.counters.stale_hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            expirations: self.counters.expirations.load(Ordering::Relaxed),
+            inserts: self.counters.inserts.load(Ordering::Relaxed),
+            invalidations: self.counters.invalidations.load(Ordering::Relaxed),
+            entries,
+            weighted_size,
+        })
     }
 
     cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
 
             #[inline(always)]
-            pub fn lookup(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
+            fn get(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
                 Ok(self.cache_impl.lock()?.get(pubkey).cloned())
             }
 
             #[inline(always)]
-            pub fn store(&self, reference : &Arc<AccountDataReference>) -> Result<()> {
+            fn insert(&self, reference : &Arc<AccountDataReference>) -> Result<()> {
                 self.cache_impl.lock()?.insert(*reference.key,reference.clone());
                 Ok(())
             }
 
             #[inline(always)]
-            pub fn purge(&self, pubkey : Option<&Pubkey>) -> Result<()> {
+            fn invalidate(&self, pubkey : Option<&Pubkey>) -> Result<()> {
                 match pubkey {
                     Some(pubkey) => {
                         self.cache_impl.lock()?.invalidate(pubkey);
@@ -89,21 +311,26 @@ impl Cache {
                 }
             }
 
+            fn size(&self) -> Result<(u64, u64)> {
+                let cache_impl = self.cache_impl.lock()?;
+                Ok((cache_impl.entry_count(), cache_impl.weighted_size()))
+            }
+
         } else {
 
             #[inline(always)]
-            pub fn lookup(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
+            fn get(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
                 Ok(self.cache_impl.get(pubkey))
             }
 
             #[inline(always)]
-            pub fn store(&self, reference : &Arc<AccountDataReference>) -> Result<()> {
+            fn insert(&self, reference : &Arc<AccountDataReference>) -> Result<()> {
                 self.cache_impl.insert(*reference.key,reference.clone());
                 Ok(())
             }
 
             #[inline(always)]
-            pub fn purge(&self, pubkey: Option<&Pubkey>) -> Result<()> {
+            fn invalidate(&self, pubkey: Option<&Pubkey>) -> Result<()> {
                 match pubkey {
                     Some(pubkey) => {
                         self.cache_impl.invalidate(pubkey);
@@ -115,6 +342,145 @@ impl Cache {
                 Ok(())
             }
 
+            fn size(&self) -> Result<(u64, u64)> {
+                Ok((self.cache_impl.entry_count(), self.cache_impl.weighted_size()))
+            }
+
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::{AccountData, FetchStamp};
+    use crate::pubkey::generate_random_pubkey;
+    use crate::store::StoreEvent;
+
+    fn new_reference() -> AccountDataReference {
+        let account_data = AccountData::new_static(generate_random_pubkey(), Pubkey::default());
+        AccountDataReference::new(account_data)
+    }
+
+    fn fetched(commitment: Commitment, age: u64) -> Result<Arc<AccountDataReference>> {
+        let fetch_stamp = FetchStamp {
+            slot: None,
+            commitment,
+            timestamp: Instant(Instant::now()?.0 - age),
+        };
+        Ok(Arc::new(new_reference().with_fetch_stamp(fetch_stamp)))
+    }
+
+    #[test]
+    fn cache_freshness() -> Result<()> {
+        let cache = Cache::new_with_config(CacheConfig {
+            confirmed_freshness: Some(Duration::from_secs(10)),
+            stale_while_revalidate: Duration::from_secs(20),
+            ..Default::default()
+        });
+
+        let fresh = fetched(Commitment::Confirmed, 0)?;
+        let stale = fetched(Commitment::Confirmed, 15)?;
+        let expired = fetched(Commitment::Confirmed, 40)?;
+        // no freshness period is configured for processed data
+        let processed = fetched(Commitment::Processed, 40)?;
+        let unstamped = Arc::new(new_reference());
+        let restored = Arc::new(new_reference().with_restored(true));
+        let ahead = Arc::new(new_reference().with_fetch_stamp(FetchStamp {
+            slot: None,
+            commitment: Commitment::Confirmed,
+            timestamp: Instant(Instant::now()?.0 + 60),
+        }));
+        for reference in [
+            &fresh, &stale, &expired, &processed, &unstamped, &restored, &ahead,
+        ] {
+            cache.store(reference)?;
+        }
+
+        assert!(matches!(
+            cache.lookup_with_freshness(&fresh.key)?,
+            CacheLookup::Fresh(_)
+        ));
+        assert!(matches!(
+            cache.lookup_with_freshness(&stale.key)?,
+            CacheLookup::Stale(_)
+        ));
+        assert!(matches!(
+            cache.lookup_with_freshness(&expired.key)?,
+            CacheLookup::Miss
+        ));
+        // expired entries are discarded
+        assert!(cache.lookup(&expired.key)?.is_none());
+        assert!(matches!(
+            cache.lookup_with_freshness(&processed.key)?,
+            CacheLookup::Fresh(_)
+        ));
+        assert!(matches!(
+            cache.lookup_with_freshness(&unstamped.key)?,
+            CacheLookup::Fresh(_)
+        ));
+        assert!(matches!(
+            cache.lookup_with_freshness(&restored.key)?,
+            CacheLookup::Stale(_)
+        ));
+        // fetch stamps ahead of the local clock are fresh
+        assert!(matches!(
+            cache.lookup_with_freshness(&ahead.key)?,
+            CacheLookup::Fresh(_)
+        ));
+        assert!(matches!(
+            cache.lookup_with_freshness(&generate_random_pubkey())?,
+            CacheLookup::Miss
+        ));
+
+        let stats = cache.stats()?;
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.stale_hits, 2);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.expirations, 1);
+        assert_eq!(stats.inserts, 6);
+        assert_eq!(stats.invalidations, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn cache_time_to_live() -> Result<()> {
+        let cache = Cache::new_with_config(CacheConfig {
+            time_to_live: Some(Duration::from_millis(50)),
+            ..Default::default()
+        });
+
+        let reference = Arc::new(new_reference());
+        cache.store(&reference)?;
+        assert!(cache.lookup(&reference.key)?.is_some());
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(cache.lookup(&reference.key)?.is_none());
+
+        let stats = cache.stats()?;
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn cache_store_notifier_invalidation() -> Result<()> {
+        let cache = Arc::new(Cache::new_with_default_capacity());
+        let notifier = Notifier::new();
+        cache.bind_store_notifier(&notifier);
+
+        let reference = Arc::new(new_reference());
+        cache.store(&reference)?;
+        assert!(cache.lookup(&reference.key)?.is_some());
+
+        notifier.notify(StoreEvent::stored(&reference)?);
+        // invalidation is performed by a background task
+        for _ in 0..100 {
+            if cache.lookup(&reference.key)?.is_none() {
+                break;
+            }
+            async_std::task::sleep(Duration::from_millis(10)).await;
         }
+        assert!(cache.lookup(&reference.key)?.is_none());
+        assert_eq!(cache.stats()?.invalidations, 1);
+        Ok(())
     }
 }
@@ -8,6 +8,7 @@ use crate::result::Result;
 use crate::utils;
 use kaizen_macros::Meta;
 use solana_program::account_info::AccountInfo;
+use std::cmp::Ordering;
 use std::marker::PhantomData;
 use std::ops::{Index, IndexMut};
 use std::{cell::RefCell, mem, rc::Rc};
@@ -191,6 +192,27 @@ where
         self.segment.get_data_len()
     }
 
+    /// Number of records that fit in the reserved segment capacity
+    pub fn get_records_capacity(&self) -> usize {
+        (self.segment.get_capacity() - mem::size_of::<ArrayMeta>()) / mem::size_of::<T>()
+    }
+
+    ///
+    /// Reserves segment capacity for up to `additional` records, allowing
+    /// subsequent inserts without displacing trailing segments. The reservation
+    /// is clamped to the account data increase permitted within a single instruction.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_reserve(&self, additional: usize) -> Result<()> {
+        self.segment
+            .try_reserve(Array::<T>::calculate_data_len(self.len() + additional))?;
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn init_len(&mut self, records: usize) {
         self.get_meta().records = records as u32;
@@ -356,47 +378,27 @@ where
         let new_byte_len = self.get_byte_offset_at_idx(records_after);
 
         if new_byte_len > capacity {
-            log_trace!("[linear store] resizing...  current: {} bytes,  new: {} bytes, delta: {} size_of<T>: {}", 
+            log_trace!(
+                "[linear store] resizing...  current: {} bytes,  new: {} bytes, delta: {} size_of<T>: {}",
                 capacity,
                 new_byte_len,
-                new_byte_len-capacity,
+                new_byte_len - capacity,
                 mem::size_of::<T>()
             );
-
-            // log_trace!("account before: {:#?}",self.account);
-
-            // println!("~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-");
-            // let meta = self.get_meta();
-            // log_trace!("meta A: {:?}", meta);
-            // let test_index = self.segment.store.get_index::<u16>();
-            // log_trace!("test index: {:?}", test_index);
-            // println!("~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-~-");
-
-            // log_trace!("volatile_try_insert_at");
             self.segment.try_resize(new_byte_len, zero_init)?;
-            // log_trace!("account after: {:#?}",self.account);
-            // let meta = self.get_meta();
-            // log_trace!("meta B: {:?}", meta);
-            // log_trace!("resizing ... A");
-            if idx < records_before {
-                let segment_offset = self.segment.get_offset();
-                // log_trace!("resizing ... B");
-                // let data_offset = self.get_data_offset();
-                let mut data = self.account.data.borrow_mut();
-                let from = segment_offset + self.get_byte_offset_at_idx(idx);
-                let to = segment_offset + self.get_byte_offset_at_idx(records_before);
-                let dest = from + mem::size_of::<T>();
-                // log_trace!("Array<T> resizing where size_of<T> is: {}", mem::size_of::<T>());
-                data[..].copy_within(from..to, dest);
-            } else {
-                // log_trace!("+ + + + + + + + + + + + %%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%% T SIZE IS: {}", mem::size_of::<T>());
-            }
-        } else {
-            // log_trace!("resizing ... C");
-            log_warning!("[linear store] segment shrinking is not implemented");
-            // we do nothing for now
-            // todo!("reduction of account during try_insert is not implemented");
         }
+
+        // segment data may exceed the used records (e.g. after a removal without realloc),
+        // so trailing records are shifted regardless of the resize
+        if idx < records_before {
+            let segment_offset = self.segment.get_offset();
+            let mut data = self.account.data.borrow_mut();
+            let from = segment_offset + self.get_byte_offset_at_idx(idx);
+            let to = segment_offset + self.get_byte_offset_at_idx(records_before);
+            let dest = from + mem::size_of::<T>();
+            data[..].copy_within(from..to, dest);
+        }
+
         // log_trace!("resizing ... D");
 
         let meta = self.get_meta();
@@ -449,59 +451,145 @@ where
         }
     }
 
-    pub fn try_remove_at_swap_last(
+    ///
+    /// Removes the record at `idx` by replacing it with the last record
+    /// (does not preserve the ordering of records).
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove_at_swap_last(
         &self,
         idx: usize,
         realloc: bool,
         zero_init: bool,
     ) -> Result<()> {
-        // FIXME: finish try_remove_at_swap_last implementation!
         if idx >= self.len() {
             return Err(ErrorCode::MappedArrayBounds.into());
         }
 
         let meta = self.get_meta();
-        let records = meta.records as usize;
-        let _data_offset = self.get_data_offset();
-        // todo!("try_remove_at_swap_last");
-        if records > 1 && idx + 1 < records {
-            // FIXME finish implementation
-        }
-
-        // let new_len = {
-        let meta = self.get_meta();
-        let mut records = meta.records as usize;
+        let records = meta.records as usize - 1;
         let data_offset = self.get_data_offset();
+        let last = data_offset + records * mem::size_of::<T>();
 
-        if records > 1 && idx + 1 < records {
+        if idx < records {
             let dest = data_offset + idx * mem::size_of::<T>();
-            let src = data_offset + (records - 1) * mem::size_of::<T>();
-            // let last = data_offset + records*mem::size_of::<T>();
             let mut data = self.account.data.borrow_mut();
-            data[..].copy_within(src..(src + mem::size_of::<T>()), dest);
+            data[..].copy_within(last..(last + mem::size_of::<T>()), dest);
         }
 
-        records -= 1;
         meta.records = records as u32;
-        let new_len = mem::size_of::<ArrayMeta>() + records * mem::size_of::<T>();
-        // };
 
         if realloc {
             log_trace!("try remove at swap last");
-            self.segment.try_resize(new_len, zero_init)
+            self.segment
+                .try_resize(Array::<T>::calculate_data_len(records), zero_init)
         } else {
             if zero_init {
-                utils::fill_account_buffer_u8(
-                    self.account,
-                    new_len..new_len + mem::size_of::<T>(),
-                    0,
-                );
+                utils::fill_account_buffer_u8(self.account, last..last + mem::size_of::<T>(), 0);
             }
 
             Ok(())
         }
     }
 
+    ///
+    /// Removes the record at `idx` by replacing it with the last record
+    /// (does not preserve the ordering of records). The segment is not
+    /// resized, leaving the space of the removed record as spare capacity.
+    ///
+    pub fn try_swap_remove(&self, idx: usize, zero_init: bool) -> Result<()> {
+        // without reallocation segment data is not displaced
+        unsafe { self.try_remove_at_swap_last(idx, false, zero_init) }
+    }
+
+    ///
+    /// Appends all records from the slice, resizing the segment once.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_extend_from_slice(&self, records: &[T]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let records_before = self.len();
+        self.try_resize_for_items(records_before + records.len(), false)?;
+        self.as_slice_mut()[records_before..].copy_from_slice(records);
+        Ok(())
+    }
+
+    ///
+    /// Retains only the records for which the predicate returns `true`,
+    /// preserving their order. Returns the number of removed records.
+    /// If `realloc` is set, the segment is resized once after compaction.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_retain<F>(&self, mut f: F, realloc: bool) -> Result<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let records = self.as_slice_mut();
+        let mut retained = 0;
+        for idx in 0..records.len() {
+            if f(&records[idx]) {
+                if idx != retained {
+                    records[retained] = records[idx];
+                }
+                retained += 1;
+            }
+        }
+
+        self.try_truncate(retained, realloc)?;
+        Ok(records.len() - retained)
+    }
+
+    ///
+    /// Reduces the number of records to `records`. If `realloc` is set,
+    /// the segment is resized to fit the remaining records.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_truncate(&self, records: usize, realloc: bool) -> Result<()> {
+        if records >= self.len() {
+            return Ok(());
+        }
+
+        self.get_meta().records = records as u32;
+        if realloc {
+            self.segment
+                .try_resize(Array::<T>::calculate_data_len(records), false)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn iter_mut(&self) -> std::slice::IterMut<'info, T> {
+        self.as_slice_mut().iter_mut()
+    }
+
+    /// Sorts records in place using the comparator function
+    /// (unstable sort; does not allocate).
+    pub fn sort_by<F>(&self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.as_slice_mut().sort_unstable_by(compare)
+    }
+
     pub fn iter(&self) -> MappedArrayIterator<'info, T> {
         MappedArrayIterator {
             offset: self.get_offset(),
@@ -520,6 +608,55 @@ where
     pub fn binary_search(&self, value: &T) -> std::result::Result<usize, usize> {
         self.as_slice().binary_search(value)
     }
+
+    /// Sorts records in place (unstable sort; does not allocate).
+    pub fn sort(&self) {
+        self.as_slice_mut().sort_unstable()
+    }
+
+    ///
+    /// Inserts the record preserving the sort order of the array,
+    /// returning the index of the inserted record.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_insert_sorted(&self, record: &T) -> Result<usize> {
+        let idx = match self.binary_search(record) {
+            Ok(idx) | Err(idx) => idx,
+        };
+        self.try_insert_at(idx, record)?;
+        Ok(idx)
+    }
+
+    ///
+    /// Removes consecutive duplicate records (all duplicates if the array is sorted).
+    /// Returns the number of removed records.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_dedup(&self, realloc: bool) -> Result<usize> {
+        let records = self.as_slice_mut();
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        let mut retained = 1;
+        for idx in 1..records.len() {
+            if records[idx] != records[retained - 1] {
+                records[retained] = records[idx];
+                retained += 1;
+            }
+        }
+
+        self.try_truncate(retained, realloc)?;
+        Ok(records.len() - retained)
+    }
 }
 
 impl<'info, 'refs, T> Index<usize> for Array<'info, 'refs, T>
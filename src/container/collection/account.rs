@@ -68,6 +68,36 @@ where
         )
     }
 
+    /// Creates a collection embedded in container meta that tracks
+    /// removed entries in the supplied `tombstones` segment
+    pub fn try_create_from_meta_with_tombstones<'refs>(
+        data: &'info mut PdaCollectionMeta,
+        tombstones: Rc<Segment<'info, 'refs>>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Result<PdaCollectionInterface<'info, PdaCollectionMetaTombstoneInterface<'info, 'refs>>>
+    {
+        PdaCollectionInterface::<PdaCollectionMetaTombstoneInterface>::try_create_impl(
+            tombstones.account().key.as_ref(),
+            PdaCollectionMetaTombstoneInterface::new(data, tombstones, seed, container_type),
+        )
+    }
+
+    /// Loads a collection embedded in container meta that tracks
+    /// removed entries in the supplied `tombstones` segment
+    pub fn try_load_from_meta_with_tombstones<'refs>(
+        data: &'info mut PdaCollectionMeta,
+        tombstones: Rc<Segment<'info, 'refs>>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Result<PdaCollectionInterface<'info, PdaCollectionMetaTombstoneInterface<'info, 'refs>>>
+    {
+        PdaCollectionInterface::<PdaCollectionMetaTombstoneInterface>::try_load_impl(
+            tombstones.account().key.as_ref(),
+            PdaCollectionMetaTombstoneInterface::new(data, tombstones, seed, container_type),
+        )
+    }
+
     pub fn try_create_from_segment_with_collection_args<'refs>(
         segment: Rc<Segment<'info, 'refs>>,
         seed: &'static [u8],
@@ -98,6 +128,7 @@ where
         self.meta.try_create() //seed, container_type)
     }
 
+    /// Number of indices allocated by the collection (including removed entries)
     pub fn len(&self) -> usize {
         self.meta.get_len() as usize
     }
@@ -106,6 +137,21 @@ where
         self.meta.get_len() == 0
     }
 
+    /// Number of removed (tombstoned) entries
+    pub fn removed_len(&self) -> usize {
+        self.meta.get_removed() as usize
+    }
+
+    /// Number of entries that have not been removed
+    pub fn live_len(&self) -> usize {
+        (self.meta.get_len() - self.meta.get_removed()) as usize
+    }
+
+    /// Returns `true` if the entry at `idx` has been removed (tombstoned)
+    pub fn is_removed(&self, idx: u64) -> bool {
+        self.meta.is_removed(idx)
+    }
+
     // pub fn get_seed_at(&self, idx : u64) -> Vec<u8> {
     //     let domain = self.domain;
     //     let index_bytes: [u8; 8] = unsafe { std::mem::transmute(idx.to_be()) };
@@ -161,6 +207,35 @@ where
         }
     }
 
+    /// Loads the entry at `index`, verifying the account address against
+    /// the collection seeds (derived from the address of the container
+    /// owning the collection, allowing navigation of collection trees).
+    pub fn try_load_container_at<'refs, T>(
+        &self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        index: u64,
+        seed_bump: u8,
+        account: &'refs AccountInfo<'info>,
+    ) -> Result<<T as Container<'info, 'refs>>::T>
+    where
+        T: Container<'info, 'refs>,
+    {
+        if index >= self.meta.get_len() || self.meta.is_removed(index) {
+            return Err(error_code!(ErrorCode::AccountCollectionNotFound).with_account(account.key));
+        }
+
+        let seed_bump = [seed_bump];
+        let seeds = self.get_seed_at(&index, Some(&seed_bump));
+        let pda = Pubkey::create_program_address(&seeds, ctx.program_id)?;
+        if account.key != &pda {
+            return Err(
+                error_code!(ErrorCode::AccountCollectionInvalidAddress).with_account(account.key)
+            );
+        }
+
+        T::try_load(account)
+    }
+
     pub fn try_create_container<'refs, T>(
         &mut self,
         ctx: &ContextReference<'info, 'refs, '_, '_>,
@@ -200,6 +275,91 @@ where
         Ok(container)
     }
 
+    /// Creates a container from one of the candidate templates supplied by
+    /// [`InstructionBuilder::with_collection_template_candidates()`](crate::builder::InstructionBuilder::with_collection_template_candidates).
+    /// The candidate matching the collection length at the time of the program
+    /// execution is selected, allowing multiple users to create collection
+    /// entries in parallel (as long as the number of entries created concurrently
+    /// does not exceed the number of supplied candidates).
+    pub fn try_create_container_from_candidates<'refs, T>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        data_len: Option<usize>,
+    ) -> Result<<T as Container<'info, 'refs>>::T>
+    where
+        T: Container<'info, 'refs>,
+    {
+        let candidates = ctx.try_consume_collection_template_candidates()?;
+
+        let next_index = self.meta.get_len();
+        let mut selected = None;
+        for (bump, tpl_account_info) in candidates.into_iter() {
+            let seed_bump = [bump];
+            let tpl_seeds = self.get_seed_at(&next_index, Some(&seed_bump));
+            if let Ok(pda) = Pubkey::create_program_address(&tpl_seeds, ctx.program_id) {
+                if tpl_account_info.key == &pda {
+                    selected = Some((bump, tpl_account_info));
+                    break;
+                }
+            }
+        }
+
+        let (bump, tpl_account_info) = selected.ok_or_else(|| {
+            error_code!(ErrorCode::AccountCollectionCandidateNotFound).with_message(&format!(
+                "no collection template candidate for index {next_index}"
+            ))
+        })?;
+
+        self.try_create_container::<T>(ctx, &[bump], tpl_account_info, data_len)
+    }
+
+    /// Removes the entry at `index`, closing its account and transferring
+    /// the account lamports to the `recipient`. The index is tombstoned:
+    /// it is never reused and accessors skip it. Tombstones are recorded
+    /// in the collection segment; collections embedded in container meta
+    /// require a tombstone segment (see [`Self::try_load_from_meta_with_tombstones()`]).
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove_container<'refs>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        index: u64,
+        seed_bump: u8,
+        account: &'refs AccountInfo<'info>,
+        recipient: &'refs AccountInfo<'info>,
+    ) -> Result<()> {
+        if index >= self.meta.get_len() || self.meta.is_removed(index) {
+            return Err(error_code!(ErrorCode::AccountCollectionNotFound).with_account(account.key));
+        }
+
+        let seed_bump = &[seed_bump];
+        let seeds = self.get_seed_at(&index, Some(seed_bump));
+        let pda = Pubkey::create_program_address(&seeds, ctx.program_id)?;
+        if account.key != &pda {
+            return Err(
+                error_code!(ErrorCode::AccountCollectionInvalidAddress).with_account(account.key)
+            );
+        }
+
+        if let Some(container_type) = self.meta.get_container_type() {
+            if container::try_get_container_type(account)? != container_type {
+                return Err(
+                    error_code!(ErrorCode::AccountCollectionInvalidContainerType)
+                        .with_account(account.key),
+                );
+            }
+        }
+
+        self.meta.try_set_removed(index)?;
+        ctx.try_close_account(account, recipient)?;
+
+        Ok(())
+    }
+
     pub fn try_insert_container<'refs, T>(
         &mut self,
         ctx: &ContextReference<'info, 'refs, '_, '_>,
@@ -215,6 +375,9 @@ where
             }
         }
 
+        // inserted containers are expected at the address derived from `len + 1`
+        // (unlike `try_create_container()` which derives it from `len`); this is
+        // kept as-is since existing collections already hold entries at these addresses
         let next_index = self.meta.get_len() + 1;
         let seed_bump = &[seed_bump];
         let tpl_seeds = self.get_seed_at(&next_index, Some(seed_bump));
@@ -253,12 +416,91 @@ where
 
         Ok(())
     }
+
+    /// Creates a container from the collection template candidates (see
+    /// [`try_create_container_from_candidates()`](Self::try_create_container_from_candidates)),
+    /// initializing it using `init` before recording the value of its indexed field in the `index`
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_create_container_from_candidates_with_index<'refs, T, K, F>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        data_len: Option<usize>,
+        index: &CollectionIndex<'_, '_, K>,
+        init: F,
+    ) -> Result<T>
+    where
+        T: Container<'info, 'refs, T = T> + IndexKey<K>,
+        K: Copy + Ord,
+        F: FnOnce(&mut T) -> Result<()>,
+    {
+        let mut container = self.try_create_container_from_candidates::<T>(ctx, data_len)?;
+        init(&mut container)?;
+        index.try_insert(&container.index_key(), container.pubkey())?;
+        Ok(container)
+    }
+
+    /// Inserts the container (see [`try_insert_container()`](Self::try_insert_container)),
+    /// recording the value of its indexed field in the `index`
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_insert_container_with_index<'refs, T, K>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        seed_bump: u8,
+        container: &T,
+        index: &CollectionIndex<'_, '_, K>,
+    ) -> Result<()>
+    where
+        T: Container<'info, 'refs> + IndexKey<K>,
+        K: Copy + Ord,
+    {
+        self.try_insert_container(ctx, seed_bump, container)?;
+        index.try_insert_container(container)
+    }
+
+    /// Removes the entry at `index` (see [`try_remove_container()`](Self::try_remove_container)),
+    /// along with its record in the `collection_index`. The indexed field value
+    /// is obtained from the container before its account is closed.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove_container_with_index<'refs, T, K>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        index: u64,
+        seed_bump: u8,
+        account: &'refs AccountInfo<'info>,
+        recipient: &'refs AccountInfo<'info>,
+        collection_index: &CollectionIndex<'_, '_, K>,
+    ) -> Result<()>
+    where
+        T: Container<'info, 'refs>,
+        <T as Container<'info, 'refs>>::T: IndexKey<K>,
+        K: Copy + Ord,
+    {
+        let key = T::try_load(account)?.index_key();
+        self.try_remove_container(ctx, index, seed_bump, account, recipient)?;
+        collection_index.try_remove(&key, account.key)
+    }
 }
 
 cfg_if! {
     if #[cfg(not(target_os = "solana"))] {
 
-        use futures::{stream::FuturesOrdered, StreamExt};
+        use futures::{stream::FuturesOrdered, Stream, StreamExt};
+        use super::stream::{paginate, Page, PageOptions};
         use crate::container::interfaces::{
             PdaCollectionCreatorInterface,
             AsyncPdaCollectionCreatorInterface,
@@ -291,10 +533,16 @@ cfg_if! {
                 self.load_container_at_with_transport::<T>(program_id, idx, &transport).await
             }
 
+            /// Loads the entry at `idx`, returning `None` if the entry has been removed
+            /// (tombstoned entries are resolved from the collection without a lookup)
             pub async fn load_container_at_with_transport<'this,T>(&self, program_id: &Pubkey, idx: u64, transport: &Arc<Transport>)
             -> Result<Option<ContainerReference<'this,T>>>
             where T: kaizen::container::Container<'this,'this>
             {
+                if self.is_removed(idx) {
+                    return Ok(None);
+                }
+
                 let container_pubkey = self.get_pubkey_at(program_id, idx)?;
                 load_container_with_transport::<T>(transport,&container_pubkey).await
             }
@@ -319,6 +567,73 @@ cfg_if! {
 
                 Ok(futures.collect::<Vec<_>>().await)
             }
+
+            /// Creates a paginated stream of containers held by the collection
+            pub fn stream<'a,'this,T>(&'a self, program_id: &Pubkey, options: PageOptions)
+            -> Result<impl Stream<Item = Result<Page<'this,T>>> + 'a>
+            where 'this: 'a, T: kaizen::container::Container<'this,'this> + 'a
+            {
+                let transport = Transport::global()?;
+                Ok(self.stream_with_transport::<T>(program_id, options, &transport))
+            }
+
+            pub fn stream_with_transport<'a,'this,T>(&'a self, program_id: &Pubkey, options: PageOptions, transport: &Arc<Transport>)
+            -> impl Stream<Item = Result<Page<'this,T>>> + 'a
+            where 'this: 'a, T: kaizen::container::Container<'this,'this> + 'a
+            {
+                let program_id = *program_id;
+                let transport = transport.clone();
+                paginate(self.len(), options, move |range: std::ops::Range<usize>| {
+                    let transport = transport.clone();
+                    async move {
+                        let range = range.start as u64..range.end as u64;
+                        self.load_container_range_with_transport::<T>(&program_id, range, &transport)
+                            .await?
+                            .into_iter()
+                            .collect::<Result<Vec<_>>>()
+                    }
+                })
+            }
+
+            /// Loads containers in the given range, skipping removed (tombstoned) entries
+            pub async fn load_live_container_range<'this,T>(&self, program_id: &Pubkey, range: std::ops::Range<u64>)
+            -> Result<Vec<(u64, ContainerReference<'this,T>)>>
+            where T: kaizen::container::Container<'this,'this>
+            {
+                let transport = Transport::global()?;
+                self.load_live_container_range_with_transport::<T>(program_id, range, &transport).await
+            }
+
+            pub async fn load_live_container_range_with_transport<'this,T>(&self, program_id: &Pubkey, range: std::ops::Range<u64>, transport: &Arc<Transport>)
+            -> Result<Vec<(u64, ContainerReference<'this,T>)>>
+            where T: kaizen::container::Container<'this,'this>
+            {
+                let mut futures = FuturesOrdered::new();
+                for idx in range.filter(|idx| !self.is_removed(*idx)) {
+                    futures.push_back(async move {
+                        let container = self
+                            .load_container_at_with_transport::<T>(program_id, idx, transport)
+                            .await?
+                            .ok_or_else(|| error_code!(ErrorCode::AccountCollectionNotFound)
+                                .with_message(&format!("collection entry {idx} not found")))?;
+                        Result::Ok((idx, container))
+                    });
+                }
+
+                futures.collect::<Vec<_>>().await.into_iter().collect()
+            }
+
+            /// Creates an accessor for the given range, skipping removed (tombstoned) entries
+            pub fn live_accessor(&self, program_id: &Pubkey, index_range: std::ops::Range<usize>)
+            -> Result<Arc<PdaCollectionAccessor>>
+            {
+                let mut list = Vec::new();
+                for idx in index_range.filter(|idx| !self.is_removed(*idx as u64)) {
+                    list.push(self.get_pubkey_at(program_id, idx as u64)?);
+                }
+
+                Ok(Arc::new(PdaCollectionAccessor { list }))
+            }
         }
 
         // ~~~
@@ -342,6 +657,12 @@ cfg_if! {
             list : Vec<(Pubkey, u8)>
         }
 
+        impl PdaCollectionCreator {
+            pub fn new(list : Vec<(Pubkey, u8)>) -> Self {
+                PdaCollectionCreator { list }
+            }
+        }
+
         #[workflow_async_trait]
         impl AsyncPdaCollectionCreatorInterface for PdaCollectionCreator
         {
@@ -397,3 +718,337 @@ cfg_if! {
 
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use kaizen::container::Containers;
+    use kaizen::emulator::interface::EmulatorInterface;
+    use kaizen::emulator::Simulator;
+    use kaizen::prelude::*;
+    use kaizen::result::Result;
+
+    #[container(Containers::CollectionTestInterface)]
+    pub struct CollectionTestRoot<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        #[collection(seed(b"items"), container(CollectionTestItem))]
+        pub items: PdaCollection<'info, 'refs>,
+    }
+
+    #[container(Containers::PDATestInterface)]
+    pub struct CollectionTestItem<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        pub records: Array<'info, 'refs, u64>,
+    }
+
+    impl IndexKey<u64> for CollectionTestItem<'_, '_> {
+        fn index_key(&self) -> u64 {
+            self.records.as_slice().first().copied().unwrap_or(0)
+        }
+    }
+
+    #[container(Containers::IndexedCollectionTestInterface)]
+    pub struct IndexedCollectionTestRoot<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        #[collection(seed(b"items"), container(CollectionTestItem), index(by_record))]
+        pub items: PdaCollection<'info, 'refs>,
+        pub by_record: CollectionIndex<'info, 'refs, u64>,
+    }
+
+    #[async_std::test]
+    async fn pda_collection_parallel_creation() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_sequence(0u64);
+
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_generic_account_templates(1)
+            .seal()?;
+        let root = builder.generic_template_accounts()[0].pubkey;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let allocation_args = AccountAllocationArgs::new(AddressDomain::Default);
+                let account =
+                    ctx.try_create_pda(CollectionTestRoot::initial_data_len(), &allocation_args)?;
+                CollectionTestRoot::try_create(account)?;
+                Ok(())
+            })
+            .await?;
+
+        // two users build their instructions against the same collection state
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let mut builders = Vec::new();
+        for _ in 0..2 {
+            let mut account_data = reference.account_data.lock()?;
+            let account_info = account_data.into_account_info();
+            let container = CollectionTestRoot::try_load(&account_info)?;
+            let builder = InstructionBuilder::new_with_config_for_testing(&config)
+                .with_handler_accounts(&[AccountMeta::new(root, false)])
+                .with_collection_template_candidates(&container.items, 2)
+                .await?
+                .seal()?;
+            builders.push(builder);
+        }
+
+        let candidates = builders[0].collection_template_accounts();
+        assert_eq!(candidates, builders[1].collection_template_accounts());
+
+        for builder in builders {
+            simulator
+                .execute_handler(builder, |ctx: &ContextReference| {
+                    let mut root = CollectionTestRoot::try_load(&ctx.handler_accounts[0])?;
+                    root.items
+                        .try_create_container_from_candidates::<CollectionTestItem>(ctx, None)?;
+                    Ok(())
+                })
+                .await?;
+        }
+
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        let container = CollectionTestRoot::try_load(&account_info)?;
+        assert_eq!(container.items.len(), 2);
+
+        for candidate in candidates.iter() {
+            let reference = simulator
+                .lookup(&candidate.pubkey)
+                .await?
+                .expect("missing collection account");
+            let container_type = reference.account_data.lock()?.container_type();
+            assert_eq!(container_type, Some(CollectionTestItem::CONTAINER_TYPE));
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn pda_collection_removal() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_sequence(0u64);
+
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_generic_account_templates(1)
+            .seal()?;
+        let root = builder.generic_template_accounts()[0].pubkey;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let allocation_args = AccountAllocationArgs::new(AddressDomain::Default);
+                let account =
+                    ctx.try_create_pda(CollectionTestRoot::initial_data_len(), &allocation_args)?;
+                CollectionTestRoot::try_create(account)?;
+                Ok(())
+            })
+            .await?;
+
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let (builder, removed) = {
+            let mut account_data = reference.account_data.lock()?;
+            let account_info = account_data.into_account_info();
+            let container = CollectionTestRoot::try_load(&account_info)?;
+            let builder = InstructionBuilder::new_with_config_for_testing(&config)
+                .with_handler_accounts(&[AccountMeta::new(root, false)])
+                .with_collection_templates(&container.items, 2)
+                .await?
+                .seal()?;
+            (
+                builder,
+                container.items.get_pda_at(&simulator.program_id(), 0)?,
+            )
+        };
+        let items = builder.collection_template_accounts();
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let mut root = CollectionTestRoot::try_load(&ctx.handler_accounts[0])?;
+                for _ in 0..2 {
+                    root.items
+                        .try_create_container_from_candidates::<CollectionTestItem>(ctx, None)?;
+                }
+                Ok(())
+            })
+            .await?;
+
+        let (removed_pubkey, removed_bump) = removed;
+        assert_eq!(items[0].pubkey, removed_pubkey);
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_handler_accounts(&[
+                AccountMeta::new(root, false),
+                AccountMeta::new(removed_pubkey, false),
+            ])
+            .with_instruction_data(&[removed_bump])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let mut root = CollectionTestRoot::try_load(&ctx.handler_accounts[0])?;
+                unsafe {
+                    root.items.try_remove_container(
+                        ctx,
+                        0,
+                        ctx.instruction_data[0],
+                        &ctx.handler_accounts[1],
+                        ctx.authority,
+                    )?
+                };
+                Ok(())
+            })
+            .await?;
+
+        assert!(simulator.lookup(&removed_pubkey).await?.is_none());
+        assert!(simulator.lookup(&items[1].pubkey).await?.is_some());
+
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        let container = CollectionTestRoot::try_load(&account_info)?;
+        assert_eq!(container.items.len(), 2);
+        assert_eq!(container.items.removed_len(), 1);
+        assert_eq!(container.items.live_len(), 1);
+        assert!(container.items.is_removed(0));
+        assert!(!container.items.is_removed(1));
+        let accessor = container
+            .items
+            .live_accessor(&simulator.program_id(), 0..2)?;
+        assert_eq!(accessor.list, vec![items[1].pubkey]);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn pda_collection_declared_index() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_sequence(0u64);
+
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_generic_account_templates(1)
+            .seal()?;
+        let root = builder.generic_template_accounts()[0].pubkey;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let allocation_args = AccountAllocationArgs::new(AddressDomain::Default);
+                let account = ctx.try_create_pda(
+                    IndexedCollectionTestRoot::initial_data_len(),
+                    &allocation_args,
+                )?;
+                IndexedCollectionTestRoot::try_create(account)?;
+                Ok(())
+            })
+            .await?;
+
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let (builder, removed) = {
+            let mut account_data = reference.account_data.lock()?;
+            let account_info = account_data.into_account_info();
+            let container = IndexedCollectionTestRoot::try_load(&account_info)?;
+            let builder = InstructionBuilder::new_with_config_for_testing(&config)
+                .with_handler_accounts(&[AccountMeta::new(root, false)])
+                .with_collection_templates(&container.items, 2)
+                .await?
+                .seal()?;
+            (
+                builder,
+                container.items.get_pda_at(&simulator.program_id(), 0)?,
+            )
+        };
+        let items = builder.collection_template_accounts();
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let mut root = IndexedCollectionTestRoot::try_load(&ctx.handler_accounts[0])?;
+                for value in [20u64, 10] {
+                    unsafe {
+                        root.try_create_items_container::<CollectionTestItem, _>(
+                            ctx,
+                            None,
+                            |item| item.records.try_insert(&value),
+                        )?;
+                    }
+                }
+                Ok(())
+            })
+            .await?;
+
+        {
+            let reference = simulator
+                .lookup(&root)
+                .await?
+                .expect("missing root account");
+            let mut account_data = reference.account_data.lock()?;
+            let account_info = account_data.into_account_info();
+            let container = IndexedCollectionTestRoot::try_load(&account_info)?;
+            assert_eq!(
+                container.by_record.range_pubkeys(..),
+                vec![items[1].pubkey, items[0].pubkey]
+            );
+            assert_eq!(
+                container.by_record.range_pubkeys(15..),
+                vec![items[0].pubkey]
+            );
+        }
+
+        let (removed_pubkey, removed_bump) = removed;
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_handler_accounts(&[
+                AccountMeta::new(root, false),
+                AccountMeta::new(removed_pubkey, false),
+            ])
+            .with_instruction_data(&[removed_bump])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let mut root = IndexedCollectionTestRoot::try_load(&ctx.handler_accounts[0])?;
+                unsafe {
+                    root.try_remove_items_container::<CollectionTestItem>(
+                        ctx,
+                        0,
+                        ctx.instruction_data[0],
+                        &ctx.handler_accounts[1],
+                        ctx.authority,
+                    )?;
+                }
+                Ok(())
+            })
+            .await?;
+
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        let container = IndexedCollectionTestRoot::try_load(&account_info)?;
+        assert_eq!(container.by_record.range_pubkeys(..), vec![items[1].pubkey]);
+        assert_eq!(container.items.live_len(), 1);
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 //! CollectionMeta traits used by collection interfaces.
 //!
 use crate::result::Result;
+use kaizen::error::ErrorCode;
 use kaizen::prelude::*;
 use kaizen_macros::Meta;
 use std::cmp::Ordering;
@@ -13,6 +14,18 @@ pub trait CollectionMeta {
     fn get_seed(&self) -> &[u8]; //Vec<u8>;
     fn get_len(&self) -> u64;
     fn set_len(&mut self, _len: u64);
+    /// Number of removed (tombstoned) entries
+    fn get_removed(&self) -> u64;
+    /// Returns `true` if the entry at `idx` has been removed
+    fn is_removed(&self, _idx: u64) -> bool;
+    /// Tombstones the entry at `idx`
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    unsafe fn try_set_removed(&mut self, _idx: u64) -> Result<()>;
     fn get_container_type(&self) -> Option<u32>;
 }
 
@@ -24,6 +37,105 @@ pub struct PdaCollectionMeta {
     // collection_container_type : u32,
 }
 
+/// Removal record preceding the tombstone bitmap (see [`CollectionTombstones`]),
+/// holding the number of removed entries (indices below the collection length
+/// whose accounts have been closed).
+#[derive(Meta, Copy, Clone)]
+#[repr(packed)]
+pub struct PdaCollectionRemovedMeta {
+    collection_removed: u64,
+}
+
+/// Returns `true` if `idx` is marked in the tombstone `bitmap`
+/// (indices past the end of the bitmap have not been removed)
+pub fn is_tombstoned(bitmap: &[u8], idx: u64) -> bool {
+    bitmap
+        .get((idx / 8) as usize)
+        .map(|byte| byte & (1 << (idx % 8)) != 0)
+        .unwrap_or(false)
+}
+
+/// Tombstones of removed collection entries held by a segment starting at
+/// `offset`: a [`PdaCollectionRemovedMeta`] record followed by a bitmap holding
+/// a bit per collection index. The bitmap covers indices up to the highest
+/// removed index and the segment is grown as entries are removed. Segments
+/// created before removal tracking was introduced hold neither and are
+/// extended by the first removal.
+#[derive(Debug)]
+pub struct CollectionTombstones<'info, 'refs> {
+    segment: Rc<Segment<'info, 'refs>>,
+    offset: usize,
+}
+
+impl<'info, 'refs> CollectionTombstones<'info, 'refs> {
+    pub fn new(segment: Rc<Segment<'info, 'refs>>, offset: usize) -> Self {
+        Self { segment, offset }
+    }
+
+    fn removed_meta(&self) -> Option<&mut PdaCollectionRemovedMeta> {
+        let data = self.segment.as_ref_mut_u8();
+        if data.len() < self.offset + std::mem::size_of::<PdaCollectionRemovedMeta>() {
+            None
+        } else {
+            Some(unsafe {
+                &mut *data[self.offset..]
+                    .as_mut_ptr()
+                    .cast::<PdaCollectionRemovedMeta>()
+            })
+        }
+    }
+
+    fn bitmap_offset(&self) -> usize {
+        self.offset + std::mem::size_of::<PdaCollectionRemovedMeta>()
+    }
+
+    pub fn bitmap(&self) -> &[u8] {
+        self.segment
+            .as_ref_u8()
+            .get(self.bitmap_offset()..)
+            .unwrap_or_default()
+    }
+
+    pub fn get_removed(&self) -> u64 {
+        self.removed_meta()
+            .map(|removed_meta| removed_meta.get_collection_removed())
+            .unwrap_or(0)
+    }
+
+    pub fn is_removed(&self, idx: u64) -> bool {
+        is_tombstoned(self.bitmap(), idx)
+    }
+
+    /// Tombstones the entry at `idx`, growing the segment to cover `idx` if needed
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_set_removed(&self, idx: u64) -> Result<()> {
+        if self.is_removed(idx) {
+            return Err(error_code!(ErrorCode::AccountCollectionNotFound)
+                .with_message(&format!("collection entry {idx} has already been removed")));
+        }
+
+        let byte = (idx / 8) as usize;
+        let data_len = self.bitmap_offset() + byte + 1;
+        if self.segment.get_data_len() < data_len {
+            self.segment.try_resize(data_len, true)?;
+        }
+
+        let removed_meta = self.removed_meta().ok_or_else(|| {
+            error_code!(ErrorCode::AccountCollectionMetaSegmentSizeTooSmall)
+                .with_message("collection segment has no removal record")
+        })?;
+        removed_meta.set_collection_removed(removed_meta.get_collection_removed() + 1);
+        let offset = self.bitmap_offset();
+        self.segment.as_ref_mut_u8()[offset + byte] |= 1 << (idx % 8);
+        Ok(())
+    }
+}
+
 impl PdaCollectionMeta {
     fn try_create(&mut self) -> Result<()> {
         // }, seed_src : &[u8], container_type : Option<u32>) -> Result<()> {
@@ -119,15 +231,103 @@ impl<'info> CollectionMeta for PdaCollectionMetaInterface<'info> {
         self.data_mut().set_len(len);
     }
 
+    // collections embedded in container meta have no room for tombstones
+    // (see `PdaCollectionMetaTombstoneInterface`)
+    fn get_removed(&self) -> u64 {
+        0
+    }
+
+    fn is_removed(&self, _idx: u64) -> bool {
+        false
+    }
+
+    unsafe fn try_set_removed(&mut self, _idx: u64) -> Result<()> {
+        Err(error_code!(ErrorCode::AccountCollectionMissingMeta).with_message(
+            "removal requires a tombstone segment (see PdaCollectionInterface::try_load_from_meta_with_tombstones())",
+        ))
+    }
+
     fn get_container_type(&self) -> Option<u32> {
         self.container_type
         //        self.data_ref().get_container_type()
     }
 }
 
+/// Collection embedded in container meta, tracking removed entries
+/// using a dedicated tombstone segment
+pub struct PdaCollectionMetaTombstoneInterface<'info, 'refs> {
+    data: &'info mut PdaCollectionMeta,
+    tombstones: CollectionTombstones<'info, 'refs>,
+    seed: &'static [u8],
+    container_type: Option<u32>,
+}
+
+impl<'info, 'refs> PdaCollectionMetaTombstoneInterface<'info, 'refs> {
+    pub fn new(
+        data: &'info mut PdaCollectionMeta,
+        tombstones: Rc<Segment<'info, 'refs>>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Self {
+        Self {
+            data,
+            tombstones: CollectionTombstones::new(tombstones, 0),
+            seed,
+            container_type,
+        }
+    }
+}
+
+impl<'info, 'refs> CollectionMeta for PdaCollectionMetaTombstoneInterface<'info, 'refs> {
+    fn try_create(&mut self) -> Result<()> {
+        self.data.try_create()?;
+        if let Some(removed_meta) = self.tombstones.removed_meta() {
+            removed_meta.set_collection_removed(0);
+        }
+        Ok(())
+    }
+
+    fn try_load(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn min_data_len() -> usize {
+        std::mem::size_of::<PdaCollectionMeta>()
+    }
+
+    fn get_seed(&self) -> &[u8] {
+        self.seed
+    }
+
+    fn get_len(&self) -> u64 {
+        self.data.get_len()
+    }
+
+    fn set_len(&mut self, len: u64) {
+        self.data.set_len(len);
+    }
+
+    fn get_removed(&self) -> u64 {
+        self.tombstones.get_removed()
+    }
+
+    fn is_removed(&self, idx: u64) -> bool {
+        self.tombstones.is_removed(idx)
+    }
+
+    unsafe fn try_set_removed(&mut self, idx: u64) -> Result<()> {
+        self.tombstones.try_set_removed(idx)
+    }
+
+    fn get_container_type(&self) -> Option<u32> {
+        self.container_type
+    }
+}
+
 #[derive(Debug)]
 pub struct PdaCollectionSegmentInterface<'info, 'refs> {
     segment: Rc<Segment<'info, 'refs>>,
+    tombstones: CollectionTombstones<'info, 'refs>,
     seed: &'static [u8],
     container_type: Option<u32>,
 }
@@ -139,6 +339,10 @@ impl<'info, 'refs> PdaCollectionSegmentInterface<'info, 'refs> {
         container_type: Option<u32>,
     ) -> Self {
         Self {
+            tombstones: CollectionTombstones::new(
+                segment.clone(),
+                std::mem::size_of::<PdaCollectionMeta>(),
+            ),
             segment,
             seed,
             container_type,
@@ -152,12 +356,20 @@ impl<'info, 'refs> PdaCollectionSegmentInterface<'info, 'refs> {
     pub fn data_mut(&self) -> &mut PdaCollectionMeta {
         self.segment.as_struct_mut::<PdaCollectionMeta>()
     }
+
+    pub fn tombstones(&self) -> &CollectionTombstones<'info, 'refs> {
+        &self.tombstones
+    }
 }
 
 impl<'info, 'refs> CollectionMeta for PdaCollectionSegmentInterface<'info, 'refs> {
     fn try_create(&mut self) -> Result<()> {
         // }, seed : &[u8], container_type : Option<u32>) -> Result<()> {
-        self.data_mut().try_create() //seed,container_type)
+        self.data_mut().try_create()?; //seed,container_type)
+        if let Some(removed_meta) = self.tombstones.removed_meta() {
+            removed_meta.set_collection_removed(0);
+        }
+        Ok(())
     }
 
     fn try_load(&mut self) -> Result<()> {
@@ -165,7 +377,7 @@ impl<'info, 'refs> CollectionMeta for PdaCollectionSegmentInterface<'info, 'refs
     }
 
     fn min_data_len() -> usize {
-        std::mem::size_of::<PdaCollectionMeta>()
+        std::mem::size_of::<PdaCollectionMeta>() + std::mem::size_of::<PdaCollectionRemovedMeta>()
     }
 
     fn get_seed(&self) -> &[u8] {
@@ -182,6 +394,18 @@ impl<'info, 'refs> CollectionMeta for PdaCollectionSegmentInterface<'info, 'refs
         self.data_mut().set_len(len)
     }
 
+    fn get_removed(&self) -> u64 {
+        self.tombstones.get_removed()
+    }
+
+    fn is_removed(&self, idx: u64) -> bool {
+        self.tombstones.is_removed(idx)
+    }
+
+    unsafe fn try_set_removed(&mut self, idx: u64) -> Result<()> {
+        self.tombstones.try_set_removed(idx)
+    }
+
     fn get_container_type(&self) -> Option<u32> {
         // self.data_ref().get_container_type()
         self.container_type
@@ -190,6 +414,170 @@ impl<'info, 'refs> CollectionMeta for PdaCollectionSegmentInterface<'info, 'refs
 
 // ~~~
 
+/// Meta of ring collections, tracking the sequence of the next entry (head)
+/// and the sequence of the oldest retained entry (tail)
+pub trait RingCollectionMeta {
+    fn min_data_len() -> usize;
+    fn try_create(&mut self) -> Result<()>;
+    fn try_load(&mut self) -> Result<()>;
+    fn get_seed(&self) -> &[u8];
+    fn get_head(&self) -> u64;
+    fn set_head(&mut self, _head: u64);
+    fn get_tail(&self) -> u64;
+    fn set_tail(&mut self, _tail: u64);
+    fn get_container_type(&self) -> Option<u32>;
+}
+
+#[derive(Meta, Copy, Clone)]
+#[repr(packed)]
+pub struct PdaRingCollectionMeta {
+    ring_head: u64,
+    ring_tail: u64,
+}
+
+impl PdaRingCollectionMeta {
+    fn try_create(&mut self) -> Result<()> {
+        self.set_ring_head(0);
+        self.set_ring_tail(0);
+        Ok(())
+    }
+}
+
+pub struct PdaRingCollectionMetaInterface<'info> {
+    data: &'info mut PdaRingCollectionMeta,
+    seed: &'static [u8],
+    container_type: Option<u32>,
+}
+
+impl<'info> PdaRingCollectionMetaInterface<'info> {
+    pub fn new(
+        data: &'info mut PdaRingCollectionMeta,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Self {
+        Self {
+            data,
+            seed,
+            container_type,
+        }
+    }
+
+    pub fn data_ref(&self) -> &PdaRingCollectionMeta {
+        self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut PdaRingCollectionMeta {
+        self.data
+    }
+}
+
+impl<'info> RingCollectionMeta for PdaRingCollectionMetaInterface<'info> {
+    fn try_create(&mut self) -> Result<()> {
+        self.data_mut().try_create()
+    }
+
+    fn try_load(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn min_data_len() -> usize {
+        std::mem::size_of::<PdaRingCollectionMeta>()
+    }
+
+    fn get_seed(&self) -> &[u8] {
+        self.seed
+    }
+
+    fn get_head(&self) -> u64 {
+        self.data_ref().get_ring_head()
+    }
+
+    fn set_head(&mut self, head: u64) {
+        self.data_mut().set_ring_head(head);
+    }
+
+    fn get_tail(&self) -> u64 {
+        self.data_ref().get_ring_tail()
+    }
+
+    fn set_tail(&mut self, tail: u64) {
+        self.data_mut().set_ring_tail(tail);
+    }
+
+    fn get_container_type(&self) -> Option<u32> {
+        self.container_type
+    }
+}
+
+#[derive(Debug)]
+pub struct PdaRingCollectionSegmentInterface<'info, 'refs> {
+    segment: Rc<Segment<'info, 'refs>>,
+    seed: &'static [u8],
+    container_type: Option<u32>,
+}
+
+impl<'info, 'refs> PdaRingCollectionSegmentInterface<'info, 'refs> {
+    pub fn new(
+        segment: Rc<Segment<'info, 'refs>>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Self {
+        Self {
+            segment,
+            seed,
+            container_type,
+        }
+    }
+
+    pub fn data_ref(&self) -> &PdaRingCollectionMeta {
+        self.segment.as_struct_ref::<PdaRingCollectionMeta>()
+    }
+
+    pub fn data_mut(&self) -> &mut PdaRingCollectionMeta {
+        self.segment.as_struct_mut::<PdaRingCollectionMeta>()
+    }
+}
+
+impl<'info, 'refs> RingCollectionMeta for PdaRingCollectionSegmentInterface<'info, 'refs> {
+    fn try_create(&mut self) -> Result<()> {
+        self.data_mut().try_create()
+    }
+
+    fn try_load(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn min_data_len() -> usize {
+        std::mem::size_of::<PdaRingCollectionMeta>()
+    }
+
+    fn get_seed(&self) -> &[u8] {
+        self.seed
+    }
+
+    fn get_head(&self) -> u64 {
+        self.data_ref().get_ring_head()
+    }
+
+    fn set_head(&mut self, head: u64) {
+        self.data_mut().set_ring_head(head)
+    }
+
+    fn get_tail(&self) -> u64 {
+        self.data_ref().get_ring_tail()
+    }
+
+    fn set_tail(&mut self, tail: u64) {
+        self.data_mut().set_ring_tail(tail)
+    }
+
+    fn get_container_type(&self) -> Option<u32> {
+        self.container_type
+    }
+}
+
+// ~~~
+
 #[derive(Meta, Copy, Clone)]
 #[repr(packed)]
 pub struct PubkeyCollectionMeta {
@@ -12,3 +12,14 @@ mod pubkey;
 pub use pubkey::*;
 mod proxy;
 pub use proxy::*;
+mod ring;
+pub use ring::*;
+
+cfg_if::cfg_if! {
+    if #[cfg(not(target_os = "solana"))] {
+        mod stream;
+        pub use stream::*;
+        mod tree;
+        pub use tree::*;
+    }
+}
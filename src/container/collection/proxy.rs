@@ -4,7 +4,7 @@
 
 use kaizen::container::*;
 use kaizen::context::*;
-use kaizen::error::ErrorCode;
+use kaizen::error::{error, ErrorCode};
 use kaizen::prelude::*;
 use kaizen::result::Result;
 
@@ -109,3 +109,149 @@ impl<'info, 'refs> Container<'info, 'refs> for Proxy<'info, 'refs> {
     }
     // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
 }
+
+/// Proxy referencing an account owned by another program
+/// (such as an SPL token account, an NFT mint or a container of another Kaizen program)
+#[derive(Meta)]
+#[repr(packed)]
+pub struct ForeignProxyMeta {
+    container_type: u32,
+    reference: Pubkey,
+    owner: Pubkey,
+}
+
+pub struct ForeignProxy<'info, 'refs> {
+    account: &'refs AccountInfo<'info>,
+    meta: &'info mut ForeignProxyMeta,
+}
+
+impl<'info, 'refs> ForeignProxy<'info, 'refs> {
+    pub fn account(&self) -> &'refs AccountInfo<'info> {
+        self.account
+    }
+
+    pub fn reference(&self) -> &Pubkey {
+        &self.meta.reference
+    }
+
+    /// Program owning the referenced account
+    pub fn owner(&self) -> &Pubkey {
+        &self.meta.owner
+    }
+
+    pub fn data_len() -> usize {
+        std::mem::size_of::<ForeignProxyMeta>()
+    }
+
+    pub fn try_create(
+        account: &'refs AccountInfo<'info>,
+        reference: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<Self> {
+        let mut data = account.data.borrow_mut();
+        let meta = unsafe { &mut *data.as_mut_ptr().cast::<ForeignProxyMeta>() };
+        meta.set_container_type(Containers::ForeignProxy as u32);
+        meta.set_reference(*reference);
+        meta.set_owner(*owner);
+
+        let proxy = ForeignProxy { account, meta };
+
+        Ok(proxy)
+    }
+
+    pub fn try_load(account: &'refs AccountInfo<'info>) -> Result<Self> {
+        let mut data = account.data.borrow_mut();
+        let meta = unsafe { &mut *data.as_mut_ptr().cast::<ForeignProxyMeta>() };
+
+        if meta.get_container_type() != Containers::ForeignProxy as u32 {
+            return Err(error_code!(ErrorCode::InvalidProxyContainerType));
+        }
+
+        let proxy = ForeignProxy { account, meta };
+
+        Ok(proxy)
+    }
+
+    /// Verifies that the supplied account is the referenced account
+    /// and that it is owned by the program recorded in the proxy
+    pub fn try_verify_reference(&self, account: &AccountInfo<'info>) -> Result<()> {
+        if account.key != self.reference() {
+            return Err(
+                error_code!(ErrorCode::AccountCollectionInvalidAddress).with_account(account.key)
+            );
+        }
+
+        if account.owner != self.owner() {
+            return Err(error_code!(ErrorCode::IllegalOwner).with_account(account.key));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'info, 'refs> Container<'info, 'refs> for ForeignProxy<'info, 'refs> {
+    type T = Self;
+
+    fn container_type() -> u32 {
+        Containers::ForeignProxy as u32
+    }
+
+    fn initial_data_len() -> usize {
+        ForeignProxy::data_len()
+    }
+
+    fn account(&self) -> &'refs solana_program::account_info::AccountInfo<'info> {
+        self.account()
+    }
+
+    fn pubkey(&self) -> &solana_program::pubkey::Pubkey {
+        self.account().key
+    }
+
+    fn try_allocate(
+        _ctx: &ContextReference<'info, 'refs, '_, '_>,
+        _allocation_args: &AccountAllocationArgs<'info, 'refs, '_>,
+        _reserve_data_len: usize,
+    ) -> kaizen::result::Result<ForeignProxy<'info, 'refs>> {
+        Err(error!(
+            "ForeignProxy accounts are allocated by PdaProxyCollection::try_insert_foreign_reference()"
+        ))
+    }
+
+    fn try_create(_account: &'refs AccountInfo<'info>) -> Result<ForeignProxy<'info, 'refs>> {
+        Err(error!(
+            "ForeignProxy requires the referenced account and its owner (see ForeignProxy::try_create())"
+        ))
+    }
+
+    fn try_load(account: &'refs AccountInfo<'info>) -> Result<ForeignProxy<'info, 'refs>> {
+        Self::try_load(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+
+    #[test]
+    fn foreign_proxy_owner_verification() -> Result<()> {
+        let mut target_instance = MockAccountDataInstance::new(16);
+        let target = target_instance.into_account_info();
+        let mut other_instance = MockAccountDataInstance::new(16);
+        let other = other_instance.into_account_info();
+
+        let mut instance = MockAccountDataInstance::new(ForeignProxy::data_len());
+        let account = instance.into_account_info();
+        ForeignProxy::try_create(&account, target.key, target.owner)?;
+
+        let proxy = ForeignProxy::try_load(&account)?;
+        assert_eq!(proxy.reference(), target.key);
+        assert_eq!(proxy.owner(), target.owner);
+        proxy.try_verify_reference(&target)?;
+        assert!(proxy.try_verify_reference(&other).is_err());
+        assert!(Proxy::try_load(&account).is_err());
+        assert!(<ForeignProxy as Container>::try_create(&account).is_err());
+        Ok(())
+    }
+}
@@ -55,6 +55,22 @@ where
         Ok(())
     }
 
+    /// Creates a collection keeping pubkeys sorted, providing `O(log n)`
+    /// lookups and rejecting duplicate pubkeys.
+    pub fn try_create_sorted<'i, 'r>(
+        &mut self,
+        ctx: &ContextReference<'i, 'r, '_, '_>,
+        allocation_args: &AccountAllocationArgs<'i, 'r, '_>,
+        data_type: Option<u32>,
+        container_type: Option<u32>,
+    ) -> Result<()> {
+        let collection_store = PubkeyCollectionStore::try_allocate(ctx, allocation_args, 0)?;
+        self.meta
+            .try_create(collection_store.pubkey(), data_type, container_type)?;
+        collection_store.try_init_sorted(container_type)?;
+        Ok(())
+    }
+
     pub fn try_load(&mut self, ctx: &ContextReference<'info, 'refs, '_, '_>) -> Result<()> {
         if let Some(account_info) = ctx.locate_index_account(self.meta.pubkey()) {
             let container = PubkeyCollectionStore::try_load(account_info)?;
@@ -165,6 +181,77 @@ where
         Ok(())
     }
 
+    /// Inserts the container, recording the value of its indexed field in the `index`
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_insert_container_with_index<'i, 'r, C, K>(
+        &mut self,
+        target: &C,
+        index: &CollectionIndex<'_, '_, K>,
+    ) -> Result<()>
+    where
+        C: Container<'i, 'r> + IndexKey<K>,
+        K: Copy + Ord,
+    {
+        self.try_insert_container(target)?;
+        index.try_insert_container(target)
+    }
+
+    /// Removes the container, along with its record in the `index`
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove_container_with_index<'i, 'r, C, K>(
+        &mut self,
+        target: &C,
+        index: &CollectionIndex<'_, '_, K>,
+    ) -> Result<()>
+    where
+        C: Container<'i, 'r> + IndexKey<K>,
+        K: Copy + Ord,
+    {
+        self.try_remove_pubkey(target.pubkey())?;
+        index.try_remove_container(target)
+    }
+
+    fn try_container(&self) -> Result<&PubkeyCollectionStore<'info, 'refs>> {
+        self.container
+            .as_ref()
+            .ok_or_else(|| error_code!(ErrorCode::PubkeyCollectionNotLoaded))
+    }
+
+    pub fn is_sorted(&self) -> Result<bool> {
+        Ok(self.try_container()?.is_sorted())
+    }
+
+    pub fn contains(&self, key: &Pubkey) -> Result<bool> {
+        Ok(self.try_container()?.contains(key))
+    }
+
+    pub fn find(&self, key: &Pubkey) -> Result<Option<usize>> {
+        Ok(self.try_container()?.find(key))
+    }
+
+    /// Removes the record of the pubkey (without requiring its sequence)
+    pub fn try_remove_pubkey(&mut self, key: &Pubkey) -> Result<()> {
+        self.try_container()?.try_remove_pubkey(key)?;
+        let len = self.meta.get_len();
+        self.meta.set_len(len - 1);
+        Ok(())
+    }
+
+    /// Migrates an existing (insertion-ordered) collection to the sorted mode
+    pub fn try_migrate_to_sorted(&self) -> Result<()> {
+        self.try_container()?.try_migrate_to_sorted()
+    }
+
     // pub fn as_slice(&self) -> Result<&[PubkeyMeta]> {
     //     if let Some(container) = &self.container {
     //         Ok(container.as_slice())
@@ -196,6 +283,11 @@ where
     }
 }
 
+/// Store version keeping records in the insertion (sequence) order
+pub const PUBKEY_COLLECTION_STORE_VERSION_SEQUENCE: u32 = 1;
+/// Store version keeping records sorted by pubkey (rejecting duplicates)
+pub const PUBKEY_COLLECTION_STORE_VERSION_SORTED: u32 = 2;
+
 #[derive(Meta, Copy, Clone)]
 #[repr(packed)]
 pub struct PubkeyCollectionStoreMeta {
@@ -212,15 +304,97 @@ pub struct PubkeyCollectionStore<'info, 'refs> {
 impl<'info, 'refs> PubkeyCollectionStore<'info, 'refs> {
     pub fn try_init(&self, container_type: Option<u32>) -> Result<()> {
         let mut meta = self.meta.borrow_mut();
-        meta.set_version(1);
+        meta.set_version(PUBKEY_COLLECTION_STORE_VERSION_SEQUENCE);
         meta.set_container_type(container_type.unwrap_or(0u32));
         Ok(())
     }
 
+    pub fn try_init_sorted(&self, container_type: Option<u32>) -> Result<()> {
+        self.try_init(container_type)?;
+        self.meta
+            .borrow_mut()
+            .set_version(PUBKEY_COLLECTION_STORE_VERSION_SORTED);
+        Ok(())
+    }
+
+    /// Returns `true` if records are sorted by pubkey
+    pub fn is_sorted(&self) -> bool {
+        self.meta.borrow().get_version() == PUBKEY_COLLECTION_STORE_VERSION_SORTED
+    }
+
     fn try_insert(&self, seq: u32, key: &Pubkey) -> Result<()> {
-        let record = unsafe { self.records.try_allocate(false)? };
-        record.set_seq(seq);
-        record.key = *key;
+        if self.is_sorted() {
+            match self.search(key) {
+                Ok(_) => return Err(error_code!(ErrorCode::PubkeyCollectionCollision)),
+                Err(idx) => unsafe {
+                    self.records
+                        .try_insert_at(idx, &PubkeyMeta::new(seq, *key))?;
+                },
+            }
+        } else {
+            let record = unsafe { self.records.try_allocate(false)? };
+            record.set_seq(seq);
+            record.key = *key;
+        }
+        Ok(())
+    }
+
+    fn search(&self, key: &Pubkey) -> std::result::Result<usize, usize> {
+        self.records
+            .as_slice()
+            .binary_search_by(|record| record.key.cmp(key))
+    }
+
+    /// Returns the position of the pubkey (`O(log n)` for sorted stores)
+    pub fn find(&self, key: &Pubkey) -> Option<usize> {
+        if self.is_sorted() {
+            self.search(key).ok()
+        } else {
+            self.records
+                .as_slice()
+                .iter()
+                .position(|record| record.key == *key)
+        }
+    }
+
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.find(key).is_some()
+    }
+
+    pub fn try_remove_pubkey(&self, key: &Pubkey) -> Result<()> {
+        match self.find(key) {
+            Some(idx) => {
+                unsafe {
+                    self.records.try_remove_at(idx, true)?;
+                }
+                Ok(())
+            }
+            None => Err(error_code!(ErrorCode::PubkeyCollectionRecordNotFound)),
+        }
+    }
+
+    /// Migrates an insertion-ordered store to the sorted mode. Fails
+    /// if the store contains duplicate pubkeys.
+    pub fn try_migrate_to_sorted(&self) -> Result<()> {
+        if self.is_sorted() {
+            return Ok(());
+        }
+
+        let mut keys = self
+            .records
+            .as_slice()
+            .iter()
+            .map(|record| record.key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(error_code!(ErrorCode::PubkeyCollectionCollision));
+        }
+
+        self.records.sort_by(|a, b| a.key.cmp(&b.key));
+        self.meta
+            .borrow_mut()
+            .set_version(PUBKEY_COLLECTION_STORE_VERSION_SORTED);
         Ok(())
     }
 
@@ -233,14 +407,25 @@ impl<'info, 'refs> PubkeyCollectionStore<'info, 'refs> {
 
     pub fn try_remove(&self, sequence: &PubkeySequence) -> Result<()> {
         let records: &[PubkeySequence] = self.records.as_struct_slice();
-        match records.binary_search(sequence) {
-            Ok(idx) => {
+        // sorted stores are ordered by pubkey, not by sequence
+        let position = if self.is_sorted() {
+            records
+                .iter()
+                .position(|record| record.get_seq() == sequence.get_seq())
+        } else {
+            records.binary_search(sequence).ok()
+        };
+        match position {
+            Some(idx) => {
                 unsafe {
                     self.records.try_remove_at(idx, true)?;
                 }
                 Ok(())
             }
-            Err(_idx) => Err(error_code!(ErrorCode::PubkeyCollectionRecordNotFound)),
+            None => {
+                let key = sequence.key;
+                Err(error_code!(ErrorCode::PubkeyCollectionRecordNotFound).with_account(&key))
+            }
         }
     }
 
@@ -259,6 +444,8 @@ cfg_if! {
     if #[cfg(not(target_os = "solana"))] {
         use kaizen::error;
         use futures::future::join_all;
+        use futures::Stream;
+        use super::stream::{paginate, Page, PageOptions};
         use solana_program::instruction::AccountMeta;
         use kaizen::container::{AccountAggregatorInterface,AsyncAccountAggregatorInterface};
 
@@ -380,6 +567,16 @@ cfg_if! {
                 Ok(list)
             }
 
+            /// Creates a paginated stream of containers referenced by the collection
+            pub fn stream<'a,'this,C>(&'a self, options: PageOptions)
+            -> impl Stream<Item = Result<Page<'this,C>>> + 'a
+            where 'this: 'a, C: kaizen::container::Container<'this,'this> + 'a
+            {
+                paginate(self.len(), options, move |range: std::ops::Range<usize>| {
+                    self.load_container_range::<C>(range)
+                })
+            }
+
             pub async fn load_container_range_strict<'this,C>(&self, range: std::ops::Range<usize>)
             -> Result<Vec<ContainerReference<'this,C>>>
             where C: kaizen::container::Container<'this,'this>
@@ -584,3 +781,53 @@ cfg_if! {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+
+    #[test]
+    fn pubkey_collection_store_sorted() -> Result<()> {
+        let mut instance = MockAccountDataInstance::new(PubkeyCollectionStore::initial_data_len());
+        let account = instance.into_account_info();
+        let store = PubkeyCollectionStore::try_create(&account)?;
+        store.try_init(None)?;
+
+        let keys = (0..5).map(|_| generate_random_pubkey()).collect::<Vec<_>>();
+        for (seq, key) in keys.iter().enumerate() {
+            store.try_insert(seq as u32, key)?;
+        }
+        store.try_insert(5, &keys[0])?;
+        assert!(store.try_migrate_to_sorted().is_err());
+        store.try_remove(&PubkeySequence::new(5, keys[0]))?;
+
+        store.try_migrate_to_sorted()?;
+        assert!(store.is_sorted());
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(
+            store
+                .as_slice()
+                .iter()
+                .map(|record| record.key)
+                .collect::<Vec<_>>(),
+            sorted
+        );
+
+        assert!(store.try_insert(6, &keys[1]).is_err());
+        let key = generate_random_pubkey();
+        store.try_insert(7, &key)?;
+        assert!(store.contains(&key));
+        assert!(store
+            .as_slice()
+            .windows(2)
+            .all(|pair| pair[0].key < pair[1].key));
+
+        store.try_remove_pubkey(&keys[2])?;
+        assert!(!store.contains(&keys[2]));
+        assert!(store.try_remove_pubkey(&keys[2]).is_err());
+        assert_eq!(store.records.len(), 5);
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 use cfg_if::cfg_if;
 // use crate::address::ProgramAddressData;
 use super::meta::*;
-use super::proxy::Proxy;
+use super::proxy::{ForeignProxy, Proxy};
 use crate::container::Container;
 use crate::result::Result;
 use kaizen::error::ErrorCode;
@@ -142,21 +142,15 @@ where
     //     [domain, &self.meta.get_seed(),&index_bytes].concat()
     // }
 
-    pub fn try_insert_reference<'refs, T>(
-        &mut self,
+    /// Allocates the proxy account for the next collection entry
+    fn try_create_proxy_account<'refs>(
+        &self,
         ctx: &ContextReference<'info, 'refs, '_, '_>,
         bump: u8,
-        container: &T,
-    ) -> Result<()>
-    where
-        T: Container<'info, 'refs>,
-    {
-        if let Some(container_type) = self.meta.get_container_type() {
-            if T::container_type() != container_type {
-                return Err(error_code!(ErrorCode::ContainerTypeMismatch));
-            }
-        }
-
+        data_len: usize,
+    ) -> Result<&'refs AccountInfo<'info>> {
+        // proxy accounts occupy indices `1..=len` (the next entry is located
+        // at `len + 1`); existing collections already hold proxies at these addresses
         let next_index = self.meta.get_len() + 1;
         // let tpl_seeds = self.get_proxy_seed_at(&next_index,Some(&[bump]));
         let bump = &[bump];
@@ -178,7 +172,7 @@ where
         let allocation_args = AccountAllocationArgs::new(AddressDomain::None);
         // let account_info =
         ctx.try_create_pda_with_args(
-            Proxy::data_len(),
+            data_len,
             &allocation_args,
             &tpl_seeds,
             // tpl_program_address_data,
@@ -186,17 +180,103 @@ where
             false,
         )?;
 
+        Ok(tpl_account_info)
+    }
+
+    pub fn try_insert_reference<'refs, T>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        bump: u8,
+        container: &T,
+    ) -> Result<()>
+    where
+        T: Container<'info, 'refs>,
+    {
+        if let Some(container_type) = self.meta.get_container_type() {
+            if T::container_type() != container_type {
+                return Err(error_code!(ErrorCode::ContainerTypeMismatch));
+            }
+        }
+
+        let tpl_account_info = self.try_create_proxy_account(ctx, bump, Proxy::data_len())?;
         Proxy::try_create(tpl_account_info, container.pubkey())?;
-        self.meta.set_len(next_index);
+        self.meta.set_len(self.meta.get_len() + 1);
+
+        Ok(())
+    }
+
+    /// Inserts a reference to an account owned by another program. The owner
+    /// of the `account` is verified against the `owner` and recorded in the
+    /// proxy, allowing it to be verified again when the reference is used.
+    pub fn try_insert_foreign_reference<'refs>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        bump: u8,
+        account: &AccountInfo<'info>,
+        owner: &Pubkey,
+    ) -> Result<()> {
+        if account.owner != owner {
+            return Err(error_code!(ErrorCode::IllegalOwner).with_account(account.key));
+        }
+
+        let tpl_account_info =
+            self.try_create_proxy_account(ctx, bump, ForeignProxy::data_len())?;
+        ForeignProxy::try_create(tpl_account_info, account.key, owner)?;
+        self.meta.set_len(self.meta.get_len() + 1);
 
         Ok(())
     }
+
+    /// Loads the foreign proxy at `index` (proxies occupy indices `1..=len`),
+    /// verifying the proxy address as well as the address and the owner of
+    /// the referenced `account`.
+    pub fn try_load_foreign_reference<'refs>(
+        &self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        index: u64,
+        bump: u8,
+        proxy_account: &'refs AccountInfo<'info>,
+        account: &AccountInfo<'info>,
+    ) -> Result<ForeignProxy<'info, 'refs>> {
+        if index == 0 || index > self.meta.get_len() {
+            return Err(
+                error_code!(ErrorCode::AccountCollectionNotFound).with_account(proxy_account.key)
+            );
+        }
+
+        let bump = &[bump];
+        let seeds = self.get_proxy_seed_at(&index, Some(bump));
+        let pda = Pubkey::create_program_address(&seeds, ctx.program_id)?;
+        if proxy_account.key != &pda {
+            return Err(error_code!(ErrorCode::AccountCollectionInvalidAddress)
+                .with_account(proxy_account.key));
+        }
+
+        let proxy = ForeignProxy::try_load(proxy_account)?;
+        proxy.try_verify_reference(account)?;
+
+        Ok(proxy)
+    }
 }
 
 cfg_if! {
     if #[cfg(not(target_os = "solana"))] {
 
-        use futures::{stream::FuturesOrdered, StreamExt};
+        use futures::{stream::FuturesOrdered, Stream, StreamExt};
+        use super::stream::{paginate_from, Page, PageOptions};
+        use crate::container::registry::{self, ContainerDeclaration};
+        use crate::container::Containers;
+
+        /// Account referenced by a proxy collection entry
+        #[derive(Clone)]
+        pub struct ForeignReference {
+            pub pubkey: Pubkey,
+            /// Program owning the referenced account (verified against the proxy)
+            pub owner: Pubkey,
+            pub reference: Arc<AccountDataReference>,
+            /// Container declaration registered by the owning program
+            pub declaration: Option<ContainerDeclaration>,
+        }
 
         impl<'info,M> PdaProxyCollectionInterface<'info,M>
         where M: CollectionMeta
@@ -204,6 +284,7 @@ cfg_if! {
 
         // impl<'info,'refs> AccountReferenceCollection<'info,'refs> {
 
+            /// Derives the address of the proxy at `idx` (proxies occupy indices `1..=len`)
             pub fn get_proxy_pda_at(&self, program_id : &Pubkey, idx : u64) -> Result<(Pubkey, u8)> {
                 let (address, bump) = Pubkey::find_program_address(
                     &self.get_proxy_seed_at(&idx,None),
@@ -227,18 +308,83 @@ cfg_if! {
                 self.load_container_at_with_transport::<T>(program_id, idx, &transport).await
             }
 
+            /// Returns the pubkey referenced by the proxy at `idx` along with the
+            /// owner recorded by foreign proxies (`None` for same-program proxies)
+            pub async fn load_proxy_reference_at_with_transport(&self, program_id: &Pubkey, idx: usize, transport: &Arc<Transport>)
+            -> Result<(Pubkey, Option<Pubkey>)>
+            {
+                let proxy_pubkey = self.get_proxy_pubkey_at(program_id, idx)?;
+                let reference = match transport.lookup(&proxy_pubkey).await? {
+                    Some(reference) => reference,
+                    None => return Err(error_code!(ErrorCode::AccountReferenceCollectionProxyNotFound))
+                };
+
+                let mut account_data = reference.account_data.lock()?;
+                let container_type = account_data.container_type();
+                let account_info = account_data.into_account_info();
+                if container_type == Some(Containers::ForeignProxy as u32) {
+                    let proxy = ForeignProxy::try_load(&account_info)?;
+                    Ok((*proxy.reference(), Some(*proxy.owner())))
+                } else {
+                    let proxy = Proxy::try_load(&account_info)?;
+                    Ok((*proxy.reference(), None))
+                }
+            }
+
             pub async fn load_container_at_with_transport<'this,T>(&self, program_id: &Pubkey, idx: usize, transport: &Arc<Transport>)
             -> Result<Option<ContainerReference<'this,T>>>
             where T: kaizen::container::Container<'this,'this>
             {
-                let proxy_pubkey = self.get_proxy_pubkey_at(program_id, idx)?;
-                let proxy = match load_container_with_transport::<Proxy>(transport, &proxy_pubkey).await? {
-                    Some(proxy) => proxy,
-                    None => return Err(error_code!(ErrorCode::AccountReferenceCollectionProxyNotFound))
+                let (container_pubkey, owner) = self.load_proxy_reference_at_with_transport(program_id, idx, transport).await?;
+                match owner {
+                    None => load_container_with_transport::<T>(transport,&container_pubkey).await,
+                    Some(owner) => {
+                        let reference = match transport.lookup(&container_pubkey).await? {
+                            Some(reference) => reference,
+                            None => return Ok(None)
+                        };
+                        if reference.account_data.lock()?.owner != owner {
+                            return Err(error_code!(ErrorCode::IllegalOwner).with_account(&container_pubkey));
+                        }
+                        Ok(Some(reference.try_into_container::<T>()?))
+                    }
+                }
+            }
+
+            /// Loads the account referenced at `idx`, verifying its owner (for foreign
+            /// proxies) and resolving its container declaration using the registry
+            /// of the owning program. The declaration is `None` for accounts that do
+            /// not hold registered containers (such as SPL token accounts).
+            pub async fn load_foreign_reference_at(&self, program_id: &Pubkey, idx: usize)
+            -> Result<Option<ForeignReference>>
+            {
+                let transport = Transport::global()?;
+                self.load_foreign_reference_at_with_transport(program_id, idx, &transport).await
+            }
+
+            pub async fn load_foreign_reference_at_with_transport(&self, program_id: &Pubkey, idx: usize, transport: &Arc<Transport>)
+            -> Result<Option<ForeignReference>>
+            {
+                let (pubkey, owner) = self.load_proxy_reference_at_with_transport(program_id, idx, transport).await?;
+                let reference = match transport.lookup(&pubkey).await? {
+                    Some(reference) => reference,
+                    None => return Ok(None)
                 };
 
-                let container_pubkey = proxy.reference();
-                load_container_with_transport::<T>(transport,container_pubkey).await
+                let owner = owner.unwrap_or(*program_id);
+                let container_type = {
+                    let account_data = reference.account_data.lock()?;
+                    if account_data.owner != owner {
+                        return Err(error_code!(ErrorCode::IllegalOwner).with_account(&pubkey));
+                    }
+                    account_data.container_type()
+                };
+                let declaration = match container_type {
+                    Some(container_type) => registry::lookup_for_program(&owner, container_type)?,
+                    None => None,
+                };
+
+                Ok(Some(ForeignReference { pubkey, owner, reference, declaration }))
             }
 
 
@@ -263,6 +409,35 @@ cfg_if! {
                 Ok(futures.collect::<Vec<_>>().await)
             }
 
+            /// Creates a paginated stream of containers referenced by the collection.
+            /// Page ranges and container indices are the 1-based proxy indices
+            /// accepted by [`Self::try_load_foreign_reference()`].
+            pub fn stream<'a,'this,T>(&'a self, program_id: &Pubkey, options: PageOptions)
+            -> Result<impl Stream<Item = Result<Page<'this,T>>> + 'a>
+            where 'this: 'a, T: kaizen::container::Container<'this,'this> + 'a
+            {
+                let transport = Transport::global()?;
+                Ok(self.stream_with_transport::<T>(program_id, options, &transport))
+            }
+
+            pub fn stream_with_transport<'a,'this,T>(&'a self, program_id: &Pubkey, options: PageOptions, transport: &Arc<Transport>)
+            -> impl Stream<Item = Result<Page<'this,T>>> + 'a
+            where 'this: 'a, T: kaizen::container::Container<'this,'this> + 'a
+            {
+                let program_id = *program_id;
+                let transport = transport.clone();
+                // proxies occupy indices `1..=len`; pages report the same indices
+                paginate_from(1, self.len(), options, move |range: std::ops::Range<usize>| {
+                    let transport = transport.clone();
+                    async move {
+                        self.load_container_range_with_transport::<T>(&program_id, range, &transport)
+                            .await?
+                            .into_iter()
+                            .collect::<Result<Vec<_>>>()
+                    }
+                })
+            }
+
         }
 
     }
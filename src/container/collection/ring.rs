@@ -0,0 +1,508 @@
+//!
+//! Bounded PDA ring-buffer collection retaining the most recent entries
+//!
+//! Entries are addressed by a monotonic sequence: the ring meta tracks the head
+//! (the sequence of the next entry) and the tail (the sequence of the oldest
+//! retained entry). The entry with
+//! sequence `n` occupies the PDA slot `n % CAPACITY`, so once the collection is
+//! full, inserting an entry recycles the account of the oldest entry.
+//!
+
+use super::meta::*;
+use crate::container::Container;
+use crate::result::Result;
+use cfg_if::cfg_if;
+use kaizen::error::ErrorCode;
+use kaizen::prelude::*;
+
+pub type PdaRingCollection<'info, 'refs, const CAPACITY: u64> =
+    PdaRingCollectionInterface<'info, PdaRingCollectionSegmentInterface<'info, 'refs>, CAPACITY>;
+pub type PdaRingCollectionReference<'info, const CAPACITY: u64> =
+    PdaRingCollectionInterface<'info, PdaRingCollectionMetaInterface<'info>, CAPACITY>;
+
+#[derive(Debug)]
+pub struct PdaRingCollectionInterface<'info, M, const CAPACITY: u64> {
+    pub domain: &'info [u8],
+    meta: M,
+}
+
+impl<'info, M, const CAPACITY: u64> PdaRingCollectionInterface<'info, M, CAPACITY>
+where
+    M: RingCollectionMeta,
+{
+    fn try_create_impl(domain: &'info [u8], mut meta: M) -> Result<Self> {
+        if CAPACITY == 0 {
+            return Err(error_code!(ErrorCode::AccountCollectionInvalidCapacity));
+        }
+        meta.try_create()?;
+        Ok(Self { domain, meta })
+    }
+
+    fn try_load_impl(domain: &'info [u8], mut meta: M) -> Result<Self> {
+        if CAPACITY == 0 {
+            return Err(error_code!(ErrorCode::AccountCollectionInvalidCapacity));
+        }
+        meta.try_load()?;
+        Ok(Self { domain, meta })
+    }
+
+    pub fn data_len_min() -> usize {
+        M::min_data_len()
+    }
+
+    pub fn try_create_from_meta(
+        data: &'info mut PdaRingCollectionMeta,
+        account_info: &AccountInfo<'info>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Result<PdaRingCollectionReference<'info, CAPACITY>> {
+        PdaRingCollectionReference::<CAPACITY>::try_create_impl(
+            account_info.key.as_ref(),
+            PdaRingCollectionMetaInterface::new(data, seed, container_type),
+        )
+    }
+
+    pub fn try_load_from_meta(
+        data: &'info mut PdaRingCollectionMeta,
+        account_info: &AccountInfo<'info>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Result<PdaRingCollectionReference<'info, CAPACITY>> {
+        PdaRingCollectionReference::<CAPACITY>::try_load_impl(
+            account_info.key.as_ref(),
+            PdaRingCollectionMetaInterface::new(data, seed, container_type),
+        )
+    }
+
+    pub fn try_create_from_segment_with_collection_args<'refs>(
+        segment: Rc<Segment<'info, 'refs>>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Result<PdaRingCollection<'info, 'refs, CAPACITY>> {
+        PdaRingCollection::<CAPACITY>::try_create_impl(
+            segment.account().key.as_ref(),
+            PdaRingCollectionSegmentInterface::new(segment, seed, container_type),
+        )
+    }
+
+    pub fn try_load_from_segment_with_collection_args<'refs>(
+        segment: Rc<Segment<'info, 'refs>>,
+        seed: &'static [u8],
+        container_type: Option<u32>,
+    ) -> Result<PdaRingCollection<'info, 'refs, CAPACITY>> {
+        PdaRingCollection::<CAPACITY>::try_load_impl(
+            segment.account().key.as_ref(),
+            PdaRingCollectionSegmentInterface::new(segment, seed, container_type),
+        )
+    }
+
+    /// Maximum number of retained entries
+    pub fn capacity(&self) -> u64 {
+        CAPACITY
+    }
+
+    /// Sequence of the next entry
+    pub fn head(&self) -> u64 {
+        self.meta.get_head()
+    }
+
+    /// Sequence of the oldest retained entry
+    pub fn tail(&self) -> u64 {
+        self.meta.get_tail()
+    }
+
+    /// Number of retained entries
+    pub fn len(&self) -> usize {
+        (self.head() - self.tail()) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head() == self.tail()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.head() - self.tail() >= CAPACITY
+    }
+
+    /// PDA slot occupied by the entry with the given sequence
+    pub fn slot_of(&self, sequence: u64) -> u64 {
+        sequence % CAPACITY
+    }
+
+    pub fn get_seed_at<'seed>(
+        &'seed self,
+        slot: &u64,
+        suffix: Option<&'seed [u8]>,
+    ) -> Vec<&'seed [u8]> {
+        let slot_bytes: &[u8; 8] = unsafe { &*(slot as *const u64 as *const [u8; 8]) };
+        if let Some(suffix) = suffix {
+            vec![self.domain, self.meta.get_seed(), slot_bytes, suffix]
+        } else {
+            vec![self.domain, self.meta.get_seed(), slot_bytes]
+        }
+    }
+
+    fn check_container_type<'refs, T>(&self) -> Result<()>
+    where
+        T: Container<'info, 'refs>,
+    {
+        if let Some(container_type) = self.meta.get_container_type() {
+            if T::container_type() != container_type {
+                return Err(error_code!(ErrorCode::ContainerTypeMismatch));
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the retained entry with the given sequence
+    pub fn try_load_container<'refs, T>(
+        &self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        sequence: u64,
+        seed_bump: u8,
+        account: &'refs AccountInfo<'info>,
+    ) -> Result<<T as Container<'info, 'refs>>::T>
+    where
+        T: Container<'info, 'refs>,
+    {
+        self.check_container_type::<T>()?;
+
+        if sequence < self.tail() || sequence >= self.head() {
+            return Err(error_code!(ErrorCode::AccountCollectionNotFound).with_account(account.key));
+        }
+
+        let slot = self.slot_of(sequence);
+        let seed_bump = [seed_bump];
+        let seeds = self.get_seed_at(&slot, Some(&seed_bump));
+        let pda = Pubkey::create_program_address(&seeds, ctx.program_id)?;
+        if account.key != &pda {
+            return Err(
+                error_code!(ErrorCode::AccountCollectionInvalidAddress).with_account(account.key)
+            );
+        }
+
+        T::try_load(account)
+    }
+
+    /// Inserts an entry at the head of the collection. While the collection
+    /// is not full, the `account` is a template for the next slot and is
+    /// allocated; otherwise the `account` must be the account of the oldest
+    /// entry, which is reinitialized (see [`ContextReference::try_recycle_account()`])
+    /// and evicted from the collection.
+    pub fn try_push_container<'refs, T>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        seed_bump: u8,
+        account: &'refs AccountInfo<'info>,
+        data_len: Option<usize>,
+    ) -> Result<<T as Container<'info, 'refs>>::T>
+    where
+        T: Container<'info, 'refs>,
+    {
+        self.check_container_type::<T>()?;
+
+        let head = self.head();
+        let tail = self.tail();
+        let slot = self.slot_of(head);
+        let seed_bump = [seed_bump];
+        let seeds = self.get_seed_at(&slot, Some(&seed_bump));
+        let pda = Pubkey::create_program_address(&seeds, ctx.program_id)?;
+        if account.key != &pda {
+            return Err(
+                error_code!(ErrorCode::AccountCollectionInvalidAddress).with_account(account.key)
+            );
+        }
+
+        let data_len = match data_len {
+            Some(data_len) => data_len,
+            None => T::initial_data_len(),
+        };
+
+        if head - tail < CAPACITY {
+            let allocation_args = AccountAllocationArgs::new(AddressDomain::None);
+            ctx.try_create_pda_with_args(data_len, &allocation_args, &seeds, account, false)?;
+        } else {
+            ctx.try_recycle_account(account, data_len)?;
+            self.meta.set_tail(tail + 1);
+        }
+
+        self.meta.set_head(head + 1);
+        T::try_create(account)
+    }
+
+    /// Inserts an entry using the collection template candidates supplied by
+    /// [`InstructionBuilder::with_collection_templates()`](crate::builder::InstructionBuilder::with_collection_templates)
+    /// or [`InstructionBuilder::with_collection_template_candidates()`](crate::builder::InstructionBuilder::with_collection_template_candidates),
+    /// selecting the candidate matching the slot of the collection head.
+    pub fn try_push_container_from_candidates<'refs, T>(
+        &mut self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        data_len: Option<usize>,
+    ) -> Result<<T as Container<'info, 'refs>>::T>
+    where
+        T: Container<'info, 'refs>,
+    {
+        let candidates = ctx.try_consume_collection_template_candidates()?;
+
+        let slot = self.slot_of(self.head());
+        let mut selected = None;
+        for (bump, account) in candidates.into_iter() {
+            let seed_bump = [bump];
+            let seeds = self.get_seed_at(&slot, Some(&seed_bump));
+            if let Ok(pda) = Pubkey::create_program_address(&seeds, ctx.program_id) {
+                if account.key == &pda {
+                    selected = Some((bump, account));
+                    break;
+                }
+            }
+        }
+
+        let (bump, account) = selected.ok_or_else(|| {
+            error_code!(ErrorCode::AccountCollectionCandidateNotFound)
+                .with_message(&format!("no collection template candidate for slot {slot}"))
+        })?;
+
+        self.try_push_container::<T>(ctx, bump, account, data_len)
+    }
+}
+
+cfg_if! {
+    if #[cfg(not(target_os = "solana"))] {
+
+        use futures::{stream::FuturesOrdered, Stream, StreamExt};
+        use super::stream::{paginate, Page, PageOptions};
+        use super::account::PdaCollectionCreator;
+        use crate::container::interfaces::PdaCollectionCreatorInterface;
+
+        impl<'info,M,const CAPACITY: u64> PdaRingCollectionInterface<'info,M,CAPACITY>
+        where M: RingCollectionMeta
+        {
+            pub fn get_pda_at_slot(&self, program_id : &Pubkey, slot : u64) -> Result<(Pubkey, u8)> {
+                let (address, bump) = Pubkey::find_program_address(
+                    &self.get_seed_at(&slot,None),
+                    program_id
+                );
+
+                Ok((address, bump))
+            }
+
+            /// Returns the address of the entry with the given sequence
+            pub fn get_pubkey_at(&self, program_id : &Pubkey, sequence : u64) -> Result<Pubkey> {
+                Ok(self.get_pda_at_slot(program_id, self.slot_of(sequence))?.0)
+            }
+
+            /// Sequences of retained entries (newest first)
+            pub fn sequences(&self) -> impl Iterator<Item = u64> {
+                (self.tail()..self.head()).rev()
+            }
+
+            pub async fn load_container_at_with_transport<'this,T>(&self, program_id: &Pubkey, sequence: u64, transport: &Arc<Transport>)
+            -> Result<Option<ContainerReference<'this,T>>>
+            where T: kaizen::container::Container<'this,'this>
+            {
+                if sequence < self.tail() || sequence >= self.head() {
+                    return Err(error!("ring collection entry {sequence} is not retained (retained range {}..{})",self.tail(),self.head()));
+                }
+
+                let container_pubkey = self.get_pubkey_at(program_id, sequence)?;
+                load_container_with_transport::<T>(transport,&container_pubkey).await
+            }
+
+            pub async fn load_container_range_with_transport<'this,T>(&self, program_id: &Pubkey, range: std::ops::Range<u64>, transport: &Arc<Transport>)
+            -> Result<Vec<Result<Option<ContainerReference<'this,T>>>>>
+            where T: kaizen::container::Container<'this,'this>
+            {
+                let mut futures = FuturesOrdered::new();
+                for sequence in range {
+                    let f = self.load_container_at_with_transport::<T>(program_id, sequence, transport);
+                    futures.push_back(f);
+                }
+
+                Ok(futures.collect::<Vec<_>>().await)
+            }
+
+            /// Loads up to `count` most recent entries (newest first)
+            pub async fn load_recent_containers<'this,T>(&self, program_id: &Pubkey, count: usize)
+            -> Result<Vec<(u64, ContainerReference<'this,T>)>>
+            where T: kaizen::container::Container<'this,'this>
+            {
+                let transport = Transport::global()?;
+                self.load_recent_containers_with_transport::<T>(program_id, count, &transport).await
+            }
+
+            pub async fn load_recent_containers_with_transport<'this,T>(&self, program_id: &Pubkey, count: usize, transport: &Arc<Transport>)
+            -> Result<Vec<(u64, ContainerReference<'this,T>)>>
+            where T: kaizen::container::Container<'this,'this>
+            {
+                let start = self.head().saturating_sub(count as u64).max(self.tail());
+                let containers = self.load_container_range_with_transport::<T>(program_id, start..self.head(), transport).await?;
+                let mut list = Vec::new();
+                for (sequence, container) in (start..self.head()).zip(containers.into_iter()) {
+                    if let Some(container) = container? {
+                        list.push((sequence, container));
+                    }
+                }
+                list.reverse();
+                Ok(list)
+            }
+
+            /// Creates a paginated stream of retained entries (newest first).
+            /// Page ranges and entry indices are entry sequences.
+            pub fn stream<'a,'this,T>(&'a self, program_id: &Pubkey, page_size: usize)
+            -> Result<impl Stream<Item = Result<Page<'this,T>>> + 'a>
+            where 'this: 'a, T: kaizen::container::Container<'this,'this> + 'a
+            {
+                let transport = Transport::global()?;
+                Ok(self.stream_with_transport::<T>(program_id, page_size, &transport))
+            }
+
+            pub fn stream_with_transport<'a,'this,T>(&'a self, program_id: &Pubkey, page_size: usize, transport: &Arc<Transport>)
+            -> impl Stream<Item = Result<Page<'this,T>>> + 'a
+            where 'this: 'a, T: kaizen::container::Container<'this,'this> + 'a
+            {
+                let program_id = *program_id;
+                let transport = transport.clone();
+                let tail = self.tail() as usize;
+                paginate(self.len(), PageOptions::reverse(page_size), move |range: std::ops::Range<usize>| {
+                    let transport = transport.clone();
+                    async move {
+                        let range = (tail + range.start) as u64..(tail + range.end) as u64;
+                        self.load_container_range_with_transport::<T>(&program_id, range, &transport)
+                            .await?
+                            .into_iter()
+                            .collect::<Result<Vec<_>>>()
+                    }
+                })
+                .map(move |page| page.map(|page| Page {
+                    range: tail + page.range.start..tail + page.range.end,
+                    containers: page.containers.into_iter().map(|(idx, container)| (tail + idx, container)).collect(),
+                }))
+            }
+        }
+
+        // ~~~
+
+        impl<'info,M,const CAPACITY: u64> PdaCollectionCreatorInterface for PdaRingCollectionInterface<'info,M,CAPACITY>
+        where M: RingCollectionMeta {
+            type Creator = PdaCollectionCreator;
+            fn creator(&self, program_id: &Pubkey, number_of_accounts : usize) -> Result<Arc<Self::Creator>> {
+
+                // while the collection is full, the slots of the
+                // oldest entries are supplied for recycling
+                let mut list = Vec::new();
+                for sequence in self.head()..self.head()+number_of_accounts as u64 {
+                    list.push(self.get_pda_at_slot(program_id, self.slot_of(sequence))?);
+                }
+
+                Ok(Arc::new(PdaCollectionCreator::new(list)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kaizen::container::Containers;
+    use kaizen::emulator::interface::EmulatorInterface;
+    use kaizen::emulator::Simulator;
+    use kaizen::prelude::*;
+    use kaizen::result::Result;
+
+    #[container(Containers::RingCollectionTestInterface)]
+    pub struct RingCollectionTestRoot<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        #[collection(seed(b"feed"), container(RingCollectionTestItem))]
+        pub feed: PdaRingCollection<'info, 'refs, 2>,
+    }
+
+    #[container(Containers::RingCollectionTestItemInterface)]
+    pub struct RingCollectionTestItem<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        pub records: Array<'info, 'refs, u64>,
+    }
+
+    #[async_std::test]
+    async fn pda_ring_collection_recycling() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_sequence(0u64);
+
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_generic_account_templates(1)
+            .seal()?;
+        let root = builder.generic_template_accounts()[0].pubkey;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let allocation_args = AccountAllocationArgs::new(AddressDomain::Default);
+                let account = ctx
+                    .try_create_pda(RingCollectionTestRoot::initial_data_len(), &allocation_args)?;
+                RingCollectionTestRoot::try_create(account)?;
+                Ok(())
+            })
+            .await?;
+
+        for sequence in 0..3u64 {
+            let reference = simulator
+                .lookup(&root)
+                .await?
+                .expect("missing root account");
+            let builder = {
+                let mut account_data = reference.account_data.lock()?;
+                let account_info = account_data.into_account_info();
+                let container = RingCollectionTestRoot::try_load(&account_info)?;
+                assert_eq!(container.feed.head(), sequence);
+                InstructionBuilder::new_with_config_for_testing(&config)
+                    .with_handler_accounts(&[AccountMeta::new(root, false)])
+                    .with_collection_templates(&container.feed, 1)
+                    .await?
+                    .seal()?
+            };
+
+            simulator
+                .execute_handler(builder, |ctx: &ContextReference| {
+                    let mut root = RingCollectionTestRoot::try_load(&ctx.handler_accounts[0])?;
+                    let item = root
+                        .feed
+                        .try_push_container_from_candidates::<RingCollectionTestItem>(ctx, None)?;
+                    unsafe { item.records.try_insert(&root.feed.tail())? };
+                    Ok(())
+                })
+                .await?;
+        }
+
+        let reference = simulator
+            .lookup(&root)
+            .await?
+            .expect("missing root account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        let container = RingCollectionTestRoot::try_load(&account_info)?;
+        assert_eq!(container.feed.head(), 3);
+        assert_eq!(container.feed.tail(), 1);
+        assert_eq!(container.feed.len(), 2);
+        assert!(container.feed.is_full());
+        assert_eq!(container.feed.sequences().collect::<Vec<_>>(), vec![2, 1]);
+
+        // the third entry recycles the slot of the first entry
+        let recycled = container.feed.get_pubkey_at(&simulator.program_id(), 2)?;
+        assert_eq!(
+            recycled,
+            container.feed.get_pubkey_at(&simulator.program_id(), 0)?
+        );
+        let reference = simulator
+            .lookup(&recycled)
+            .await?
+            .expect("missing collection account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        let item = RingCollectionTestItem::try_load(&account_info)?;
+        assert_eq!(item.records.as_slice(), &[1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+//!
+//! Client-side paginated streams over account collections.
+//!
+//! Collections expose `stream()` functions returning an async [`Stream`] of
+//! [`Page`]s, each containing containers loaded by a single batch of lookups.
+//!
+
+use crate::container::{Container, ContainerReference};
+use crate::result::Result;
+use futures::{stream, Future, Stream, StreamExt};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageOrder {
+    /// Pages start from the first collection entry
+    Forward,
+    /// Pages start from the last collection entry (entries within each page are reversed as well)
+    Reverse,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageOptions {
+    pub page_size: usize,
+    pub order: PageOrder,
+}
+
+impl Default for PageOptions {
+    fn default() -> Self {
+        PageOptions {
+            page_size: 25,
+            order: PageOrder::Forward,
+        }
+    }
+}
+
+impl PageOptions {
+    pub fn new(page_size: usize, order: PageOrder) -> Self {
+        PageOptions { page_size, order }
+    }
+
+    pub fn forward(page_size: usize) -> Self {
+        PageOptions::new(page_size, PageOrder::Forward)
+    }
+
+    pub fn reverse(page_size: usize) -> Self {
+        PageOptions::new(page_size, PageOrder::Reverse)
+    }
+}
+
+pub struct Page<'this, T>
+where
+    T: Container<'this, 'this>,
+{
+    /// Collection index range covered by the page (using the index
+    /// convention of the collection, see [`paginate_from()`])
+    pub range: Range<usize>,
+    /// Loaded containers along with their collection index
+    /// (missing or removed entries are skipped)
+    pub containers: Vec<(usize, ContainerReference<'this, T>)>,
+}
+
+impl<'this, T> Page<'this, T>
+where
+    T: Container<'this, 'this>,
+{
+    pub fn len(&self) -> usize {
+        self.containers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+}
+
+/// Splits a collection of `len` entries into page ranges. Reverse pages are
+/// aligned to the end of the collection, so the first page is always full.
+pub fn page_ranges(len: usize, options: &PageOptions) -> Vec<Range<usize>> {
+    let page_size = options.page_size.max(1);
+    match options.order {
+        PageOrder::Forward => (0..len)
+            .step_by(page_size)
+            .map(|start| start..(start + page_size).min(len))
+            .collect(),
+        PageOrder::Reverse => {
+            let mut ranges = Vec::new();
+            let mut end = len;
+            while end > 0 {
+                let start = end.saturating_sub(page_size);
+                ranges.push(start..end);
+                end = start;
+            }
+            ranges
+        }
+    }
+}
+
+/// Creates a paginated stream over a collection of `len` entries. The `load`
+/// function receives a page range and loads containers of the range in a
+/// single batch, returning `None` for missing entries.
+pub fn paginate<'a, 'this, T, F, Fut>(
+    len: usize,
+    options: PageOptions,
+    load: F,
+) -> impl Stream<Item = Result<Page<'this, T>>> + 'a
+where
+    'this: 'a,
+    T: Container<'this, 'this> + 'a,
+    F: Fn(Range<usize>) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<Option<ContainerReference<'this, T>>>>> + 'a,
+{
+    paginate_from(0, len, options, load)
+}
+
+/// Creates a paginated stream over a collection of `len` entries indexed
+/// from `first` (such as proxy collections, holding entries at `1..=len`).
+/// Page ranges passed to `load` and reported in each [`Page`] use the same
+/// indices as the collection.
+pub fn paginate_from<'a, 'this, T, F, Fut>(
+    first: usize,
+    len: usize,
+    options: PageOptions,
+    load: F,
+) -> impl Stream<Item = Result<Page<'this, T>>> + 'a
+where
+    'this: 'a,
+    T: Container<'this, 'this> + 'a,
+    F: Fn(Range<usize>) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<Option<ContainerReference<'this, T>>>>> + 'a,
+{
+    let reverse = options.order == PageOrder::Reverse;
+    let ranges = page_ranges(len, &options)
+        .into_iter()
+        .map(move |range| range.start + first..range.end + first);
+    stream::iter(ranges).then(move |range| {
+        let containers = load(range.clone());
+        async move {
+            let mut containers = range
+                .clone()
+                .zip(containers.await?)
+                .filter_map(|(idx, container)| container.map(|container| (idx, container)))
+                .collect::<Vec<_>>();
+            if reverse {
+                containers.reverse();
+            }
+            Ok(Page { range, containers })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collection_stream_page_ranges() {
+        assert_eq!(
+            page_ranges(10, &PageOptions::forward(4)),
+            vec![0..4, 4..8, 8..10]
+        );
+        assert_eq!(
+            page_ranges(10, &PageOptions::reverse(4)),
+            vec![6..10, 2..6, 0..2]
+        );
+        assert!(page_ranges(0, &PageOptions::default()).is_empty());
+        assert_eq!(
+            page_ranges(3, &PageOptions::forward(0)),
+            vec![0..1, 1..2, 2..3]
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//!
+//! Client-side navigation of collection trees.
+//!
+//! Containers may own child collections (declared using `#[collection()]`)
+//! whose entries are PDAs derived from the address of the owning container,
+//! forming a tree (e.g. threads containing replies containing reactions).
+//! [`CollectionNode`] uses container layouts registered by the `#[container]`
+//! macro to enumerate child collections of any node, allowing the tree to be
+//! walked with descendants loaded lazily (one level at a time) and counted
+//! without loading the leaf accounts.
+//!
+
+use super::meta::{is_tombstoned, PdaCollectionMeta, PdaCollectionRemovedMeta};
+use crate::accounts::AccountData;
+use crate::container::registry::{self, ContainerDeclaration};
+use crate::container::segment::SegmentStore;
+use crate::container::ContainerHeader;
+use crate::error::*;
+use crate::result::Result;
+use crate::transport::{Interface, Transport};
+use futures::future::join_all;
+use solana_program::pubkey::Pubkey;
+use std::mem;
+use std::sync::Arc;
+
+/// Child collection owned by a [`CollectionNode`]
+#[derive(Debug, Clone)]
+pub struct ChildCollection {
+    pub name: &'static str,
+    pub seed: &'static [u8],
+    /// Container declared for collection entries
+    pub container: Option<&'static str>,
+    /// Number of indices allocated by the collection (including removed entries)
+    pub len: u64,
+    pub removed: u64,
+    /// Tombstone bitmap of removed entries
+    pub tombstones: Vec<u8>,
+}
+
+impl ChildCollection {
+    /// Number of entries that have not been removed
+    pub fn live_len(&self) -> u64 {
+        self.len - self.removed
+    }
+
+    pub fn is_removed(&self, index: u64) -> bool {
+        is_tombstoned(&self.tombstones, index)
+    }
+
+    /// Indices of entries that have not been removed
+    pub fn live_indices(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).filter(|index| !self.is_removed(*index))
+    }
+}
+
+/// Container participating in a collection tree
+#[derive(Debug, Clone)]
+pub struct CollectionNode {
+    pub pubkey: Pubkey,
+    /// Program owning the node (and deriving addresses of its children)
+    pub program_id: Pubkey,
+    pub declaration: ContainerDeclaration,
+    /// Depth of the node relative to the node the navigation has started from
+    pub depth: usize,
+    pub collections: Vec<ChildCollection>,
+}
+
+impl CollectionNode {
+    /// Creates a node from container account data, resolving the container
+    /// layout using the container registry.
+    pub fn try_from_account_data(account_data: &AccountData, depth: usize) -> Result<Self> {
+        let declaration = registry::try_lookup_account_data(account_data)?;
+        Self::try_from_declaration(&declaration, account_data, depth)
+    }
+
+    pub fn try_from_declaration(
+        declaration: &ContainerDeclaration,
+        account_data: &AccountData,
+        depth: usize,
+    ) -> Result<Self> {
+        let meta_size = declaration.meta.as_ref().map(|meta| meta.size).unwrap_or(0);
+        let offset = mem::size_of::<ContainerHeader>() + meta_size;
+        if account_data.data_len() < offset {
+            return Err(error_code!(ErrorCode::AccountSizeTooSmall).with_account(&account_data.key));
+        }
+
+        let mut account_data_ = account_data.clone();
+        let account_info = account_data_.into_account_info();
+        let store = SegmentStore::try_load(&account_info, offset)?;
+
+        let mut collections = Vec::new();
+        for (idx, descriptor) in declaration.segments.iter().enumerate() {
+            let seed = match descriptor.collection_seed {
+                Some(seed) if descriptor.base_type_name() == "PdaCollection" => seed,
+                _ => continue,
+            };
+
+            let data = store.try_get_segment_ref_u8(idx + 1)?;
+            if data.len() < mem::size_of::<PdaCollectionMeta>() {
+                return Err(error_code!(ErrorCode::AccountCollectionInvalidAccount)
+                    .with_account(&account_data.key)
+                    .with_message(&format!(
+                        "collection segment '{}' is too small",
+                        descriptor.name
+                    )));
+            }
+            let meta = unsafe { &*data.as_ptr().cast::<PdaCollectionMeta>() };
+            // collection segments created before removal tracking hold no removal record
+            let (removed, tombstones) = data
+                .get(mem::size_of::<PdaCollectionMeta>()..)
+                .filter(|data| data.len() >= mem::size_of::<PdaCollectionRemovedMeta>())
+                .map(|data| {
+                    let removed = unsafe { &*data.as_ptr().cast::<PdaCollectionRemovedMeta>() }
+                        .get_collection_removed();
+                    (
+                        removed,
+                        data[mem::size_of::<PdaCollectionRemovedMeta>()..].to_vec(),
+                    )
+                })
+                .unwrap_or_default();
+            collections.push(ChildCollection {
+                name: descriptor.name,
+                seed,
+                container: descriptor.collection_container,
+                len: meta.get_collection_len(),
+                removed,
+                tombstones,
+            });
+        }
+
+        Ok(CollectionNode {
+            pubkey: account_data.key,
+            program_id: account_data.owner,
+            declaration: declaration.clone(),
+            depth,
+            collections,
+        })
+    }
+
+    pub fn collection(&self, name: &str) -> Option<&ChildCollection> {
+        self.collections
+            .iter()
+            .find(|collection| collection.name == name)
+    }
+
+    fn try_collection(&self, name: &str) -> Result<&ChildCollection> {
+        self.collection(name).ok_or_else(|| {
+            error_code!(ErrorCode::AccountCollectionNotFound)
+                .with_account(&self.pubkey)
+                .with_message(&format!(
+                    "container {} has no collection '{name}'",
+                    self.declaration.name
+                ))
+        })
+    }
+
+    /// Derives the address of the child at `index` (matching the seeds
+    /// used by `PdaCollectionInterface` within the program)
+    pub fn child_pda(&self, collection: &ChildCollection, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[self.pubkey.as_ref(), collection.seed, &index.to_le_bytes()],
+            &self.program_id,
+        )
+    }
+
+    /// Addresses of children held by the named collection (excluding removed entries)
+    pub fn child_pubkeys(&self, name: &str) -> Result<Vec<Pubkey>> {
+        let collection = self.try_collection(name)?;
+        Ok(collection
+            .live_indices()
+            .map(|index| self.child_pda(collection, index).0)
+            .collect())
+    }
+
+    /// Number of direct children (across all collections)
+    pub fn count_children(&self) -> u64 {
+        self.collections
+            .iter()
+            .map(|collection| collection.live_len())
+            .sum()
+    }
+
+    pub async fn load(pubkey: &Pubkey) -> Result<Option<Self>> {
+        let transport = Transport::global()?;
+        Self::load_with_transport(pubkey, &transport).await
+    }
+
+    pub async fn load_with_transport(
+        pubkey: &Pubkey,
+        transport: &Arc<Transport>,
+    ) -> Result<Option<Self>> {
+        match transport.lookup(pubkey).await? {
+            Some(reference) => {
+                let account_data = reference.account_data.lock()?;
+                Ok(Some(Self::try_from_account_data(&account_data, 0)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn load_nodes_with_transport(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        transport: &Arc<Transport>,
+    ) -> Result<Vec<CollectionNode>> {
+        let lookups = pubkeys.iter().map(|pubkey| transport.lookup(pubkey));
+        let mut nodes = Vec::new();
+        for reference in join_all(lookups).await {
+            if let Some(reference) = reference? {
+                let account_data = reference.account_data.lock()?;
+                // skip accounts reassigned since the node has been loaded
+                if account_data.owner != self.program_id || account_data.container_type().is_none()
+                {
+                    continue;
+                }
+                nodes.push(Self::try_from_account_data(&account_data, self.depth + 1)?);
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Loads children held by the named collection
+    pub async fn load_children(&self, name: &str) -> Result<Vec<CollectionNode>> {
+        let transport = Transport::global()?;
+        self.load_children_with_transport(name, &transport).await
+    }
+
+    pub async fn load_children_with_transport(
+        &self,
+        name: &str,
+        transport: &Arc<Transport>,
+    ) -> Result<Vec<CollectionNode>> {
+        let pubkeys = self.child_pubkeys(name)?;
+        self.load_nodes_with_transport(pubkeys, transport).await
+    }
+
+    /// Loads children held by all collections of the node
+    pub async fn load_all_children_with_transport(
+        &self,
+        transport: &Arc<Transport>,
+    ) -> Result<Vec<CollectionNode>> {
+        let mut pubkeys = Vec::new();
+        for collection in self.collections.iter() {
+            pubkeys.extend(
+                collection
+                    .live_indices()
+                    .map(|index| self.child_pda(collection, index).0),
+            );
+        }
+        self.load_nodes_with_transport(pubkeys, transport).await
+    }
+
+    /// Walks the tree depth-first (starting with this node). Children of a
+    /// node are loaded only if the `visitor` returns `true` for the node.
+    pub async fn walk_with_transport<F>(
+        &self,
+        transport: &Arc<Transport>,
+        mut visitor: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&CollectionNode) -> bool,
+    {
+        let mut stack = vec![self.clone()];
+        while let Some(node) = stack.pop() {
+            if visitor(&node) && node.count_children() > 0 {
+                let mut children = node.load_all_children_with_transport(transport).await?;
+                children.reverse();
+                stack.extend(children);
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts descendants up to `max_depth` levels below this node. Only nodes
+    /// above the last level are loaded (the last level is counted using the
+    /// collection lengths of its parents).
+    pub async fn count_descendants_with_transport(
+        &self,
+        transport: &Arc<Transport>,
+        max_depth: usize,
+    ) -> Result<u64> {
+        let max_depth = self.depth + max_depth;
+        let mut count = 0;
+        self.walk_with_transport(transport, |node| {
+            if node.depth < max_depth {
+                count += node.count_children();
+            }
+            node.depth + 1 < max_depth
+        })
+        .await?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::collection::PdaCollectionReference;
+    use crate::container::layout::SegmentDescriptor;
+    use crate::container::segment::Layout;
+    use crate::pubkey::generate_random_pubkey;
+
+    static SEGMENTS: &[SegmentDescriptor] = &[
+        SegmentDescriptor::new("title", "Utf8String<'info, 'refs>"),
+        SegmentDescriptor::new("replies", "PdaCollection<'info, 'refs>")
+            .with_collection(b"replies", Some("Reply")),
+    ];
+
+    #[test]
+    fn collection_tree_node_children() -> Result<()> {
+        let declaration =
+            ContainerDeclaration::new(0xe0000ffe, "Thread").with_layout(None, SEGMENTS);
+
+        let layout = Layout::<u16>::from(&[
+            4,
+            mem::size_of::<PdaCollectionMeta>() + mem::size_of::<PdaCollectionRemovedMeta>() + 1,
+        ]);
+        let data_len = mem::size_of::<ContainerHeader>() + layout.data_len();
+        let program_id = generate_random_pubkey();
+        let mut account_data =
+            AccountData::new_allocated_for_program(generate_random_pubkey(), program_id, data_len);
+        {
+            let account_info = account_data.into_account_info();
+            let store = SegmentStore::try_create(
+                &account_info,
+                mem::size_of::<ContainerHeader>(),
+                &layout,
+            )?;
+            let data = store.try_get_segment_ref_mut_u8(2)?;
+            let meta = unsafe { &mut *data.as_mut_ptr().cast::<PdaCollectionMeta>() };
+            meta.set_collection_len(3);
+            let removed_meta = unsafe {
+                &mut *data[mem::size_of::<PdaCollectionMeta>()..]
+                    .as_mut_ptr()
+                    .cast::<PdaCollectionRemovedMeta>()
+            };
+            removed_meta.set_collection_removed(1);
+            // tombstone of the entry at index 1
+            *data.last_mut().unwrap() = 0b10;
+        }
+
+        let node = CollectionNode::try_from_declaration(&declaration, &account_data, 0)?;
+        assert_eq!(node.collections.len(), 1);
+        let replies = node.collection("replies").expect("missing collection");
+        assert_eq!(replies.container, Some("Reply"));
+        assert_eq!(replies.live_len(), 2);
+        assert!(replies.is_removed(1));
+        assert!(!replies.is_removed(2));
+        assert_eq!(node.count_children(), 2);
+        assert!(node.child_pubkeys("reactions").is_err());
+
+        // children are derived from the parent address as within the program
+        let account_info = account_data.into_account_info();
+        let mut meta: PdaCollectionMeta = unsafe { mem::zeroed() };
+        let collection =
+            PdaCollectionReference::try_load_from_meta(&mut meta, &account_info, b"replies", None)?;
+        let pubkeys = node.child_pubkeys("replies")?;
+        assert_eq!(
+            pubkeys,
+            vec![
+                collection.get_pubkey_at(&program_id, 0)?,
+                collection.get_pubkey_at(&program_id, 2)?,
+            ]
+        );
+        Ok(())
+    }
+}
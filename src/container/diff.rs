@@ -0,0 +1,346 @@
+//!
+//! Segment-level diffing of container account data snapshots.
+//!
+//! Two [`AccountData`] snapshots of the same container are compared segment
+//! by segment using the [`SegmentStore`] index. `Array` and `Map` segments
+//! (as declared in the container layout registered by the `#[container]` macro)
+//! are additionally compared record by record.
+//!
+
+use crate::accounts::{AccountData, AccountDataReference};
+use crate::container::array::ArrayMeta;
+use crate::container::layout::array_records;
+use crate::container::registry::{self, ContainerDeclaration};
+use crate::container::segment::SegmentStore;
+use crate::container::ContainerHeader;
+use crate::error::*;
+use crate::result::Result;
+use solana_program::pubkey::Pubkey;
+use std::mem;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordChange {
+    Added {
+        idx: usize,
+        data: Vec<u8>,
+    },
+    Removed {
+        idx: usize,
+        data: Vec<u8>,
+    },
+    Modified {
+        idx: usize,
+        before: Vec<u8>,
+        after: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentChange {
+    Added {
+        idx: usize,
+        name: Option<&'static str>,
+        data: Vec<u8>,
+    },
+    Removed {
+        idx: usize,
+        name: Option<&'static str>,
+        data: Vec<u8>,
+    },
+    Modified {
+        idx: usize,
+        name: Option<&'static str>,
+        before: Vec<u8>,
+        after: Vec<u8>,
+        /// Record-level changes of `Array` and `Map` segments
+        records: Option<Vec<RecordChange>>,
+    },
+}
+
+impl SegmentChange {
+    /// Index of the segment (container fields, starting from `0`)
+    pub fn idx(&self) -> usize {
+        match self {
+            SegmentChange::Added { idx, .. }
+            | SegmentChange::Removed { idx, .. }
+            | SegmentChange::Modified { idx, .. } => *idx,
+        }
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            SegmentChange::Added { name, .. }
+            | SegmentChange::Removed { name, .. }
+            | SegmentChange::Modified { name, .. } => *name,
+        }
+    }
+}
+
+/// Differences between two snapshots of a container account
+#[derive(Debug, Clone)]
+pub struct AccountDataDiff {
+    pub key: Pubkey,
+    pub container_type: u32,
+    /// Lamports before and after (if changed)
+    pub lamports: Option<(u64, u64)>,
+    /// Container versions before and after (if changed)
+    pub version: Option<(u16, u16)>,
+    pub meta_changed: bool,
+    pub segments: Vec<SegmentChange>,
+}
+
+impl AccountDataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.lamports.is_none()
+            && self.version.is_none()
+            && !self.meta_changed
+            && self.segments.is_empty()
+    }
+
+    /// Returns the change of the segment with the given field name
+    pub fn segment(&self, name: &str) -> Option<&SegmentChange> {
+        self.segments
+            .iter()
+            .find(|change| change.name() == Some(name))
+    }
+}
+
+/// Returns the segment data up to the last record of a record-based segment,
+/// excluding spare capacity (which may hold data of removed records).
+fn records_data(data: &[u8], record_size: usize) -> &[u8] {
+    if data.len() < mem::size_of::<ArrayMeta>() {
+        return data;
+    }
+    let records = ArrayMeta::from_buffer(data, 0).get_records() as usize;
+    data.get(..mem::size_of::<ArrayMeta>() + records * record_size)
+        .unwrap_or(data)
+}
+
+fn diff_records(
+    before: &[u8],
+    after: &[u8],
+    record_size: Option<usize>,
+) -> Option<Vec<RecordChange>> {
+    let (_, before) = array_records(before, record_size)?;
+    let (_, after) = array_records(after, record_size)?;
+
+    let mut changes = Vec::new();
+    for idx in 0..before.len().max(after.len()) {
+        match (before.get(idx), after.get(idx)) {
+            (Some(before), Some(after)) if before != after => {
+                changes.push(RecordChange::Modified {
+                    idx,
+                    before: before.to_vec(),
+                    after: after.to_vec(),
+                });
+            }
+            (Some(before), None) => changes.push(RecordChange::Removed {
+                idx,
+                data: before.to_vec(),
+            }),
+            (None, Some(after)) => changes.push(RecordChange::Added {
+                idx,
+                data: after.to_vec(),
+            }),
+            _ => {}
+        }
+    }
+    Some(changes)
+}
+
+/// Compares two snapshots of a container account using the supplied declaration
+pub fn try_diff_with_declaration(
+    declaration: &ContainerDeclaration,
+    before: &AccountData,
+    after: &AccountData,
+) -> Result<AccountDataDiff> {
+    for account_data in [before, after] {
+        if account_data.container_type() != Some(declaration.container_type_id) {
+            return Err(error_code!(ErrorCode::ContainerTypeMismatch)
+                .with_account(&account_data.key)
+                .with_message(&format!(
+                    "unable to diff account data: expecting container {}",
+                    declaration
+                )));
+        }
+    }
+
+    let header_size = mem::size_of::<ContainerHeader>();
+    let meta_size = declaration.meta.as_ref().map(|meta| meta.size).unwrap_or(0);
+    let meta_range = header_size..header_size + meta_size;
+
+    let mut before_data = before.clone();
+    let before_account_info = before_data.into_account_info();
+    let before_store = SegmentStore::try_load(&before_account_info, header_size + meta_size)?;
+    let mut after_data = after.clone();
+    let after_account_info = after_data.into_account_info();
+    let after_store = SegmentStore::try_load(&after_account_info, header_size + meta_size)?;
+
+    let mut segments = Vec::new();
+    for idx in 1..before_store.len().max(after_store.len()) {
+        let descriptor = declaration.segments.get(idx - 1);
+        let name = descriptor.map(|descriptor| descriptor.name);
+        let record_based = matches!(
+            descriptor.map(|descriptor| descriptor.base_type_name()),
+            Some("Array") | Some("Map") | Some("CollectionIndex")
+        );
+        let record_size = descriptor
+            .and_then(|descriptor| descriptor.record_size)
+            .filter(|_| record_based);
+        let before = (idx < before_store.len()).then(|| {
+            let data = before_store.get_segment_ref_u8(idx);
+            record_size.map_or(data, |record_size| records_data(data, record_size))
+        });
+        let after = (idx < after_store.len()).then(|| {
+            let data = after_store.get_segment_ref_u8(idx);
+            record_size.map_or(data, |record_size| records_data(data, record_size))
+        });
+        match (before, after) {
+            (Some(before), Some(after)) if before != after => {
+                let records = if record_based {
+                    diff_records(before, after, record_size)
+                } else {
+                    None
+                };
+                segments.push(SegmentChange::Modified {
+                    idx: idx - 1,
+                    name,
+                    before: before.to_vec(),
+                    after: after.to_vec(),
+                    records,
+                });
+            }
+            (Some(before), None) => segments.push(SegmentChange::Removed {
+                idx: idx - 1,
+                name,
+                data: before.to_vec(),
+            }),
+            (None, Some(after)) => segments.push(SegmentChange::Added {
+                idx: idx - 1,
+                name,
+                data: after.to_vec(),
+            }),
+            _ => {}
+        }
+    }
+
+    let version = (
+        before_store.get_container_version(),
+        after_store.get_container_version(),
+    );
+
+    Ok(AccountDataDiff {
+        key: after.key,
+        container_type: declaration.container_type_id,
+        lamports: (before.lamports != after.lamports).then_some((before.lamports, after.lamports)),
+        version: (version.0 != version.1).then_some(version),
+        meta_changed: before.data()[meta_range.clone()] != after.data()[meta_range],
+        segments,
+    })
+}
+
+/// Compares two snapshots of a container account registered in the container registry
+pub fn try_diff(before: &AccountData, after: &AccountData) -> Result<AccountDataDiff> {
+    let declaration = registry::try_lookup_account_data(after)?;
+    try_diff_with_declaration(&declaration, before, after)
+}
+
+/// Compares the account data of two references (e.g. a cached
+/// reference and its replacement received from the network)
+pub fn try_diff_references(
+    before: &AccountDataReference,
+    after: &AccountDataReference,
+) -> Result<AccountDataDiff> {
+    let before = before.account_data.lock()?.clone();
+    let after = after.account_data.lock()?.clone();
+    try_diff(&before, &after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::array::Array;
+    use crate::container::layout::SegmentDescriptor;
+    use crate::container::segment::Layout;
+    use crate::pubkey::generate_random_pubkey;
+
+    const CONTAINER_TYPE: u32 = 0xe0000ffe;
+
+    static SEGMENTS: &[SegmentDescriptor] = &[
+        SegmentDescriptor::new("name", "Utf8String<'info, 'refs>"),
+        SegmentDescriptor::new("records", "Array<'info, 'refs, u16>").with_record_size(2),
+    ];
+
+    fn create_account(key: Pubkey, name: &[u8], records: &[u16]) -> Result<AccountData> {
+        let layout = Layout::<u16>::from(&[name.len(), Array::<u16>::calculate_data_len(0)]);
+        let mut account_data = AccountData::new_allocated_for_program(
+            key,
+            generate_random_pubkey(),
+            mem::size_of::<ContainerHeader>() + layout.data_len(),
+        );
+        account_data.data_mut()[0..4].copy_from_slice(&CONTAINER_TYPE.to_le_bytes());
+        {
+            let account_info = account_data.into_account_info();
+            let store = SegmentStore::try_create(
+                &account_info,
+                mem::size_of::<ContainerHeader>(),
+                &layout,
+            )?;
+            store.try_get_segment_ref_mut_u8(1)?.copy_from_slice(name);
+            let array = store.try_create_linear_store::<u16>(2)?;
+            unsafe { array.try_extend_from_slice(records)? };
+            assert_eq!(array.get_meta().get_records() as usize, records.len());
+        }
+        Ok(account_data)
+    }
+
+    #[test]
+    fn container_account_data_diff() -> Result<()> {
+        let declaration =
+            ContainerDeclaration::new(CONTAINER_TYPE, "TestContainer").with_layout(None, SEGMENTS);
+        let key = generate_random_pubkey();
+        let before = create_account(key, b"name", &[1, 2, 3])?;
+        let after = create_account(key, b"name", &[1, 5, 3, 4])?;
+
+        let diff = try_diff_with_declaration(&declaration, &before, &before)?;
+        assert!(diff.is_empty());
+
+        // data of removed records left in the spare segment capacity is ignored
+        let mut shrunk = create_account(key, b"name", &[1, 2, 3, 9])?;
+        {
+            let account_info = shrunk.into_account_info();
+            let store = SegmentStore::try_load(&account_info, mem::size_of::<ContainerHeader>())?;
+            let array = store.try_get_linear_store::<u16>(2)?;
+            unsafe { array.try_remove_at(3, false)? };
+        }
+        let diff = try_diff_with_declaration(&declaration, &before, &shrunk)?;
+        assert!(diff.is_empty());
+
+        let diff = try_diff_with_declaration(&declaration, &before, &after)?;
+        assert_eq!(diff.segments.len(), 1);
+        assert!(diff.segment("name").is_none());
+        match diff.segment("records") {
+            Some(SegmentChange::Modified {
+                records: Some(records),
+                ..
+            }) => {
+                assert_eq!(
+                    records,
+                    &vec![
+                        RecordChange::Modified {
+                            idx: 1,
+                            before: vec![2, 0],
+                            after: vec![5, 0]
+                        },
+                        RecordChange::Added {
+                            idx: 3,
+                            data: vec![4, 0]
+                        },
+                    ]
+                );
+            }
+            change => panic!("unexpected segment change: {change:?}"),
+        }
+        Ok(())
+    }
+}
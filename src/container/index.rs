@@ -0,0 +1,282 @@
+//!
+//! Segment-based secondary index over collection entries
+//!
+//! [`CollectionIndex`] keeps `(key, pubkey)` records sorted by a fixed-size key
+//! extracted from child containers (such as a timestamp or a category id) using
+//! the [`IndexKey`] trait. The index is declared as a segment of the container
+//! holding the collection and is maintained by the `*_with_index()` collection
+//! functions, allowing clients to query key ranges without loading child accounts.
+//!
+//! Declaring the collection as `#[collection(seed(b"..."), index(<index segment>))]`
+//! generates `try_create_<collection>_container()`, `try_insert_<collection>_container()`
+//! and `try_remove_<collection>_container()` container functions maintaining the index.
+//!
+
+use crate::container::array::{Array, ArrayMeta};
+use crate::container::segment::Segment;
+use crate::container::Container;
+use crate::error::*;
+use crate::result::Result;
+use solana_program::pubkey::Pubkey;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::{mem, rc::Rc};
+
+/// Provides the value of the indexed field of a container
+pub trait IndexKey<K> {
+    fn index_key(&self) -> K;
+}
+
+#[repr(packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct IndexRecord<K>
+where
+    K: Copy,
+{
+    pub key: K,
+    pub pubkey: Pubkey,
+}
+
+// fields of packed records are returned by value (references to them may be unaligned)
+impl<K> IndexRecord<K>
+where
+    K: Copy,
+{
+    pub fn key(&self) -> K {
+        self.key
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+}
+
+#[derive(Debug)]
+pub struct CollectionIndex<'info, 'refs, K>
+where
+    K: Copy + Ord + 'info,
+{
+    pub records: Array<'info, 'refs, IndexRecord<K>>,
+    phantom: PhantomData<&'refs K>,
+}
+
+impl<'info, 'refs, K> CollectionIndex<'info, 'refs, K>
+where
+    K: Copy + Ord + 'info,
+{
+    pub fn try_create_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<CollectionIndex<'info, 'refs, K>> {
+        Ok(CollectionIndex {
+            records: Array::try_create_from_segment(segment)?,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn try_load_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<CollectionIndex<'info, 'refs, K>> {
+        Ok(CollectionIndex {
+            records: Array::try_load_from_segment(segment)?,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn data_len_min() -> usize {
+        mem::size_of::<ArrayMeta>()
+    }
+
+    pub fn calculate_data_len(records: usize) -> usize {
+        Array::<IndexRecord<K>>::calculate_data_len(records)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &'info [IndexRecord<K>] {
+        self.records.as_slice()
+    }
+
+    fn search(&self, key: &K, pubkey: &Pubkey) -> std::result::Result<usize, usize> {
+        self.as_slice()
+            .binary_search_by(|record| (&record.key(), &record.pubkey()).cmp(&(key, pubkey)))
+    }
+
+    /// Returns records with keys within the supplied range
+    pub fn range<R>(&self, range: R) -> &'info [IndexRecord<K>]
+    where
+        R: RangeBounds<K>,
+    {
+        let records = self.as_slice();
+        let start = match range.start_bound() {
+            Bound::Included(key) => records.partition_point(|record| record.key() < *key),
+            Bound::Excluded(key) => records.partition_point(|record| record.key() <= *key),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => records.partition_point(|record| record.key() <= *key),
+            Bound::Excluded(key) => records.partition_point(|record| record.key() < *key),
+            Bound::Unbounded => records.len(),
+        };
+        &records[start..end.max(start)]
+    }
+
+    /// Returns pubkeys of entries with keys within the supplied range
+    pub fn range_pubkeys<R>(&self, range: R) -> Vec<Pubkey>
+    where
+        R: RangeBounds<K>,
+    {
+        self.range(range)
+            .iter()
+            .map(|record| record.pubkey)
+            .collect()
+    }
+
+    pub fn contains(&self, key: &K, pubkey: &Pubkey) -> bool {
+        self.search(key, pubkey).is_ok()
+    }
+
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_insert(&self, key: &K, pubkey: &Pubkey) -> Result<()> {
+        match self.search(key, pubkey) {
+            Ok(_) => Err(error_code!(ErrorCode::CollectionIndexCollision).with_account(pubkey)),
+            Err(idx) => self.records.try_insert_at(
+                idx,
+                &IndexRecord {
+                    key: *key,
+                    pubkey: *pubkey,
+                },
+            ),
+        }
+    }
+
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove(&self, key: &K, pubkey: &Pubkey) -> Result<()> {
+        match self.search(key, pubkey) {
+            Ok(idx) => self.records.try_remove_at(idx, true),
+            Err(_) => {
+                Err(error_code!(ErrorCode::CollectionIndexRecordNotFound).with_account(pubkey))
+            }
+        }
+    }
+
+    /// Re-indexes an entry after the value of its indexed field has changed
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_update(&self, previous_key: &K, key: &K, pubkey: &Pubkey) -> Result<()> {
+        self.try_remove(previous_key, pubkey)?;
+        self.try_insert(key, pubkey)
+    }
+
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_insert_container<'i, 'r, C>(&self, container: &C) -> Result<()>
+    where
+        C: Container<'i, 'r> + IndexKey<K>,
+    {
+        self.try_insert(&container.index_key(), container.pubkey())
+    }
+
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove_container<'i, 'r, C>(&self, container: &C) -> Result<()>
+    where
+        C: Container<'i, 'r> + IndexKey<K>,
+    {
+        self.try_remove(&container.index_key(), container.pubkey())
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(not(target_os = "solana"))] {
+        use crate::container::ContainerReference;
+        use crate::transport::load_containers;
+
+        impl<'info, 'refs, K> CollectionIndex<'info, 'refs, K>
+        where
+            K: Copy + Ord + 'info,
+        {
+            /// Loads containers of entries with keys within the supplied range
+            /// (only the accounts referenced by the matching index records are fetched)
+            pub async fn load_containers_in_range<'this, C, R>(
+                &self,
+                range: R,
+            ) -> Result<Vec<Result<Option<ContainerReference<'this, C>>>>>
+            where
+                C: Container<'this, 'this>,
+                R: RangeBounds<K>,
+            {
+                load_containers::<C>(&self.range_pubkeys(range)).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+    use crate::container::segment::{Layout, SegmentStore};
+    use crate::pubkey::generate_random_pubkey;
+
+    #[test]
+    fn collection_index_range() -> Result<()> {
+        let layout = Layout::<u16>::from(&[CollectionIndex::<u64>::data_len_min()]);
+        let mut instance = MockAccountDataInstance::new(layout.data_len());
+        let account = instance.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+
+        let index = CollectionIndex::<u64>::try_create_from_segment(store.try_get_segment_at(1)?)?;
+        let entries = [30u64, 10, 20, 20, 40]
+            .into_iter()
+            .map(|key| (key, generate_random_pubkey()))
+            .collect::<Vec<_>>();
+        for (key, pubkey) in entries.iter() {
+            unsafe { index.try_insert(key, pubkey)? };
+        }
+        assert!(unsafe { index.try_insert(&entries[0].0, &entries[0].1) }.is_err());
+
+        let keys = |records: &[IndexRecord<u64>]| -> Vec<u64> {
+            records.iter().map(|record| record.key).collect()
+        };
+        assert_eq!(keys(index.range(..)), vec![10, 20, 20, 30, 40]);
+        assert_eq!(keys(index.range(20..40)), vec![20, 20, 30]);
+        assert_eq!(keys(index.range(20..=40)), vec![20, 20, 30, 40]);
+        assert_eq!(index.range_pubkeys(25..35), vec![entries[0].1]);
+
+        unsafe { index.try_update(&30, &50, &entries[0].1)? };
+        assert_eq!(keys(index.range(30..)), vec![40, 50]);
+        unsafe { index.try_remove(&10, &entries[1].1)? };
+        assert!(!index.contains(&10, &entries[1].1));
+        assert_eq!(index.len(), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+//!
+//! Container layout descriptors (registered by the `#[container]` macro)
+//! and generic decoding of container account data into JSON values.
+//!
+
+use crate::accounts::AccountData;
+use crate::container::array::ArrayMeta;
+use crate::container::registry::ContainerDeclaration;
+use crate::container::segment::SegmentStore;
+use crate::container::ContainerHeader;
+use crate::error::*;
+use crate::result::Result;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::mem;
+
+/// Descriptor of the container `meta` structure
+#[derive(Debug, Clone, Serialize)]
+pub struct MetaDescriptor {
+    pub type_name: &'static str,
+    pub size: usize,
+}
+
+impl MetaDescriptor {
+    pub const fn new(type_name: &'static str, size: usize) -> Self {
+        MetaDescriptor { type_name, size }
+    }
+}
+
+/// Descriptor of a container segment
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentDescriptor {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub fixed: bool,
+    pub flex: bool,
+    pub reserve: Option<&'static str>,
+    pub collection_seed: Option<&'static [u8]>,
+    pub collection_container: Option<&'static str>,
+    /// Size of records held by `Array`, `Map` and `CollectionIndex` segments
+    pub record_size: Option<usize>,
+}
+
+impl SegmentDescriptor {
+    pub const fn new(name: &'static str, type_name: &'static str) -> Self {
+        SegmentDescriptor {
+            name,
+            type_name,
+            fixed: false,
+            flex: false,
+            reserve: None,
+            collection_seed: None,
+            collection_container: None,
+            record_size: None,
+        }
+    }
+
+    pub const fn with_attributes(
+        mut self,
+        fixed: bool,
+        flex: bool,
+        reserve: Option<&'static str>,
+    ) -> Self {
+        self.fixed = fixed;
+        self.flex = flex;
+        self.reserve = reserve;
+        self
+    }
+
+    pub const fn with_collection(
+        mut self,
+        seed: &'static [u8],
+        container: Option<&'static str>,
+    ) -> Self {
+        self.collection_seed = Some(seed);
+        self.collection_container = container;
+        self
+    }
+
+    pub const fn with_record_size(mut self, record_size: usize) -> Self {
+        self.record_size = Some(record_size);
+        self
+    }
+
+    /// Base type name without generic arguments (`Array`, `Utf8String`, ...)
+    pub fn base_type_name(&self) -> &'static str {
+        self.type_name
+            .split('<')
+            .next()
+            .unwrap_or(self.type_name)
+            .trim()
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|v| format!("{v:02x}")).collect()
+}
+
+fn seed_to_value(seed: &[u8]) -> Value {
+    match std::str::from_utf8(seed) {
+        Ok(text) => Value::String(text.to_string()),
+        Err(_) => Value::String(to_hex(seed)),
+    }
+}
+
+/// Splits `Array` (or `Map`) segment data into its version and records.
+/// Spare segment capacity following the records is ignored. If the record
+/// size is not known, it is derived from the segment length and the record count.
+pub(crate) fn array_records(data: &[u8], record_size: Option<usize>) -> Option<(u32, Vec<&[u8]>)> {
+    if data.len() < mem::size_of::<ArrayMeta>() {
+        return None;
+    }
+
+    let meta = ArrayMeta::from_buffer(data, 0);
+    let records = meta.get_records() as usize;
+    let payload = &data[mem::size_of::<ArrayMeta>()..];
+    if records == 0 {
+        return Some((meta.get_version(), Vec::new()));
+    }
+
+    let record_size = record_size.unwrap_or(payload.len() / records);
+    if record_size == 0 || records * record_size > payload.len() {
+        return None;
+    }
+    let list = payload[..records * record_size]
+        .chunks(record_size)
+        .collect();
+    Some((meta.get_version(), list))
+}
+
+fn decode_segment_data(descriptor: Option<&SegmentDescriptor>, data: &[u8]) -> Value {
+    let base_type_name = descriptor.map(|descriptor| descriptor.base_type_name());
+    match base_type_name {
+        Some("Array") | Some("Map") | Some("CollectionIndex") => {
+            let record_size = descriptor.and_then(|descriptor| descriptor.record_size);
+            match array_records(data, record_size) {
+                Some((version, records)) => json!({
+                    "version": version,
+                    "records": records.iter().map(|record| to_hex(record)).collect::<Vec<_>>(),
+                    "recordSize": record_size
+                        .or_else(|| records.first().map(|record| record.len()))
+                        .unwrap_or(0),
+                }),
+                None => Value::String(to_hex(data)),
+            }
+        }
+        Some("Utf8String") => Value::String(String::from_utf8_lossy(data).to_string()),
+        _ => Value::String(to_hex(data)),
+    }
+}
+
+/// Decodes container account data using the layout registered
+/// in the supplied [`ContainerDeclaration`].
+pub fn try_decode_account_data(
+    declaration: &ContainerDeclaration,
+    account_data: &AccountData,
+) -> Result<Value> {
+    let data = account_data.data();
+    let header_size = mem::size_of::<ContainerHeader>();
+    let meta_size = declaration.meta.as_ref().map(|meta| meta.size).unwrap_or(0);
+    if account_data.data_len() < header_size + meta_size {
+        return Err(error_code!(ErrorCode::AccountSizeTooSmall).with_account(&account_data.key));
+    }
+
+    let mut container = Map::new();
+    container.insert("pubkey".into(), account_data.key.to_string().into());
+    container.insert("owner".into(), account_data.owner.to_string().into());
+    container.insert("lamports".into(), account_data.lamports.into());
+    container.insert("dataLen".into(), account_data.data_len().into());
+    container.insert(
+        "containerType".into(),
+        format!("0x{:08x}", declaration.container_type_id).into(),
+    );
+    container.insert("name".into(), declaration.name.into());
+    container.insert("version".into(), declaration.version.into());
+
+    if let Some(meta) = &declaration.meta {
+        container.insert(
+            "meta".into(),
+            json!({
+                "type": meta.type_name,
+                "offset": header_size,
+                "size": meta.size,
+                "data": to_hex(&data[header_size..header_size + meta.size]),
+            }),
+        );
+    }
+
+    let mut account_data = account_data.clone();
+    let account_info = account_data.into_account_info();
+    let store = SegmentStore::try_load(&account_info, header_size + meta_size)?;
+    container.insert("storeVersion".into(), store.get_container_version().into());
+
+    let mut segments = Vec::new();
+    for idx in 1..store.len() {
+        let info = store.get_index_info_at(idx);
+        let segment_data = store.try_get_segment_ref_u8(idx)?;
+        let descriptor = declaration.segments.get(idx - 1);
+
+        let mut segment = Map::new();
+        segment.insert("index".into(), (idx - 1).into());
+        if let Some(descriptor) = descriptor {
+            segment.insert("name".into(), descriptor.name.into());
+            segment.insert("type".into(), descriptor.type_name.into());
+            if descriptor.fixed {
+                segment.insert("fixed".into(), true.into());
+            }
+            if descriptor.flex {
+                segment.insert("flex".into(), true.into());
+            }
+            if let Some(reserve) = descriptor.reserve {
+                segment.insert("reserve".into(), reserve.into());
+            }
+            if let Some(seed) = descriptor.collection_seed {
+                segment.insert(
+                    "collection".into(),
+                    json!({
+                        "seed": seed_to_value(seed),
+                        "container": descriptor.collection_container,
+                    }),
+                );
+            }
+        }
+        segment.insert("offset".into(), info.offset.into());
+        segment.insert("size".into(), info.size.into());
+        segment.insert("data".into(), decode_segment_data(descriptor, segment_data));
+        segments.push(Value::Object(segment));
+    }
+    container.insert("segments".into(), Value::Array(segments));
+
+    Ok(Value::Object(container))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::segment::Layout;
+    use crate::pubkey::generate_random_pubkey;
+
+    static SEGMENTS: &[SegmentDescriptor] = &[
+        SegmentDescriptor::new("name", "Utf8String<'info, 'refs>"),
+        SegmentDescriptor::new("records", "Array<'info, 'refs, u16>")
+            .with_collection(b"records", None)
+            .with_record_size(2),
+    ];
+
+    #[test]
+    fn container_layout_decode() -> Result<()> {
+        let declaration =
+            ContainerDeclaration::new(0xe0000fff, "TestContainer").with_layout(None, SEGMENTS);
+
+        // the array segment holds spare capacity for two more records
+        let layout = Layout::<u16>::from(&[4, mem::size_of::<ArrayMeta>() + 8]);
+        let data_len = mem::size_of::<ContainerHeader>() + layout.data_len();
+        let mut account_data = AccountData::new_allocated_for_program(
+            generate_random_pubkey(),
+            generate_random_pubkey(),
+            data_len,
+        );
+        {
+            let account_info = account_data.into_account_info();
+            let store = SegmentStore::try_create(
+                &account_info,
+                mem::size_of::<ContainerHeader>(),
+                &layout,
+            )?;
+            store
+                .try_get_segment_ref_mut_u8(1)?
+                .copy_from_slice(b"test");
+            let array = store.try_get_segment_ref_mut_u8(2)?;
+            ArrayMeta::from_buffer_mut(array, 0).set_records(2);
+            array[mem::size_of::<ArrayMeta>()..][..4].copy_from_slice(&[1, 0, 2, 0]);
+        }
+        account_data.data_mut()[0..4].copy_from_slice(&0xe0000fffu32.to_le_bytes());
+
+        let value = try_decode_account_data(&declaration, &account_data)?;
+        assert_eq!(value["name"], "TestContainer");
+        assert_eq!(value["segments"][0]["name"], "name");
+        assert_eq!(value["segments"][0]["data"], "test");
+        assert_eq!(value["segments"][1]["collection"]["seed"], "records");
+        assert_eq!(value["segments"][1]["data"]["records"][1], "0200");
+        assert_eq!(value["segments"][1]["data"]["recordSize"], 2);
+        assert_eq!(
+            value["segments"][1]["data"]["records"]
+                .as_array()
+                .map(|records| records.len()),
+            Some(2)
+        );
+        Ok(())
+    }
+}
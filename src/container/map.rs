@@ -0,0 +1,217 @@
+//!
+//! Segment-based Memory-mapped sorted key-value Map
+//!
+//! Entries are stored as a sorted [`Array`] of packed [`MapEntry`] records,
+//! providing access to fixed-size keys and values with `O(log n)` lookups.
+//! As records are unaligned, keys and values are returned by value.
+//! Inserts and removals shift the records in place and resize the underlying
+//! segment as needed.
+//!
+
+use crate::container::array::{Array, ArrayMeta};
+use crate::container::segment::Segment;
+use crate::result::Result;
+use std::marker::PhantomData;
+use std::{mem, rc::Rc};
+
+#[repr(packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct MapEntry<K, V>
+where
+    K: Copy,
+    V: Copy,
+{
+    pub key: K,
+    pub value: V,
+}
+
+#[derive(Debug)]
+pub struct Map<'info, 'refs, K, V>
+where
+    K: Copy + Ord + 'info,
+    V: Copy + 'info,
+{
+    pub entries: Array<'info, 'refs, MapEntry<K, V>>,
+    phantom: PhantomData<&'refs (K, V)>,
+}
+
+impl<'info, 'refs, K, V> Map<'info, 'refs, K, V>
+where
+    K: Copy + Ord + 'info,
+    V: Copy + 'info,
+{
+    pub fn try_create_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<Map<'info, 'refs, K, V>> {
+        Ok(Map {
+            entries: Array::try_create_from_segment(segment)?,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn try_load_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<Map<'info, 'refs, K, V>> {
+        Ok(Map {
+            entries: Array::try_load_from_segment(segment)?,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn data_len_min() -> usize {
+        mem::size_of::<ArrayMeta>()
+    }
+
+    pub fn calculate_data_len(records: usize) -> usize {
+        Array::<MapEntry<K, V>>::calculate_data_len(records)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &'info [MapEntry<K, V>] {
+        self.entries.as_slice()
+    }
+
+    /// Returns the position of the key or the position where it should be inserted
+    pub fn search(&self, key: &K) -> std::result::Result<usize, usize> {
+        self.as_slice().binary_search_by(|entry| {
+            let entry_key = entry.key;
+            entry_key.cmp(key)
+        })
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        match self.search(key) {
+            Ok(idx) => Some(self.entries.get_at(idx).value),
+            Err(_) => None,
+        }
+    }
+
+    /// Replaces the value of an existing key, returning the previous value.
+    /// Unlike [`Map::try_insert()`], does not resize the segment.
+    pub fn set(&self, key: &K, value: &V) -> Option<V> {
+        match self.search(key) {
+            Ok(idx) => {
+                let entry = self.entries.get_at_mut(idx);
+                let previous = entry.value;
+                entry.value = *value;
+                Some(previous)
+            }
+            Err(_) => None,
+        }
+    }
+
+    ///
+    /// Inserts or replaces the value of the key, returning the previous value.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_insert(&self, key: &K, value: &V) -> Result<Option<V>> {
+        match self.search(key) {
+            Ok(idx) => {
+                let entry = self.entries.try_get_mut_at(idx)?;
+                let previous = entry.value;
+                entry.value = *value;
+                Ok(Some(previous))
+            }
+            Err(idx) => {
+                self.entries.try_insert_at(
+                    idx,
+                    &MapEntry {
+                        key: *key,
+                        value: *value,
+                    },
+                )?;
+                Ok(None)
+            }
+        }
+    }
+
+    ///
+    /// Removes the key, returning its value.
+    ///
+    /// # Safety
+    ///
+    /// May cause segment data displacement. Must not retain or must re-acquire
+    /// any segment memory referencing data structures (references, slices or pointers).
+    ///
+    pub unsafe fn try_remove(&self, key: &K, realloc: bool) -> Result<Option<V>> {
+        match self.search(key) {
+            Ok(idx) => {
+                let value = self.entries.try_get_at(idx)?.value;
+                self.entries.try_remove_at(idx, realloc)?;
+                Ok(Some(value))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, V)> + 'info {
+        self.as_slice().iter().map(|entry| (entry.key, entry.value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = K> + 'info {
+        self.as_slice().iter().map(|entry| entry.key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = V> + 'info {
+        self.as_slice().iter().map(|entry| entry.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+    use crate::container::segment::{Layout, SegmentStore};
+
+    #[test]
+    fn container_map_sorted_ops() -> Result<()> {
+        let layout = Layout::<u16>::from(&[Map::<u64, u32>::data_len_min()]);
+        let mut instance = MockAccountDataInstance::new(layout.data_len());
+        let account = instance.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+
+        let map = Map::<u64, u32>::try_create_from_segment(store.try_get_segment_at(1)?)?;
+        for key in [5u64, 1, 9, 3, 7] {
+            assert_eq!(unsafe { map.try_insert(&key, &(key as u32 * 10))? }, None);
+        }
+        assert_eq!(unsafe { map.try_insert(&3, &33)? }, Some(30));
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+        assert_eq!(map.get(&3), Some(33));
+        assert_eq!(map.get(&4), None);
+
+        assert_eq!(map.set(&9, &99), Some(90));
+        assert_eq!(map.set(&4, &40), None);
+        assert_eq!(unsafe { map.try_remove(&5, true)? }, Some(50));
+        assert_eq!(unsafe { map.try_remove(&5, true)? }, None);
+
+        let map = Map::<u64, u32>::try_load_from_segment(store.try_get_segment_at(1)?)?;
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![(1, 10), (3, 33), (7, 70), (9, 99)]
+        );
+        assert_eq!(
+            store.try_get_segment_data_len(1)?,
+            Map::<u64, u32>::calculate_data_len(4)
+        );
+        // entries are stored without padding
+        assert_eq!(mem::size_of::<MapEntry<u64, u32>>(), 12);
+        Ok(())
+    }
+}
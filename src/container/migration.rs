@@ -0,0 +1,307 @@
+//!
+//! Container schema versioning and in-place migration.
+//!
+//! Each container carries a schema version in its segment store header
+//! (declared using `#[container(<type>, <index size>, version = N)]`; accounts
+//! created before versioning was introduced report version `0`). [`Migrations`]
+//! declare how the segment layout of one version is transformed into the next;
+//! the `try_load_with_migrations()` function generated for each container applies
+//! them when an outdated account is loaded, reallocating account data as needed.
+//! Programs should sync the account rent after a migration.
+//!
+//! Segment indices used in migration steps refer to container fields
+//! (excluding `meta` and the segment store), starting from `0`.
+//!
+
+use crate::container::segment::SegmentStore;
+use crate::error::*;
+use crate::result::Result;
+use workflow_log::log_trace;
+
+/// Converts segment data from the previous to the new representation
+pub type SegmentConverter = fn(&[u8]) -> Result<Vec<u8>>;
+
+#[derive(Clone)]
+pub enum MigrationStep {
+    /// Inserts a zero-initialized segment
+    Insert { idx: usize, data_len: usize },
+    /// Removes a segment and its data
+    Remove { idx: usize },
+    /// Resizes a segment; additional space is zero-initialized
+    Resize { idx: usize, data_len: usize },
+    /// Replaces segment data with the result of the converter
+    Retype {
+        idx: usize,
+        convert: SegmentConverter,
+    },
+}
+
+impl MigrationStep {
+    fn apply(&self, store: &SegmentStore) -> Result<()> {
+        match self {
+            MigrationStep::Insert { idx, data_len } => store.try_insert_segment(idx + 1, *data_len),
+            MigrationStep::Remove { idx } => {
+                let mut store = *store;
+                store.try_purge_segment(idx + 1)
+            }
+            MigrationStep::Resize { idx, data_len } => {
+                let data_len_before = store.try_get_segment_data_len(idx + 1)?;
+                store.try_resize_segment(idx + 1, *data_len, false)?;
+                if *data_len > data_len_before {
+                    store.try_get_segment_ref_mut_u8(idx + 1)?[data_len_before..].fill(0);
+                }
+                Ok(())
+            }
+            MigrationStep::Retype { idx, convert } => {
+                let data = convert(store.try_get_segment_ref_u8(idx + 1)?)?;
+                store.try_resize_segment(idx + 1, data.len(), false)?;
+                store
+                    .try_get_segment_ref_mut_u8(idx + 1)?
+                    .copy_from_slice(&data);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Migration of the container layout between two schema versions
+#[derive(Clone)]
+pub struct Migration {
+    pub from: u16,
+    pub to: u16,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl Migration {
+    pub fn new(from: u16, to: u16, steps: Vec<MigrationStep>) -> Migration {
+        assert!(
+            to > from,
+            "container migration must increase the version ({from} -> {to})"
+        );
+        Migration { from, to, steps }
+    }
+}
+
+/// Set of migrations available for a container
+#[derive(Clone, Default)]
+pub struct Migrations {
+    list: Vec<Migration>,
+}
+
+impl Migrations {
+    pub fn new() -> Migrations {
+        Migrations::default()
+    }
+
+    pub fn with(mut self, from: u16, to: u16, steps: Vec<MigrationStep>) -> Self {
+        self.list.push(Migration::new(from, to, steps));
+        self
+    }
+
+    /// Migrates the store to the `target` version. Returns `true`
+    /// if the store has been modified.
+    pub fn try_migrate(&self, store: &SegmentStore, target: u16) -> Result<bool> {
+        let mut version = store.get_container_version();
+        if version == target {
+            return Ok(false);
+        }
+
+        while version != target {
+            let migration = self
+                .list
+                .iter()
+                .find(|migration| migration.from == version && migration.to <= target)
+                .ok_or_else(|| {
+                    error_code!(ErrorCode::ContainerMigrationMissing)
+                        .with_account(store.account.key)
+                        .with_message(&format!(
+                            "no migration from container version {version} to {target}"
+                        ))
+                })?;
+
+            log_trace!(
+                "[{}] migrating container version {} -> {}",
+                store.account.key,
+                migration.from,
+                migration.to
+            );
+            for step in migration.steps.iter() {
+                step.apply(store)?;
+            }
+            store.set_container_version(migration.to);
+            version = migration.to;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+    use crate::container::segment::{Layout, SEGMENT_STORE_VERSION};
+    use kaizen::container::Containers;
+    use kaizen::emulator::interface::EmulatorInterface;
+    use kaizen::emulator::Simulator;
+    use kaizen::prelude::*;
+    use kaizen::rent::RentCollector;
+
+    /// Layout of the test container prior to versioning
+    #[container(Containers::MigrationTestInterface)]
+    pub struct MigrationTestV0<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        pub records: Array<'info, 'refs, u64>,
+    }
+
+    #[container(Containers::MigrationTestInterface, version = 1)]
+    pub struct MigrationTestV1<'info, 'refs> {
+        pub store: SegmentStore<'info, 'refs>,
+        pub records: Array<'info, 'refs, u64>,
+        pub counters: Array<'info, 'refs, u32>,
+    }
+
+    fn upgrades() -> Migrations {
+        Migrations::new().with(
+            0,
+            1,
+            vec![MigrationStep::Insert {
+                idx: 1,
+                data_len: std::mem::size_of::<ArrayMeta>(),
+            }],
+        )
+    }
+
+    fn widen(data: &[u8]) -> Result<Vec<u8>> {
+        Ok(data
+            .iter()
+            .flat_map(|v| (*v as u16).to_le_bytes())
+            .collect())
+    }
+
+    #[test]
+    fn container_migration_steps() -> Result<()> {
+        let layout = Layout::<u16>::from(&[4, 8]);
+        let mut instance = MockAccountDataInstance::new(layout.data_len());
+        let account = instance.into_account_info();
+
+        // version 0 layout
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+        store
+            .try_get_segment_ref_mut_u8(1)?
+            .copy_from_slice(&[1, 2, 3, 4]);
+        store.try_get_segment_ref_mut_u8(2)?.fill(9);
+        assert_eq!(store.get_container_version(), 0);
+
+        let migrations = Migrations::new()
+            .with(
+                0,
+                1,
+                vec![
+                    MigrationStep::Retype {
+                        idx: 0,
+                        convert: widen,
+                    },
+                    MigrationStep::Insert {
+                        idx: 1,
+                        data_len: 3,
+                    },
+                ],
+            )
+            .with(
+                1,
+                2,
+                vec![
+                    MigrationStep::Remove { idx: 2 },
+                    MigrationStep::Resize {
+                        idx: 1,
+                        data_len: 5,
+                    },
+                ],
+            );
+
+        assert!(migrations.try_migrate(&store, 2)?);
+        assert!(!migrations.try_migrate(&store, 2)?);
+
+        let store = SegmentStore::try_load(&account, 0)?;
+        assert_eq!(store.get_container_version(), 2);
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.try_get_segment_ref_u8(1)?, &[1, 0, 2, 0, 3, 0, 4, 0]);
+        assert_eq!(store.try_get_segment_ref_u8(2)?, &[0; 5]);
+
+        // there is no path to version 3
+        assert!(migrations.try_migrate(&store, 3).is_err());
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn container_migration_emulator() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_sequence(0u64);
+
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_generic_account_templates(1)
+            .seal()?;
+        let pubkey = builder.generic_template_accounts()[0].pubkey;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let allocation_args = AccountAllocationArgs::new(AddressDomain::Default);
+                let account =
+                    ctx.try_create_pda(MigrationTestV0::initial_data_len(), &allocation_args)?;
+                let container = MigrationTestV0::try_create(account)?;
+                unsafe {
+                    container.records.try_insert(&1)?;
+                    container.records.try_insert(&2)?;
+                }
+                Ok(())
+            })
+            .await?;
+
+        {
+            let reference = simulator.lookup(&pubkey).await?.expect("missing account");
+            let mut account_data = reference.account_data.lock()?;
+            // segment store header of an account created before container
+            // versioning: store version as `u32` followed by the payload length
+            let data = account_data.data();
+            assert_eq!(
+                u32::from_le_bytes(data[8..12].try_into().unwrap()),
+                SEGMENT_STORE_VERSION
+            );
+            let account_info = account_data.into_account_info();
+            assert!(MigrationTestV1::try_load(&account_info).is_err());
+        }
+
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_handler_accounts(&[AccountMeta::new(pubkey, false)])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                let container = MigrationTestV1::try_load_with_migrations(
+                    &ctx.handler_accounts[0],
+                    &upgrades(),
+                )?;
+                unsafe {
+                    container.counters.try_insert(&7)?;
+                }
+                ctx.sync_rent(container.account(), &RentCollector::default())?;
+                Ok(())
+            })
+            .await?;
+
+        let reference = simulator.lookup(&pubkey).await?.expect("missing account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        assert!(MigrationTestV0::try_load(&account_info).is_err());
+        let container = MigrationTestV1::try_load(&account_info)?;
+        assert_eq!(container.store.get_container_version(), 1);
+        assert_eq!(container.records.as_slice(), &[1, 2]);
+        assert_eq!(container.counters.as_slice(), &[7]);
+        Ok(())
+    }
+}
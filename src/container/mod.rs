@@ -6,6 +6,10 @@ pub mod segment;
 pub use segment::*;
 pub mod array;
 pub use array::*;
+pub mod map;
+pub use map::*;
+pub mod index;
+pub use index::*;
 pub mod collection;
 pub use collection::*;
 pub mod structure;
@@ -16,11 +20,17 @@ pub mod serialized;
 pub use serialized::*;
 pub mod string;
 pub use string::*;
+pub mod migration;
+pub use migration::*;
 
 cfg_if::cfg_if! {
     if #[cfg(not(target_os = "solana"))] {
         pub mod interfaces;
         pub use interfaces::*;
+        pub mod layout;
+        pub use layout::*;
+        pub mod diff;
+        pub use diff::*;
     }
 }
 
@@ -33,6 +43,10 @@ pub trait Container<'info, 'refs> {
     type T;
 
     fn container_type() -> u32;
+    /// Schema version of the container layout
+    fn container_version() -> u16 {
+        0
+    }
     fn initial_data_len() -> usize;
     fn try_allocate(
         ctx: &kaizen::context::ContextReference<'info, 'refs, '_, '_>,
@@ -47,6 +61,12 @@ pub trait Container<'info, 'refs> {
     fn try_load(
         account: &'refs solana_program::account_info::AccountInfo<'info>,
     ) -> kaizen::result::Result<Self::T>;
+    /// Verifies the container layout (see [`SegmentStore::verify`])
+    fn try_verify(
+        _account: &'refs solana_program::account_info::AccountInfo<'info>,
+    ) -> kaizen::result::Result<()> {
+        Ok(())
+    }
     fn account(&self) -> &'refs solana_program::account_info::AccountInfo<'info>;
     fn pubkey(&self) -> &solana_program::pubkey::Pubkey;
 }
@@ -84,12 +104,18 @@ pub enum Containers {
     TransferTestInterface,
     PDATestInterface,
     CollectionTestInterface,
+    RingCollectionTestInterface,
+    RingCollectionTestItemInterface,
+    MigrationTestInterface,
+    IndexedCollectionTestInterface,
 
     FrameworkContainers = Ranges::Framework as u32,
     Proxy,
     IdentityProxy,
     Identity,
     OrderedCollection,
+    ForeignProxy,
+    IdentityProposal,
     // PGPPubkey,
     IndexContainers = Ranges::Indexes as u32,
     // BPTreeIndex,
@@ -167,7 +193,7 @@ cfg_if! {
 
         pub mod registry {
             use super::*;
-            use workflow_log::log_trace;
+            use workflow_log::{log_trace, log_warning};
             use std::{sync::{RwLock, Arc}};
             use ahash::AHashMap;
             use derivative::Derivative;
@@ -180,25 +206,53 @@ cfg_if! {
             pub struct ContainerDeclaration {
                 pub container_type_id : u32,
                 pub name : &'static str,
-                // #[derivative(Debug="ignore")]
-                // pub debug_fn : Arc<&'static ContainerDebugFn>,
+                pub version : u16,
+                /// Program owning accounts of this container (`None` for containers
+                /// that are not scoped to a program, such as framework containers)
+                pub program_id : Option<Pubkey>,
+                pub meta : Option<MetaDescriptor>,
+                pub segments : &'static [SegmentDescriptor],
             }
 
             impl ContainerDeclaration {
-                pub const fn new(container_type_id: u32, name: &'static str,
-                    // debug_fn: &'static ContainerDebugFn
-                ) -> Self {
+                pub const fn new(container_type_id: u32, name: &'static str) -> Self {
                     ContainerDeclaration {
                         container_type_id,
                         name,
-                        // debug_fn : Arc::new(debug_fn),
+                        version : 0,
+                        program_id : None,
+                        meta : None,
+                        segments : &[],
                     }
                 }
+
+                pub const fn with_version(mut self, version: u16) -> Self {
+                    self.version = version;
+                    self
+                }
+
+                pub const fn with_program_id(mut self, program_id: Pubkey) -> Self {
+                    self.program_id = Some(program_id);
+                    self
+                }
+
+                pub const fn with_layout(mut self, meta: Option<MetaDescriptor>, segments: &'static [SegmentDescriptor]) -> Self {
+                    self.meta = meta;
+                    self.segments = segments;
+                    self
+                }
+
+                pub fn key(&self) -> ContainerTypeKey {
+                    (self.program_id, self.container_type_id)
+                }
             }
 
             impl std::fmt::Display for ContainerDeclaration {
                 fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                    write!(f, "0x{:08x} {}", self.container_type_id,self.name)
+                    match &self.program_id {
+                        Some(program_id) => write!(f, "0x{:08x} {} ({})", self.container_type_id, self.name, program_id),
+                        None => write!(f, "0x{:08x} {}", self.container_type_id, self.name),
+                    }
                 }
             }
 
@@ -206,8 +260,11 @@ cfg_if! {
             #[cfg(not(target_arch = "wasm32"))]
             inventory::collect!(ContainerDeclaration);
 
+            /// Registry key: the owning program (if declared) and the container type
+            pub type ContainerTypeKey = (Option<Pubkey>, u32);
+            pub type ContainerTypeMap = AHashMap<ContainerTypeKey,ContainerDeclaration>;
             // pub type ContainerTypeRegistry = BTreeMap<u32,ContainerDeclaration>;
-            pub type ContainerTypeRegistry = Arc<RwLock<AHashMap<u32,ContainerDeclaration>>>;
+            pub type ContainerTypeRegistry = Arc<RwLock<ContainerTypeMap>>;
             static mut CONTAINER_TYPE_REGISTRY : Option<ContainerTypeRegistry> = None;
 
             pub fn global() -> ContainerTypeRegistry {
@@ -222,10 +279,122 @@ cfg_if! {
                 }
             }
 
+            /// Inserts the declaration into the map, returning declarations of other
+            /// programs (or unscoped declarations) sharing the same container type.
+            /// Registering the same container type twice for the same program is fatal.
+            fn insert(map: &mut ContainerTypeMap, container_declaration: &ContainerDeclaration) -> Vec<ContainerDeclaration> {
+                if let Some(previous_declaration) = map.insert(container_declaration.key(), container_declaration.clone()) {
+                    panic!("duplicate container type registration for type {}:\n{:#?}\n~vs~\n{:#?}",
+                        container_declaration.container_type_id,
+                        container_declaration,
+                        previous_declaration
+                    );
+                }
+
+                map.values()
+                    .filter(|declaration| {
+                        declaration.container_type_id == container_declaration.container_type_id
+                            && declaration.program_id != container_declaration.program_id
+                    })
+                    .cloned()
+                    .collect()
+            }
+
+            fn report_collisions(container_declaration: &ContainerDeclaration, collisions: &[ContainerDeclaration]) {
+                for declaration in collisions.iter() {
+                    log_warning!("[container] container type collision: {} ~vs~ {}", container_declaration, declaration);
+                }
+            }
+
+            /// Resolves the declaration of a container type. When `program_id` is supplied,
+            /// declarations of that program take precedence over unscoped declarations and
+            /// declarations of other programs are never returned. Without `program_id`,
+            /// the container type must be unambiguous across the registry.
+            fn resolve(map: &ContainerTypeMap, program_id: Option<&Pubkey>, container_type_id: u32) -> std::result::Result<Option<ContainerDeclaration>, Vec<ContainerDeclaration>> {
+                if let Some(program_id) = program_id {
+                    if let Some(declaration) = map.get(&(Some(*program_id), container_type_id)) {
+                        return Ok(Some(declaration.clone()));
+                    }
+                }
+
+                if let Some(declaration) = map.get(&(None, container_type_id)) {
+                    return Ok(Some(declaration.clone()));
+                }
+
+                let candidates = map.values()
+                    .filter(|declaration| declaration.container_type_id == container_type_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                match (program_id, candidates.len()) {
+                    (None, 1) => Ok(candidates.into_iter().next()),
+                    (_, 0) => Ok(None),
+                    _ => Err(candidates),
+                }
+            }
+
+            /// Looks up the container type across all programs. Returns `None` if
+            /// the container type is declared by multiple programs; use
+            /// [`lookup_for_program`] to resolve such container types.
             pub fn lookup(container_type_id: u32) -> Result<Option<ContainerDeclaration>> {
                 // let registry = global();
                 // let registry = registry.read()?;
-                Ok(global().read()?.get(&container_type_id).cloned())
+                match resolve(&global().read()?, None, container_type_id) {
+                    Ok(declaration) => Ok(declaration),
+                    Err(candidates) => {
+                        log_warning!("[container] ambiguous container type 0x{:08x} is declared by {} programs", container_type_id, candidates.len());
+                        Ok(None)
+                    }
+                }
+            }
+
+            /// Looks up the container type declared by the given program
+            /// (or a container type that is not scoped to a program).
+            pub fn lookup_for_program(program_id: &Pubkey, container_type_id: u32) -> Result<Option<ContainerDeclaration>> {
+                Ok(resolve(&global().read()?, Some(program_id), container_type_id).unwrap_or(None))
+            }
+
+            /// Resolves the declaration of the container held by the account data,
+            /// checking the account owner before trusting the container header.
+            pub fn try_lookup_account_data(account_data: &AccountData) -> Result<ContainerDeclaration> {
+                let container_type = account_data.container_type().ok_or_else(|| {
+                    error_code!(ErrorCode::UnknownContainerType)
+                        .with_account(&account_data.key)
+                        .with_message("account data is too small to hold a container")
+                })?;
+
+                let registry = global();
+                let map = registry.read()?;
+                match resolve(&map, Some(&account_data.owner), container_type).unwrap_or(None) {
+                    Some(declaration) => Ok(declaration),
+                    None if map.values().any(|declaration| declaration.container_type_id == container_type) => {
+                        Err(error_code!(ErrorCode::IllegalOwner)
+                            .with_account(&account_data.key)
+                            .with_message(&format!("container type 0x{container_type:08x} is not declared by the account owner {}", account_data.owner)))
+                    }
+                    None => {
+                        Err(error_code!(ErrorCode::UnknownContainerType)
+                            .with_account(&account_data.key)
+                            .with_message(&format!("container type 0x{container_type:08x} is not registered")))
+                    }
+                }
+            }
+
+            /// Returns container types declared more than once (by different programs
+            /// or both scoped and unscoped) along with the conflicting declarations.
+            pub fn collisions() -> Result<Vec<(u32, Vec<ContainerDeclaration>)>> {
+                let registry = global();
+                let map = registry.read()?;
+                let mut declarations = AHashMap::<u32, Vec<ContainerDeclaration>>::new();
+                for declaration in map.values() {
+                    declarations.entry(declaration.container_type_id).or_default().push(declaration.clone());
+                }
+                let mut collisions = declarations
+                    .into_iter()
+                    .filter(|(_, declarations)| declarations.len() > 1)
+                    .collect::<Vec<_>>();
+                collisions.sort_by_key(|(container_type_id, _)| *container_type_id);
+                Ok(collisions)
             }
 
             #[cfg(not(target_arch = "wasm32"))]
@@ -244,13 +413,8 @@ cfg_if! {
                     //     container_declaration.container_type,
                     //     container_declaration.name
                     // );
-                    if let Some(previous_declaration) = map.insert(container_declaration.container_type_id, container_declaration.clone()) {
-                        panic!("duplicate container type registration for type {}:\n{:#?}\n~vs~\n{:#?}",
-                            container_declaration.container_type_id,
-                            container_declaration,
-                            previous_declaration
-                        );
-                    }
+                    let collisions = insert(&mut map, container_declaration);
+                    report_collisions(container_declaration, &collisions);
                 }
 
                 Ok(())
@@ -265,12 +429,47 @@ cfg_if! {
                 let registry = global();
                 let mut map = registry.write()?;
                 // let mut map = global().write()?;
-                if let Some(previous_declaration) = map.insert(container_declaration.container_type_id, container_declaration.clone()) {
-                    panic!("duplicate container type registration for type {}:\n{:#?}\n~vs~\n{:#?}", container_declaration.container_type_id, container_declaration,previous_declaration);
-                }
+                let collisions = insert(&mut map, &container_declaration);
+                report_collisions(&container_declaration, &collisions);
                 Ok(())
             }
 
+            /// Decodes account data of any registered container into a JSON value
+            /// using the layout descriptor registered by the `#[container]` macro.
+            pub fn try_decode(reference: &AccountDataReference) -> Result<serde_json::Value> {
+                let account_data = reference.account_data.lock()?;
+                let declaration = try_lookup_account_data(&account_data)?;
+                try_decode_account_data(&declaration, &account_data)
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+                use crate::pubkey::generate_random_pubkey;
+
+                #[test]
+                fn container_registry_program_scope() {
+                    let program_a = generate_random_pubkey();
+                    let program_b = generate_random_pubkey();
+                    let mut map = ContainerTypeMap::new();
+
+                    assert!(insert(&mut map, &ContainerDeclaration::new(0xe0000f01, "A").with_program_id(program_a)).is_empty());
+                    let collisions = insert(&mut map, &ContainerDeclaration::new(0xe0000f01, "B").with_program_id(program_b));
+                    assert_eq!(collisions.len(), 1);
+                    assert_eq!(collisions[0].name, "A");
+
+                    assert_eq!(resolve(&map, Some(&program_a), 0xe0000f01).unwrap().unwrap().name, "A");
+                    assert_eq!(resolve(&map, Some(&program_b), 0xe0000f01).unwrap().unwrap().name, "B");
+                    assert!(resolve(&map, None, 0xe0000f01).is_err());
+                    assert!(resolve(&map, Some(&generate_random_pubkey()), 0xe0000f01).is_err());
+
+                    insert(&mut map, &ContainerDeclaration::new(0xe0000f02, "Shared"));
+                    assert_eq!(resolve(&map, Some(&program_a), 0xe0000f02).unwrap().unwrap().name, "Shared");
+                    assert_eq!(resolve(&map, None, 0xe0000f02).unwrap().unwrap().name, "Shared");
+                    assert!(resolve(&map, None, 0xe0000f03).unwrap().is_none());
+                }
+            }
+
             // #[wasm_bindgen]
             pub fn list_containers() -> Result<()> {
                 let registry = global();
@@ -14,19 +14,26 @@ use crate::result::Result;
 use crate::utils;
 use num::Integer;
 use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use solana_program::hash::{Hash, Hasher};
 use workflow_core::enums::u16_try_from;
 
 // #[cfg(not(target_os = "solana"))]
 use workflow_log::*;
 
 pub const SEGMENT_STORE_MAGIC: u32 = 0x47455347;
+/// Segment store format version. Stores created before container versioning
+/// hold this value as `u32`; its upper half now carries the container version.
 pub const SEGMENT_STORE_VERSION: u32 = 1;
 
 #[repr(packed)]
 #[derive(Debug, Copy, Clone)]
 pub struct SegmentStoreMeta {
     magic: u32,
-    version: u32,
+    version: u16,
+    /// Schema version of the container owning this store (`0` for
+    /// accounts created before container versioning was introduced)
+    container_version: u16,
     payload_len: u16,
     index_unit_size: u16,
     segments: u32,
@@ -98,6 +105,21 @@ impl<'info, 'refs> Segment<'info, 'refs> {
         }
     }
 
+    /// Reserves segment capacity of up to `capacity` bytes (see
+    /// [`SegmentStore::try_reserve_segment_capacity()`]); subsequent resizes
+    /// within the capacity do not displace the trailing segments.
+    pub fn try_reserve(&self, capacity: usize) -> Result<usize> {
+        if self.resizable {
+            self.store.try_reserve_segment_capacity(self.idx, capacity)
+        } else {
+            Err(ErrorCode::SegmentNotResizable.into())
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.store.get_segment_capacity(self.idx)
+    }
+
     pub fn try_get_segment_data_len(&self) -> Result<usize> {
         self.store.try_get_segment_data_len(self.idx)
     }
@@ -235,6 +257,75 @@ impl IndexInfo {
     }
 }
 
+/// Used and reserved space of a segment
+#[derive(Debug, Clone)]
+pub struct SegmentUsage {
+    pub idx: usize,
+    pub offset: usize,
+    pub size: usize,
+    pub capacity: usize,
+}
+
+impl SegmentUsage {
+    pub fn slack(&self) -> usize {
+        self.capacity - self.size
+    }
+}
+
+/// Fragmentation report of a [`SegmentStore`]
+#[derive(Debug, Clone)]
+pub struct SegmentStoreReport {
+    pub account_data_len: usize,
+    pub segments: Vec<SegmentUsage>,
+}
+
+impl SegmentStoreReport {
+    /// Bytes used by segment data (including the index segment)
+    pub fn used(&self) -> usize {
+        self.segments.iter().map(|segment| segment.size).sum()
+    }
+
+    /// Bytes reserved but not used by segments (reclaimable by compaction)
+    pub fn slack(&self) -> usize {
+        self.segments.iter().map(|segment| segment.slack()).sum()
+    }
+
+    /// Ratio of slack to the total segment capacity (`0.0` for a compact store)
+    pub fn fragmentation(&self) -> f64 {
+        let capacity = self.used() + self.slack();
+        if capacity == 0 {
+            0.0
+        } else {
+            self.slack() as f64 / capacity as f64
+        }
+    }
+}
+
+impl std::fmt::Display for SegmentStoreReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "account data len: {} used: {} slack: {} fragmentation: {:.2}%",
+            self.account_data_len,
+            self.used(),
+            self.slack(),
+            self.fragmentation() * 100.0
+        )?;
+        for segment in self.segments.iter() {
+            writeln!(
+                f,
+                "[{:>4}] offset: {:>8} size: {:>8} capacity: {:>8} slack: {:>8}",
+                segment.idx,
+                segment.offset,
+                segment.size,
+                segment.capacity,
+                segment.slack()
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Copy)]
 pub struct IndexEntry<T: Integer + IndexUnit> {
     pub offset: T,
@@ -331,6 +422,9 @@ pub struct SegmentStore<'info, 'refs> {
     pub account: &'refs AccountInfo<'info>,
     offset: usize,
     index_unit_size: IndexUnitSize,
+    /// Account data length at the time the store was created or loaded, bounding
+    /// the account data increase permitted within a single instruction
+    initial_data_len: usize,
 }
 
 impl<'info, 'refs> SegmentStore<'info, 'refs> {
@@ -375,6 +469,7 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
             account,
             offset,
             index_unit_size,
+            initial_data_len: account.data_len(),
         };
         store.try_init_meta(&index)?;
         Ok(store)
@@ -391,7 +486,7 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
             return Err(error_code!(ErrorCode::AccountIsBlank).with_account(account.key));
         }
 
-        if data.len() < mem::size_of::<SegmentStoreMeta>() + 1 {
+        if data.len() < offset + mem::size_of::<SegmentStoreMeta>() + 1 {
             log_trace!("\n{}\n",style("* * * SegmentStore::try_load() error - account size is too small (below SegmentStoreMeta) * * *").red());
             return Err(error_code!(ErrorCode::AccountSizeTooSmall).with_account(account.key));
         }
@@ -412,13 +507,21 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
             return Err(error_code!(ErrorCode::SegmentStoreMagic).with_account(account.key));
         }
 
-        // FIXME unit size result handling
-        let index_unit_size = meta.index_unit_size.try_into().unwrap();
+        let index_unit_size = match meta.index_unit_size.try_into() {
+            Ok(index_unit_size) => index_unit_size,
+            Err(_) => {
+                let index_unit_size = meta.index_unit_size;
+                return Err(error_code!(ErrorCode::SegmentStoreCorrupted)
+                    .with_account(account.key)
+                    .with_message(&format!("invalid index unit size {index_unit_size}")));
+            }
+        };
 
         let store = SegmentStore {
             account,
             offset,
             index_unit_size,
+            initial_data_len: data.len(),
         };
 
         Ok(store)
@@ -429,10 +532,130 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
         self.get_meta().segments as usize
     }
 
+    /// Validates the segment store layout: meta against the account data length,
+    /// index bounds and monotonicity of segment offsets (segments may not overlap
+    /// or extend past the account data).
+    pub fn verify(&self) -> Result<()> {
+        match self.index_unit_size {
+            IndexUnitSize::Bits16 => self.verify_impl::<u16>(),
+            IndexUnitSize::Bits32 => self.verify_impl::<u32>(),
+        }
+    }
+
+    fn verify_impl<T>(&self) -> Result<()>
+    where
+        T: 'info + Integer + IndexUnit + Copy,
+    {
+        let corrupted = |message: &str| {
+            error_code!(ErrorCode::SegmentStoreCorrupted)
+                .with_account(self.account.key)
+                .with_message(message)
+        };
+
+        let account_data_len = self.account.data_len();
+        let meta_end = self.offset + mem::size_of::<SegmentStoreMeta>();
+        if account_data_len < meta_end {
+            return Err(corrupted(
+                "account data is too small for the segment store meta",
+            ));
+        }
+
+        let meta = self.get_meta();
+        let (magic, version, payload_len) = (meta.magic, meta.version, meta.payload_len);
+        if magic != SEGMENT_STORE_MAGIC {
+            return Err(corrupted(&format!(
+                "invalid segment store magic {magic:#x}"
+            )));
+        }
+        if version as u32 != SEGMENT_STORE_VERSION {
+            return Err(corrupted(&format!(
+                "unsupported segment store version {version}"
+            )));
+        }
+        if payload_len as usize != mem::size_of::<SegmentStoreMeta>() {
+            return Err(corrupted(&format!(
+                "invalid segment store meta size {payload_len}"
+            )));
+        }
+
+        let segments = self.len();
+        if segments == 0 {
+            return Err(corrupted("segment store has no index segment"));
+        }
+        let index_size = segments * mem::size_of::<IndexEntry<T>>();
+        if meta_end + index_size > account_data_len {
+            return Err(corrupted(&format!(
+                "index of {segments} segments exceeds account data length {account_data_len}"
+            )));
+        }
+
+        let index = self.get_index::<T>();
+        if IndexUnit::as_usize(index[0].offset) != meta_end
+            || IndexUnit::as_usize(index[0].size) < index_size
+        {
+            return Err(corrupted("invalid index segment entry"));
+        }
+
+        let mut end = index[0].next_offset();
+        for (idx, entry) in index.iter().enumerate().take(segments).skip(1) {
+            let offset = IndexUnit::as_usize(entry.offset);
+            if offset < end {
+                return Err(corrupted(&format!(
+                    "segment {idx} at offset {offset} overlaps the previous segment ending at {end}"
+                )));
+            }
+            end = entry.next_offset();
+            if end > account_data_len {
+                return Err(corrupted(&format!(
+                    "segment {idx} ends at {end} past the account data length {account_data_len}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hash of segment contents (excluding the index segment); independent
+    /// of the reserved segment capacity. The checksum is not persisted in the
+    /// account data: callers retain the expected value (for example, taken
+    /// when the account was last verified) to detect changes or corruption.
+    pub fn checksum(&self) -> Hash {
+        let mut hasher = Hasher::default();
+        for idx in 1..self.len() {
+            let data = self.get_segment_ref_u8(idx);
+            hasher.hash(&(data.len() as u32).to_le_bytes());
+            hasher.hash(data);
+        }
+        hasher.result()
+    }
+
+    /// Validates the layout and compares the hash of segment contents
+    /// with the `expected` checksum.
+    pub fn verify_with_checksum(&self, expected: &Hash) -> Result<()> {
+        self.verify()?;
+        let checksum = self.checksum();
+        if checksum != *expected {
+            return Err(error_code!(ErrorCode::ContainerChecksumMismatch)
+                .with_account(self.account.key)
+                .with_message(&format!("checksum {checksum} (expected {expected})")));
+        }
+        Ok(())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.get_meta().segments == 0
     }
 
+    #[inline(always)]
+    pub fn get_container_version(&self) -> u16 {
+        self.get_meta().container_version
+    }
+
+    #[inline(always)]
+    pub fn set_container_version(&self, container_version: u16) {
+        self.get_meta().container_version = container_version;
+    }
+
     #[inline(always)]
     pub fn get_meta(&self) -> &mut SegmentStoreMeta {
         SegmentStoreMeta::from(&self.account.data, self.offset)
@@ -476,8 +699,8 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
         }
 
         let dest = self.get_segment_offset(idx);
-        let index = self.get_index::<T>();
-        let segment_data_len = IndexUnit::as_usize(index[idx].size);
+        // reserved capacity of the segment is released along with its data
+        let segment_data_len = self.get_segment_capacity_impl::<T>(idx);
         let src = dest + segment_data_len;
         let account_data_len = self.account.data_len();
         let migration_data_len = account_data_len - src;
@@ -494,15 +717,81 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
             false,
         )?;
 
-        // we do not reize the index segment (there is potential for too much memory movement)
-        //let mut segment_offsets = self.segment_offsets.borrow_mut();
+        // we do not reize the index segment (there is potential for too much memory movement);
+        // the spare index capacity can be reclaimed using `compact()`
+        let index = self.get_index::<T>();
         for k in idx..(segments - 1) {
             index[k].size = index[k + 1].size;
             index[k].offset = index[k + 1].offset - IndexUnit::from_usize(segment_data_len);
         }
 
         // TODO - zero memory
-        index[segments].zero();
+        index[segments - 1].zero();
+        self.get_meta().segments = (segments - 1) as u32;
+
+        Ok(())
+    }
+
+    /// Inserts a zero-initialized segment at `idx`, shifting subsequent segments.
+    pub fn try_insert_segment(&self, idx: usize, data_len: usize) -> Result<()> {
+        match self.index_unit_size {
+            IndexUnitSize::Bits16 => {
+                if data_len > 0xffff {
+                    return Err(error_code!(ErrorCode::SegmentSizeTooLargeForIndexUnitSize));
+                }
+                self.try_insert_segment_impl::<u16>(idx, data_len)
+            }
+            IndexUnitSize::Bits32 => self.try_insert_segment_impl::<u32>(idx, data_len),
+        }
+    }
+
+    pub fn try_insert_segment_impl<T>(&self, idx: usize, data_len: usize) -> Result<()>
+    where
+        T: 'info
+            + Integer
+            + IndexUnit
+            + std::fmt::Debug
+            + std::ops::SubAssign
+            + Copy
+            + std::ops::AddAssign,
+    {
+        let segments = self.len();
+        if idx == 0 || idx > segments {
+            return Err(ErrorCode::SegmentStorageBounds.into());
+        }
+
+        // the index segment may have spare capacity left by purged segments
+        let index_size = (segments + 1) * mem::size_of::<IndexEntry<T>>();
+        if self.get_segment_data_len(0) < index_size {
+            self.try_resize_segment_impl::<T>(0, index_size, false)?;
+        }
+
+        let index = self.get_index::<T>();
+        let total_segment_data_len = index[segments - 1].next_offset();
+        let offset = if idx == segments {
+            total_segment_data_len
+        } else {
+            IndexUnit::as_usize(index[idx].offset)
+        };
+
+        let new_account_data_len = total_segment_data_len + data_len;
+        if new_account_data_len > self.account.data_len() {
+            account_info_realloc(self.account, new_account_data_len, false, false)?;
+        }
+
+        {
+            let mut data = self.account.data.borrow_mut();
+            data[..].copy_within(offset..total_segment_data_len, offset + data_len);
+            data[offset..offset + data_len].fill(0);
+        }
+
+        self.get_meta().segments = (segments + 1) as u32;
+        let index = self.get_index::<T>();
+        for k in (idx..segments).rev() {
+            index[k + 1] = index[k];
+            index[k + 1].offset += IndexUnit::from_usize(data_len);
+        }
+        index[idx] = IndexEntry::new(offset, data_len);
 
         Ok(())
     }
@@ -545,121 +834,50 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
         let next_idx = idx + 1;
 
         let index = self.get_index::<T>();
-        // log_trace!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
-        // log_trace!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
-        // log_trace!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
-        // log_trace!("index before: {:?}", index);
-        // log_trace!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
-        // log_trace!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
-        // log_trace!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
-        // let total_size
-        let segment_data_len = IndexUnit::as_usize(index[idx].size); //.as_usize(); // as usize;
-                                                                     // let segment_data_len = index[idx].size.as_usize(); // as usize;
-                                                                     // log_trace!("@@@\n@@@\n@@@\n idx: {} new_len: {}  segment_data_len: {} \n@@@\n@@@", idx, new_len, segment_data_len);
-                                                                     // if new_len > segment_data_len {
+        let segment_data_len = IndexUnit::as_usize(index[idx].size);
         match new_len.cmp(&segment_data_len) {
             Ordering::Equal => {}
             Ordering::Greater => {
-                let delta = new_len - segment_data_len;
-                let account_data_len = self.account.data_len();
-
-                // !  - - - - - - - - - - - -
-                // ^ TODO:  ACCOUNT DATA LEN MUST BE THE SUM OF ALL SEGMENTS
-                // let headers = accounts::account_info_headers(self.account)?;
-                // log_trace!("{} serialized: {} slice: {}",style("HEADERS ===============>").white().on_red(),headers.0,headers.1);
-                // let new_account_data_len = account_data_len + delta;
-                // let migration_data_len = if next_idx == segments {
-                //     0
-                // } else {
-                //     account_data_len - IndexUnit::as_usize(index[idx+1].offset) // as usize
-                // };
-                // log_trace!("ALLOC B - account_data_len: {}, new_account_data_len: {}", account_data_len, new_account_data_len);
-
-                // account_info_realloc(self.account, new_account_data_len, false,false)?;
-                // ^ TODO:  ACCOUNT DATA LEN MUST BE THE SUM OF ALL SEGMENTS
-
-                let total_segment_data_len = IndexUnit::as_usize(index[segments - 1].offset)
-                    + IndexUnit::as_usize(index[segments - 1].size);
-
-                if account_data_len < total_segment_data_len {
-                    panic!("account data len is less than total segment data len");
-                }
-
-                //log_trace!("CHECK-A next_idx, {next_idx} segments:{segments}, total_segment_data_len:{total_segment_data_len}" );
-
-                let new_account_data_len = total_segment_data_len + delta;
-                let migration_data_len = if next_idx == segments {
-                    0
-                } else {
-                    total_segment_data_len - IndexUnit::as_usize(index[next_idx].offset)
-                    // as usize
-                };
-
-                //log_trace!("CHECK-B migration_data_len: {migration_data_len} total:{total_segment_data_len}");
-                // log_trace!("ALLOC B - account_data_len: {}, new_account_data_len: {}", account_data_len, new_account_data_len);
-                // log_trace!("{:#?}", self.account);
-                // let headers = accounts::account_info_headers(self.account)?;
-                // log_trace!("{} serialized: {} slice: {}",style("===============>").white().on_red(),headers.0,headers.1);
-                // log_trace!("{} migration data len: {}",style("===============>").white().on_red(),migration_data_len);
-                if new_account_data_len > account_data_len {
-                    account_info_realloc(self.account, new_account_data_len, false, false)?;
+                if new_len > self.get_segment_capacity_impl::<T>(idx) {
+                    self.try_grow_segment_capacity_impl::<T>(idx, new_len)?;
                 } else {
                     log_trace!("[segment store] capacity ok, skipping allocation");
                 }
 
                 let index = self.get_index::<T>();
-                // log_trace!("index reaquire: {:?}", index);
-                // log_trace!("index[{}] = {}",idx,new_len);
-                index[idx].size = IndexUnit::from_usize(new_len); //  as u32;
-
-                let mut data = self.account.data.borrow_mut();
-                if migration_data_len != 0 {
-                    let src = IndexUnit::as_usize(index[next_idx].offset); // as usize;
-                    let dest = src + delta;
-                    data[..].copy_within(src..(src + migration_data_len), dest);
-
-                    if zero_init {
-                        // TODO: cleanup, set to 0
-                        data[src..src + delta].fill(88);
-                    }
-                }
+                index[idx].size = IndexUnit::from_usize(new_len);
 
-                for entry in index.iter_mut().take(segments).skip(next_idx) {
-                    //next_idx..segments {
-                    entry.offset += IndexUnit::from_usize(delta);
+                if zero_init {
+                    let offset = IndexUnit::as_usize(index[idx].offset);
+                    let mut data = self.account.data.borrow_mut();
+                    data[offset + segment_data_len..offset + new_len].fill(0);
                 }
             }
             Ordering::Less => {
-                // } else if new_len < segment_data_len {
-                log_trace!("[segment store] reduce segment size... idx: {}", idx);
-                log_trace!("[segment store] segment_data_len: {}", segment_data_len);
-                log_trace!("[segment store] new_len: {}", new_len);
-                let delta = segment_data_len - new_len;
+                log_trace!(
+                    "[segment store] reduce segment size... idx: {} segment_data_len: {} new_len: {}",
+                    idx,
+                    segment_data_len,
+                    new_len
+                );
                 let account_data_len = self.account.data_len();
-                log_trace!("[segment store] delta: {}", delta);
-                log_trace!("[segment store] account_data_len: {}", account_data_len);
-                let new_account_data_len = account_data_len - delta;
-
-                if idx < segments - 1 {
-                    let src = index[idx].next_offset();
-                    log_trace!("[segment store] src:{}", src);
-
-                    let migration_data_len = account_data_len - src;
-
-                    log_trace!("[segment store] resize [reduce segment size] segment[{}] account_data_len: {} delta: {}  new_account_data_len: {}",
-                        idx, self.account.data_len(), delta, new_account_data_len);
-                    for entry in index.iter_mut().take(segments).skip(idx) {
-                        //idx..segments {
-                        entry.offset -= IndexUnit::from_usize(delta);
-                    }
-
-                    let dest = src - delta;
+                let new_end = IndexUnit::as_usize(index[idx].offset) + new_len;
 
+                let new_account_data_len = if next_idx < segments {
+                    // trailing segments are moved down, releasing the segment capacity
+                    let src = IndexUnit::as_usize(index[next_idx].offset);
+                    let delta = src - new_end;
                     {
                         let mut data = self.account.data.borrow_mut();
-                        data[..].copy_within(src..(src + migration_data_len), dest);
+                        data[..].copy_within(src..account_data_len, new_end);
                     }
-                }
+                    for entry in index.iter_mut().take(segments).skip(next_idx) {
+                        entry.offset -= IndexUnit::from_usize(delta);
+                    }
+                    account_data_len - delta
+                } else {
+                    new_end
+                };
 
                 index[idx].size = IndexUnit::from_usize(new_len);
 
@@ -670,6 +888,203 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
         Ok(())
     }
 
+    /// Returns the largest capacity the segment can grow to as a tuple of the
+    /// limit imposed by the account data increase permitted within a single
+    /// instruction and the limit imposed by the index unit (offsets of the
+    /// displaced trailing segments must remain addressable).
+    fn get_segment_capacity_limits_impl<T>(&self, idx: usize) -> (usize, usize)
+    where
+        T: 'info + Integer + IndexUnit + Copy,
+    {
+        let segments = self.len();
+        let index = self.get_index::<T>();
+        let capacity = self.get_segment_capacity_impl::<T>(idx);
+        let (data_end, offset_end) = if idx + 1 < segments {
+            (
+                index[segments - 1].next_offset(),
+                IndexUnit::as_usize(index[segments - 1].offset),
+            )
+        } else {
+            let end = IndexUnit::as_usize(index[idx].offset) + capacity;
+            (end, end)
+        };
+
+        let max_data_len = self.initial_data_len + MAX_PERMITTED_DATA_INCREASE;
+        let realloc_limit = capacity + max_data_len.saturating_sub(data_end);
+        let index_limit = match self.index_unit_size {
+            IndexUnitSize::Bits16 => capacity + 0xffff_usize.saturating_sub(offset_end),
+            IndexUnitSize::Bits32 => usize::MAX,
+        };
+        (realloc_limit, index_limit)
+    }
+
+    /// Grows the capacity of the segment to `capacity` bytes by displacing
+    /// the trailing segments (a single memory move) and reallocating the account.
+    fn try_grow_segment_capacity_impl<T>(&self, idx: usize, capacity: usize) -> Result<()>
+    where
+        T: 'info + Integer + IndexUnit + Copy + std::ops::AddAssign,
+    {
+        let (realloc_limit, index_limit) = self.get_segment_capacity_limits_impl::<T>(idx);
+        if capacity > realloc_limit {
+            return Err(error_code!(ErrorCode::MaxPermittedAccountDataIncrease)
+                .with_account(self.account.key));
+        }
+        if capacity > index_limit {
+            return Err(error_code!(ErrorCode::SegmentSizeTooLargeForIndexUnitSize)
+                .with_account(self.account.key));
+        }
+
+        let segments = self.len();
+        let index = self.get_index::<T>();
+        let offset = IndexUnit::as_usize(index[idx].offset);
+        let account_data_len = self.account.data_len();
+
+        if idx + 1 == segments {
+            if offset + capacity > account_data_len {
+                account_info_realloc(self.account, offset + capacity, false, false)?;
+            }
+            return Ok(());
+        }
+
+        let delta = capacity - self.get_segment_capacity_impl::<T>(idx);
+        let src = IndexUnit::as_usize(index[idx + 1].offset);
+        let total_segment_data_len = index[segments - 1].next_offset();
+        if account_data_len < total_segment_data_len {
+            panic!("account data len is less than total segment data len");
+        }
+
+        let new_account_data_len = total_segment_data_len + delta;
+        if new_account_data_len > account_data_len {
+            account_info_realloc(self.account, new_account_data_len, false, false)?;
+        }
+
+        {
+            let mut data = self.account.data.borrow_mut();
+            data[..].copy_within(src..total_segment_data_len, src + delta);
+        }
+
+        let index = self.get_index::<T>();
+        for entry in index.iter_mut().take(segments).skip(idx + 1) {
+            entry.offset += IndexUnit::from_usize(delta);
+        }
+
+        Ok(())
+    }
+
+    /// Reserves up to `capacity` bytes for the segment. Segment resizes within
+    /// the reserved capacity do not move trailing segments or reallocate the account.
+    /// Segments otherwise grow exactly by the requested size, so the reservation is
+    /// clamped to the range of the index unit and to the account data increase
+    /// permitted within a single instruction. Returns the resulting segment capacity.
+    pub fn try_reserve_segment_capacity(&self, idx: usize, capacity: usize) -> Result<usize> {
+        if idx >= self.len() {
+            return Err(ErrorCode::SegmentStorageBounds.into());
+        }
+
+        match self.index_unit_size {
+            IndexUnitSize::Bits16 => self.try_reserve_segment_capacity_impl::<u16>(idx, capacity),
+            IndexUnitSize::Bits32 => self.try_reserve_segment_capacity_impl::<u32>(idx, capacity),
+        }
+    }
+
+    fn try_reserve_segment_capacity_impl<T>(&self, idx: usize, capacity: usize) -> Result<usize>
+    where
+        T: 'info + Integer + IndexUnit + Copy + std::ops::AddAssign,
+    {
+        let (realloc_limit, index_limit) = self.get_segment_capacity_limits_impl::<T>(idx);
+        let capacity = capacity.min(realloc_limit).min(index_limit);
+        if capacity > self.get_segment_capacity_impl::<T>(idx) {
+            self.try_grow_segment_capacity_impl::<T>(idx, capacity)?;
+        }
+        Ok(self.get_segment_capacity_impl::<T>(idx))
+    }
+
+    /// Returns the space available to the segment (its size and the
+    /// reserved space up to the next segment or the end of the account)
+    pub fn get_segment_capacity(&self, idx: usize) -> usize {
+        match self.index_unit_size {
+            IndexUnitSize::Bits16 => self.get_segment_capacity_impl::<u16>(idx),
+            IndexUnitSize::Bits32 => self.get_segment_capacity_impl::<u32>(idx),
+        }
+    }
+
+    fn get_segment_capacity_impl<T>(&self, idx: usize) -> usize
+    where
+        T: 'info + Integer + IndexUnit + Copy,
+    {
+        let index = self.get_index::<T>();
+        let end = if idx + 1 < self.len() {
+            IndexUnit::as_usize(index[idx + 1].offset)
+        } else {
+            self.account.data_len()
+        };
+        end - IndexUnit::as_usize(index[idx].offset)
+    }
+
+    /// Reports used and reserved space of all segments. The index segment
+    /// is reported with the size required by the current number of segments.
+    pub fn report(&self) -> SegmentStoreReport {
+        let segments = (0..self.len())
+            .map(|idx| {
+                let info = self.get_index_info_at(idx);
+                let size = if idx == 0 {
+                    self.len() * self.index_unit_size as usize * 2
+                } else {
+                    info.size
+                };
+                SegmentUsage {
+                    idx,
+                    offset: info.offset,
+                    size,
+                    capacity: self.get_segment_capacity(idx),
+                }
+            })
+            .collect();
+
+        SegmentStoreReport {
+            account_data_len: self.account.data_len(),
+            segments,
+        }
+    }
+
+    /// Reclaims the reserved space of all segments (including spare index
+    /// capacity left by purged segments) and shrinks the account data.
+    /// Returns the number of reclaimed bytes.
+    pub fn compact(&self) -> Result<usize> {
+        match self.index_unit_size {
+            IndexUnitSize::Bits16 => self.compact_impl::<u16>(),
+            IndexUnitSize::Bits32 => self.compact_impl::<u32>(),
+        }
+    }
+
+    fn compact_impl<T>(&self) -> Result<usize>
+    where
+        T: 'info + Integer + IndexUnit + Copy,
+    {
+        let segments = self.len();
+        let index = self.get_index::<T>();
+        index[0].size = IndexUnit::from_usize(segments * mem::size_of::<IndexEntry<T>>());
+
+        let mut offset = index[0].next_offset();
+        for entry in index.iter_mut().take(segments).skip(1) {
+            let src = IndexUnit::as_usize(entry.offset);
+            let size = IndexUnit::as_usize(entry.size);
+            if src != offset {
+                let mut data = self.account.data.borrow_mut();
+                data[..].copy_within(src..src + size, offset);
+                entry.offset = IndexUnit::from_usize(offset);
+            }
+            offset += size;
+        }
+
+        let account_data_len = self.account.data_len();
+        if offset < account_data_len {
+            account_info_realloc(self.account, offset, false, false)?;
+        }
+
+        Ok(account_data_len - offset)
+    }
+
     #[inline(always)]
     pub fn try_get_segment_data_len(&self, idx: usize) -> Result<usize> {
         let segments = self.len();
@@ -822,7 +1237,7 @@ impl<'info, 'refs> SegmentStore<'info, 'refs> {
         }
         // log_trace!("~ ~ ~ init segment store meta magic to {} ({:#x})",SEGMENT_STORE_MAGIC,SEGMENT_STORE_MAGIC);
         meta.magic = SEGMENT_STORE_MAGIC;
-        meta.version = SEGMENT_STORE_VERSION;
+        meta.version = SEGMENT_STORE_VERSION as u16;
         meta.payload_len = mem::size_of::<SegmentStoreMeta>() as u16;
         meta.index_unit_size = self.index_unit_size as u16;
 
@@ -1035,4 +1450,157 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn array_collection_ops() -> Result<()> {
+        let layout = Layout::<u16>::from(&[Array::<u16>::calculate_data_len(0)]);
+        let mut container = MockAccountDataInstance::new(layout.data_len());
+        let account = container.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+        let list = store.try_create_linear_store::<u16>(1)?;
+
+        unsafe {
+            list.try_extend_from_slice(&[8, 3, 5])?;
+            list.sort();
+            assert_eq!(list.try_insert_sorted(&4)?, 1);
+            assert_eq!(list.try_insert_sorted(&9)?, 4);
+            list.try_extend_from_slice(&[9, 9])?;
+        }
+        assert_eq!(list.as_slice(), &[3, 4, 5, 8, 9, 9, 9]);
+
+        for v in list.iter_mut() {
+            *v *= 2;
+        }
+        assert_eq!(unsafe { list.try_dedup(true)? }, 2);
+        assert_eq!(unsafe { list.try_retain(|v| *v != 10, true)? }, 1);
+        assert_eq!(list.as_slice(), &[6, 8, 16, 18]);
+
+        unsafe { list.try_remove_at_swap_last(0, true, false)? };
+        assert_eq!(list.as_slice(), &[18, 8, 16]);
+        list.try_swap_remove(2, true)?;
+        assert_eq!(list.as_slice(), &[18, 8]);
+        assert_eq!(
+            store.try_get_segment_data_len(1)?,
+            Array::<u16>::calculate_data_len(3)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn segment_store_capacity_and_compaction() -> Result<()> {
+        let layout = Layout::<u16>::from(&[
+            Array::<u32>::calculate_data_len(0),
+            Array::<u8>::calculate_data_len(0),
+        ]);
+        let mut container = MockAccountDataInstance::new(layout.data_len());
+        let account = container.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+        let list_a = store.try_create_linear_store::<u32>(1)?;
+        let list_b = store.try_create_linear_store::<u8>(2)?;
+        unsafe { list_b.try_insert(&7)? };
+
+        unsafe { list_a.try_reserve(16)? };
+        assert_eq!(list_a.get_records_capacity(), 16);
+        let offset_b = store.get_segment_offset(2);
+        for v in 0..4u32 {
+            unsafe { list_a.try_insert(&v)? };
+        }
+        // appends within the reserved capacity do not displace trailing segments
+        assert_eq!(store.get_segment_offset(2), offset_b);
+
+        let report = store.report();
+        assert_eq!(report.slack(), 12 * mem::size_of::<u32>());
+        assert!(report.fragmentation() > 0.0);
+
+        assert_eq!(store.compact()?, 12 * mem::size_of::<u32>());
+        assert_eq!(store.report().slack(), 0);
+        assert_eq!(list_a.as_slice(), &[0, 1, 2, 3]);
+        assert_eq!(list_b.as_slice(), &[7]);
+        assert_eq!(
+            account.data_len(),
+            store.get_index_info_at(2).offset + Array::<u8>::calculate_data_len(1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn segment_store_capacity_growth() -> Result<()> {
+        let layout = Layout::<u16>::from(&[8, 4]);
+        let mut container = MockAccountDataInstance::new(layout.data_len());
+        let account = container.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+        store.try_get_segment_ref_mut_u8(2)?.fill(7);
+
+        // segments grow exactly by the requested size
+        store.try_resize_segment(1, 10, true)?;
+        assert_eq!(store.get_segment_capacity(1), 10);
+
+        // resizes within the reserved capacity do not displace trailing segments
+        assert_eq!(store.try_reserve_segment_capacity(1, 16)?, 16);
+        let offset = store.get_segment_offset(2);
+        store.try_resize_segment(1, 16, true)?;
+        assert_eq!(store.get_segment_offset(2), offset);
+        assert_eq!(store.try_get_segment_ref_u8(2)?, &[7; 4]);
+
+        store.verify()?;
+
+        // reservations are clamped to the range of 16 bit index offsets
+        let layout = Layout::<u16>::from(&[0xe000, 4]);
+        let mut container = MockAccountDataInstance::new(0x10100);
+        let account = container.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+        store.try_get_segment_ref_mut_u8(2)?.fill(7);
+        let capacity = store.try_reserve_segment_capacity(1, 0x20000)?;
+        assert_eq!(store.get_segment_offset(2), 0xffff);
+        assert_eq!(store.try_get_segment_ref_u8(2)?, &[7; 4]);
+        assert!(store.try_resize_segment(1, capacity + 1, true).is_err());
+        store.verify()?;
+        Ok(())
+    }
+
+    #[test]
+    fn segment_store_load_checks() -> Result<()> {
+        let layout = Layout::<u16>::from(&[4]);
+        let mut container = MockAccountDataInstance::new(layout.data_len());
+        let account = container.into_account_info();
+        SegmentStore::try_create(&account, 0, &layout)?;
+
+        // the store meta does not fit the account data at the given offset
+        assert!(SegmentStore::try_load(&account, layout.data_len()).is_err());
+
+        SegmentStoreMeta::from(&account.data, 0).index_unit_size = 3;
+        let err = SegmentStore::try_load(&account, 0).err().unwrap();
+        assert!(matches!(
+            err.variant,
+            Some(Variant::ErrorCode(ErrorCode::SegmentStoreCorrupted))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn segment_store_verify() -> Result<()> {
+        let layout = Layout::<u16>::from(&[4, 8]);
+        let mut container = MockAccountDataInstance::new(layout.data_len());
+        let account = container.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+        store.try_get_segment_ref_mut_u8(1)?.fill(1);
+        store.verify()?;
+
+        let checksum = store.checksum();
+        store.try_reserve_segment_capacity(1, 32)?;
+        store.verify()?;
+        store.verify_with_checksum(&checksum)?;
+
+        store.try_get_segment_ref_mut_u8(2)?[0] = 2;
+        assert!(store.verify_with_checksum(&checksum).is_err());
+
+        // segment extending past the account data
+        store.get_index::<u16>()[2].size = 0x1000;
+        assert!(store.verify().is_err());
+        store.get_index::<u16>()[2].size = 8;
+        // overlapping segments
+        store.get_index::<u16>()[2].offset -= 40;
+        assert!(store.verify().is_err());
+        Ok(())
+    }
 }
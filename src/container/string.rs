@@ -1,15 +1,20 @@
 //!
 //! Segment-based raw UTF-8 String storage
 //!
+//! String data is stored as raw bytes without a terminator. Account data is
+//! validated when accessed via [`Utf8String::try_as_str`]; the optional maximum
+//! byte length is declared using `#[segment(max_len = N)]` in a container.
+//!
 
 use crate::container::segment::Segment;
+use crate::error::*;
 use crate::result::Result;
 use std::rc::Rc;
 use std::string::*;
-// use kaizen::prelude::*;
 
 pub struct Utf8String<'info, 'refs> {
     pub segment: Rc<Segment<'info, 'refs>>,
+    max_len: Option<usize>,
 }
 
 impl<'info, 'refs> Utf8String<'info, 'refs> {
@@ -20,13 +25,66 @@ impl<'info, 'refs> Utf8String<'info, 'refs> {
     pub fn try_create_from_segment(
         segment: Rc<Segment<'info, 'refs>>,
     ) -> Result<Utf8String<'info, 'refs>> {
-        Ok(Utf8String { segment })
+        Ok(Utf8String {
+            segment,
+            max_len: None,
+        })
     }
 
     pub fn try_load_from_segment(
         segment: Rc<Segment<'info, 'refs>>,
     ) -> Result<Utf8String<'info, 'refs>> {
-        Ok(Utf8String { segment })
+        Ok(Utf8String {
+            segment,
+            max_len: None,
+        })
+    }
+
+    /// Limits the byte length of strings stored using [`Utf8String::try_set`]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// Byte length of the stored string
+    pub fn len(&self) -> usize {
+        self.segment.get_data_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the stored string, validating that account data is UTF-8
+    pub fn try_as_str(&self) -> Result<&str> {
+        std::str::from_utf8(self.segment.as_slice::<u8>()).map_err(|err| {
+            error_code!(ErrorCode::Utf8StringInvalid)
+                .with_account(self.segment.store.account.key)
+                .with_message(&err.to_string())
+        })
+    }
+
+    /// Stores the string, checking it against the maximum byte length.
+    /// This function can resize the underlying account data; any
+    /// references to account data should be reacquired after its use.
+    pub fn try_set(&self, text: &str) -> Result<()> {
+        if let Some(max_len) = self.max_len {
+            if text.len() > max_len {
+                return Err(error_code!(ErrorCode::Utf8StringTooLong)
+                    .with_account(self.segment.store.account.key)
+                    .with_message(&format!(
+                        "string length {} exceeds the maximum of {} bytes",
+                        text.len(),
+                        max_len
+                    )));
+            }
+        }
+
+        unsafe { self.store_bytes(text.as_bytes()) }
     }
 
     /// # Safety
@@ -53,8 +111,36 @@ impl<'info, 'refs> Utf8String<'info, 'refs> {
 }
 
 impl<'info, 'refs> ToString for Utf8String<'info, 'refs> {
+    /// Lossy conversion; invalid UTF-8 sequences are replaced
+    /// with `U+FFFD` (use [`Utf8String::try_as_str`] to validate).
     fn to_string(&self) -> String {
-        let bytes = self.segment.as_slice::<u8>();
-        unsafe { String::from_utf8_unchecked(bytes.to_vec()) }
+        String::from_utf8_lossy(self.segment.as_slice::<u8>()).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+    use crate::container::segment::{Layout, SegmentStore};
+
+    #[test]
+    fn utf8_string_validation() -> Result<()> {
+        let layout = Layout::<u16>::from(&[0]);
+        let mut instance = MockAccountDataInstance::new(layout.data_len());
+        let account = instance.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+
+        let text =
+            Utf8String::try_create_from_segment(store.try_get_segment_at(1)?)?.with_max_len(8);
+        text.try_set("kaizen")?;
+        assert_eq!(text.try_as_str()?, "kaizen");
+        assert!(text.try_set("too long text").is_err());
+        assert_eq!(text.len(), 6);
+
+        unsafe { text.store_bytes(&[0x6b, 0xff, 0x7a])? };
+        assert!(text.try_as_str().is_err());
+        assert_eq!(text.to_string(), "k\u{fffd}z");
+        Ok(())
     }
 }
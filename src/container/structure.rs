@@ -1,9 +1,13 @@
 //!
-//!  Segment-based Memory-mapped strongly-typed data (a single struct)
+//!  Segment-based Memory-mapped strongly-typed data (a single struct,
+//!  an optional struct or one of multiple tagged struct variants)
 //!
 use crate::container::segment::Segment;
+use crate::error::*;
 use crate::result::Result;
+use crate::utils;
 use std::marker::PhantomData;
+use std::mem;
 use std::rc::Rc;
 
 pub struct Struct<'info, 'refs, T> {
@@ -48,3 +52,251 @@ impl<'info, 'refs, T> Struct<'info, 'refs, T> {
         self.segment.try_as_struct_mut()
     }
 }
+
+/// Zero-copy optional struct; an empty segment represents `None`.
+pub struct OptionalStruct<'info, 'refs, T> {
+    pub segment: Rc<Segment<'info, 'refs>>,
+    phantom: PhantomData<T>,
+}
+
+impl<'info, 'refs, T> OptionalStruct<'info, 'refs, T> {
+    pub fn data_len_min() -> usize {
+        0
+    }
+
+    pub fn try_create_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<OptionalStruct<'info, 'refs, T>> {
+        Ok(OptionalStruct {
+            segment,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn try_load_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<OptionalStruct<'info, 'refs, T>> {
+        Ok(OptionalStruct {
+            segment,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.segment.get_data_len() != 0
+    }
+
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    pub fn try_as_ref(&self) -> Result<Option<&T>>
+    where
+        T: 'info,
+    {
+        if self.is_none() {
+            Ok(None)
+        } else {
+            Ok(Some(self.segment.try_as_struct_ref()?))
+        }
+    }
+
+    pub fn try_as_mut_ref(&self) -> Result<Option<&mut T>>
+    where
+        T: 'info,
+    {
+        if self.is_none() {
+            Ok(None)
+        } else {
+            Ok(Some(self.segment.try_as_struct_mut()?))
+        }
+    }
+
+    /// Stores the value, resizing the segment if it was empty. Any references
+    /// to account data should be reacquired after this call.
+    pub fn try_set(&self, value: &T) -> Result<&'info mut T>
+    where
+        T: Copy + 'info,
+    {
+        self.segment.try_resize(mem::size_of::<T>(), false)?;
+        let dest = self.segment.try_as_struct_mut::<T>()?;
+        *dest = *value;
+        Ok(dest)
+    }
+
+    /// Clears the value, releasing the segment data. Any references
+    /// to account data should be reacquired after this call.
+    pub fn try_clear(&self) -> Result<()> {
+        self.segment.try_resize(0, false)
+    }
+}
+
+/// Tag identifying a struct variant stored in a [`TaggedStruct`] segment
+pub trait StructTag {
+    const TAG: u32;
+}
+
+/// Zero-copy tagged union of structs; the segment holds a `u32` tag
+/// followed by the variant struct and is resized when the variant changes.
+/// An empty segment holds no variant.
+pub struct TaggedStruct<'info, 'refs> {
+    pub segment: Rc<Segment<'info, 'refs>>,
+}
+
+impl<'info, 'refs> TaggedStruct<'info, 'refs> {
+    pub fn data_len_min() -> usize {
+        0
+    }
+
+    pub fn try_create_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<TaggedStruct<'info, 'refs>> {
+        Ok(TaggedStruct { segment })
+    }
+
+    pub fn try_load_from_segment(
+        segment: Rc<Segment<'info, 'refs>>,
+    ) -> Result<TaggedStruct<'info, 'refs>> {
+        Ok(TaggedStruct { segment })
+    }
+
+    /// Tag of the stored variant (`None` if the segment is empty)
+    pub fn tag(&self) -> Option<u32> {
+        if self.segment.get_data_len() < mem::size_of::<u32>() {
+            None
+        } else {
+            let tag: &u32 = utils::account_buffer_as_struct_ref(
+                self.segment.store.account,
+                self.segment.get_offset(),
+            );
+            Some(*tag)
+        }
+    }
+
+    pub fn is<T>(&self) -> bool
+    where
+        T: StructTag,
+    {
+        self.tag() == Some(T::TAG)
+    }
+
+    fn try_variant_offset<T>(&self) -> Result<Option<usize>>
+    where
+        T: StructTag,
+    {
+        if !self.is::<T>() {
+            return Ok(None);
+        }
+        if self.segment.get_data_len() != mem::size_of::<u32>() + mem::size_of::<T>() {
+            return Err(ErrorCode::SegmentStorageSize.into());
+        }
+        Ok(Some(self.segment.get_offset() + mem::size_of::<u32>()))
+    }
+
+    /// Returns the stored struct if the variant matches `T`
+    pub fn try_as_ref<T>(&self) -> Result<Option<&'info T>>
+    where
+        T: StructTag + 'info,
+    {
+        Ok(self
+            .try_variant_offset::<T>()?
+            .map(|offset| utils::account_buffer_as_struct_ref(self.segment.store.account, offset)))
+    }
+
+    /// Returns the stored struct if the variant matches `T`
+    pub fn try_as_mut_ref<T>(&self) -> Result<Option<&'info mut T>>
+    where
+        T: StructTag + 'info,
+    {
+        Ok(self
+            .try_variant_offset::<T>()?
+            .map(|offset| utils::account_buffer_as_struct_mut(self.segment.store.account, offset)))
+    }
+
+    /// Stores the variant, resizing the segment to fit it. Any references
+    /// to account data should be reacquired after this call.
+    pub fn try_set<T>(&self, value: &T) -> Result<&'info mut T>
+    where
+        T: StructTag + Copy + 'info,
+    {
+        self.segment
+            .try_resize(mem::size_of::<u32>() + mem::size_of::<T>(), false)?;
+        let account = self.segment.store.account;
+        let offset = self.segment.get_offset();
+        *utils::account_buffer_as_struct_mut::<u32>(account, offset) = T::TAG;
+        let dest =
+            utils::account_buffer_as_struct_mut::<T>(account, offset + mem::size_of::<u32>());
+        *dest = *value;
+        Ok(dest)
+    }
+
+    /// Clears the stored variant, releasing the segment data. Any references
+    /// to account data should be reacquired after this call.
+    pub fn try_clear(&self) -> Result<()> {
+        self.segment.try_resize(0, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::MockAccountDataInstance;
+    use crate::container::segment::{Layout, SegmentStore};
+
+    #[repr(packed)]
+    #[derive(Clone, Copy)]
+    struct Pending {
+        slot: u64,
+    }
+
+    impl StructTag for Pending {
+        const TAG: u32 = 1;
+    }
+
+    #[repr(packed)]
+    #[derive(Clone, Copy)]
+    struct Settled {
+        slot: u64,
+        amount: u64,
+    }
+
+    impl StructTag for Settled {
+        const TAG: u32 = 2;
+    }
+
+    #[test]
+    fn optional_and_tagged_struct_segments() -> Result<()> {
+        let layout = Layout::<u16>::from(&[0, 0]);
+        let mut instance = MockAccountDataInstance::new(layout.data_len());
+        let account = instance.into_account_info();
+        let store = SegmentStore::try_create(&account, 0, &layout)?;
+
+        let optional =
+            OptionalStruct::<u64>::try_create_from_segment(store.try_get_segment_at(1)?)?;
+        assert!(optional.try_as_ref()?.is_none());
+        optional.try_set(&42)?;
+        assert_eq!(optional.try_as_ref()?, Some(&42));
+        optional.try_clear()?;
+        assert!(optional.is_none());
+
+        let tagged = TaggedStruct::try_create_from_segment(store.try_get_segment_at(2)?)?;
+        assert_eq!(tagged.tag(), None);
+        tagged.try_set(&Pending { slot: 10 })?;
+        assert!(tagged.is::<Pending>());
+        assert_eq!({ tagged.try_as_ref::<Pending>()?.unwrap().slot }, 10);
+        assert!(tagged.try_as_ref::<Settled>()?.is_none());
+
+        tagged.try_set(&Settled {
+            slot: 12,
+            amount: 100,
+        })?;
+        assert_eq!(tagged.tag(), Some(Settled::TAG));
+        let settled = tagged.try_as_ref::<Settled>()?.unwrap();
+        assert_eq!(({ settled.slot }, { settled.amount }), (12, 100));
+        assert_eq!(
+            store.try_get_segment_data_len(2)?,
+            4 + mem::size_of::<Settled>()
+        );
+        Ok(())
+    }
+}
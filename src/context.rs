@@ -18,6 +18,7 @@ use kaizen::container;
 use kaizen::error::*;
 use kaizen::identity::program::Identity;
 use kaizen::payload::Payload;
+use kaizen::realloc::account_info_realloc;
 use kaizen::rent::RentCollector;
 use kaizen::result::*;
 use workflow_log::*;
@@ -459,6 +460,19 @@ impl<'info, 'refs, 'pid, 'instr> Context<'info, 'refs, 'pid, 'instr> {
         Ok(())
     }
 
+    /// Verifies the container layout of the account before loading it,
+    /// reporting corrupted account data as [`ErrorCode::SegmentStoreCorrupted`].
+    pub fn try_load_container_verified<T>(
+        &self,
+        account: &'refs AccountInfo<'info>,
+    ) -> Result<<T as container::Container<'info, 'refs>>::T>
+    where
+        T: container::Container<'info, 'refs>,
+    {
+        T::try_verify(account)?;
+        T::try_load(account)
+    }
+
     pub fn try_consume_collection_template_address_data(
         &self,
     ) -> Result<(ProgramAddressData<'instr>, &'refs AccountInfo<'info>)> {
@@ -481,6 +495,41 @@ impl<'info, 'refs, 'pid, 'instr> Context<'info, 'refs, 'pid, 'instr> {
         Ok((program_address_data_ref, account_info))
     }
 
+    /// Consumes a group of collection template candidates supplied by
+    /// [`InstructionBuilder::with_collection_template_candidates()`](crate::builder::InstructionBuilder::with_collection_template_candidates).
+    /// A template supplied without candidates is returned as a single-entry group.
+    pub fn try_consume_collection_template_candidates(
+        &self,
+    ) -> Result<Vec<(u8, &'refs AccountInfo<'info>)>> {
+        let (program_address_data, account_info) =
+            self.try_consume_collection_template_address_data()?;
+        let (bump, candidates) = match program_address_data.seed {
+            [bump] => (*bump, 1),
+            [bump, candidates] if *candidates > 0 => (*bump, *candidates as usize),
+            _ => return Err(ErrorCode::PDAAccountArgumentData.into()),
+        };
+
+        let mut list = vec![(bump, account_info)];
+        for _ in 1..candidates {
+            if self.meta.borrow().collection_template_accounts_consumed
+                >= self.collection_template_accounts.len()
+            {
+                return Err(ErrorCode::PDAAccountArgumentData.into());
+            }
+
+            let (program_address_data, account_info) =
+                self.try_consume_collection_template_address_data()?;
+            match program_address_data.seed {
+                [bump, group] if *group as usize == candidates => {
+                    list.push((*bump, account_info));
+                }
+                _ => return Err(ErrorCode::PDAAccountArgumentData.into()),
+            }
+        }
+
+        Ok(list)
+    }
+
     pub fn try_consume_generic_template_address_data(
         &self,
     ) -> Result<(ProgramAddressData<'instr>, &'refs AccountInfo<'info>)> {
@@ -609,6 +658,67 @@ impl<'info, 'refs, 'pid, 'instr> Context<'info, 'refs, 'pid, 'instr> {
         Ok(tpl_account_info)
     }
 
+    /// Closes a program-owned account, transferring its lamports to
+    /// the `recipient` and releasing the account data.
+    pub fn try_close_account(
+        &self,
+        account_info: &'refs AccountInfo<'info>,
+        recipient: &AccountInfo<'info>,
+    ) -> Result<()> {
+        if account_info.owner != self.program_id {
+            return Err(error_code!(ErrorCode::AccountOwnership).with_account(account_info.key));
+        }
+
+        if !account_info.is_writable {
+            return Err(error_code!(ErrorCode::ReadOnlyAccess).with_account(account_info.key));
+        }
+
+        let lamports = recipient.lamports().saturating_add(account_info.lamports());
+        **recipient.lamports.borrow_mut() = lamports;
+        **account_info.lamports.borrow_mut() = 0;
+
+        account_info_realloc(account_info, 0, false, false)?;
+
+        Ok(())
+    }
+
+    /// Reinitializes a program-owned account for reuse, zeroing its data
+    /// and resizing it to `data_len`. Additional rent (if required due to
+    /// the account growth) is transferred from the authority.
+    pub fn try_recycle_account(
+        &self,
+        account_info: &'refs AccountInfo<'info>,
+        data_len: usize,
+    ) -> Result<()> {
+        if account_info.owner != self.program_id {
+            return Err(error_code!(ErrorCode::AccountOwnership).with_account(account_info.key));
+        }
+
+        if !account_info.is_writable {
+            return Err(error_code!(ErrorCode::ReadOnlyAccess).with_account(account_info.key));
+        }
+
+        account_info.data.borrow_mut().fill(0);
+        account_info_realloc(account_info, data_len, true, false)?;
+
+        let minimum_balance = self.rent.minimum_balance(data_len);
+        let lamports = account_info.lamports();
+        if lamports < minimum_balance {
+            let system_program = self
+                .locate_system_account(&solana_program::system_program::id())
+                .ok_or_else(|| error_code!(ErrorCode::SystemProgramAccountMissing))?;
+            kaizen::transfer_sol(
+                self.authority,
+                account_info,
+                self.authority,
+                system_program,
+                minimum_balance - lamports,
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub fn sync_rent(
         &self,
         account_info: &'refs AccountInfo<'info>,
@@ -7,6 +7,7 @@ use async_trait::async_trait;
 use kaizen::accounts::AccountDataReference;
 use kaizen::error::*;
 use kaizen::result::Result;
+use kaizen::store::{Notifier, StoreEvent};
 use regex::Regex;
 use solana_program::instruction::Instruction;
 use solana_program::pubkey::Pubkey;
@@ -15,6 +16,7 @@ use workflow_core::trigger::Listener;
 use workflow_log::*;
 use workflow_rpc::client::prelude::{Encoding, Interface, RpcClient, RpcClientOptions};
 use workflow_rpc::client::result::Result as RpcResult;
+use workflow_rpc::client::Notification;
 
 use super::interface::{EmulatorConfig, EmulatorInterface, ExecutionResponse};
 use super::rpc::*;
@@ -22,6 +24,7 @@ use super::rpc::*;
 #[derive(Clone)]
 pub struct EmulatorRpcClient {
     rpc: Arc<RpcClient<EmulatorOps>>,
+    notifier: Notifier,
 }
 
 impl EmulatorRpcClient {
@@ -30,7 +33,21 @@ impl EmulatorRpcClient {
         let url = re.replace(url, "ws");
         log_trace!("Emulator RPC client url: {}", url);
 
-        let interface = Interface::<EmulatorOps>::new();
+        let notifier = Notifier::new();
+        let mut interface = Interface::<EmulatorOps>::new();
+
+        // relay store change events posted by the emulator server
+        let relay = notifier.clone();
+        interface.notification(
+            EmulatorOps::StoreEvent,
+            Notification::new(move |event: StoreEvent| {
+                let relay = relay.clone();
+                Box::pin(async move {
+                    relay.notify(event);
+                    Ok(())
+                })
+            }),
+        );
 
         let options = RpcClientOptions {
             url: &url,
@@ -42,6 +59,7 @@ impl EmulatorRpcClient {
                 interface.into(),
                 options,
             )?),
+            notifier,
         };
 
         Ok(client)
@@ -144,4 +162,8 @@ impl EmulatorInterface for EmulatorRpcClient {
             .map_err(|err| err.into());
         resp
     }
+
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
 }
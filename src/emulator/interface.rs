@@ -6,6 +6,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use downcast::{downcast_sync, AnySync};
 use kaizen::accounts::{AccountDataReference, AccountDescriptorList};
 use kaizen::result::Result;
+use kaizen::store::Notifier;
 use serde::{Deserialize, Serialize};
 use solana_program::instruction;
 use solana_program::pubkey::Pubkey;
@@ -48,6 +49,9 @@ pub trait EmulatorInterface: AnySync {
     async fn list(&self) -> Result<AccountDescriptorList>;
 
     async fn configure(&self, config: EmulatorConfig) -> Result<()>;
+
+    /// Notifier relaying account store change events of the emulator.
+    fn notifier(&self) -> &Notifier;
 }
 
 downcast_sync!(dyn EmulatorInterface);
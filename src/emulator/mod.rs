@@ -442,4 +442,8 @@ impl EmulatorInterface for Emulator {
     async fn configure(&self, _config: EmulatorConfig) -> Result<()> {
         Ok(())
     }
+
+    fn notifier(&self) -> &store::Notifier {
+        self.store.notifier()
+    }
 }
@@ -99,6 +99,7 @@ u32_try_from! {
         Fund,
         List,
         Configure,
+        StoreEvent,
     }
 }
 
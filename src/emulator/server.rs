@@ -3,6 +3,7 @@
 //!
 #![allow(unused_imports)]
 use crate::accounts::AccountDataStore;
+use ahash::AHashMap;
 use async_trait::async_trait;
 use borsh::{BorshDeserialize, BorshSerialize};
 use kaizen::cache::Cache;
@@ -12,7 +13,8 @@ use kaizen::result::Result;
 use kaizen::store::FileStore;
 use solana_program::instruction::Instruction;
 use solana_program::pubkey::Pubkey;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use workflow_core::id::Id;
 use workflow_rpc::result::ServerResult;
 use workflow_rpc::server::prelude::*;
 use workflow_rpc::server::RpcHandler;
@@ -40,6 +42,7 @@ const DEFAULT_CAPACITY: u64 = 1024u64 * 1024u64 * 256u64; // 256 megabytes
 #[derive(Clone)]
 pub struct Server {
     pub emulator: Arc<Emulator>,
+    messengers: Arc<Mutex<AHashMap<Id, Arc<Messenger>>>>,
 }
 
 impl Server {
@@ -49,17 +52,47 @@ impl Server {
         let store = Arc::new(FileStore::try_new_with_cache(cache)?);
         let emulator = Arc::new(Emulator::new(store));
 
-        let server = Server { emulator };
+        let server = Server {
+            emulator,
+            messengers: Arc::new(Mutex::new(AHashMap::default())),
+        };
 
         Ok(server)
     }
 
     pub async fn init(&self) -> Result<()> {
-        self.emulator.init().await
+        self.emulator.init().await?;
+        self.start_store_event_relay();
+        Ok(())
+    }
+
+    /// Forwards emulator store change events to all connected clients.
+    fn start_store_event_relay(&self) {
+        let (_, receiver) = self.emulator.notifier().register_event_channel();
+        let messengers = self.messengers.clone();
+        workflow_core::task::spawn(async move {
+            while let Ok(event) = receiver.recv().await {
+                let list = messengers
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(id, messenger)| (*id, messenger.clone()))
+                    .collect::<Vec<_>>();
+                for (id, messenger) in list {
+                    if let Err(err) = messenger
+                        .notify(EmulatorOps::StoreEvent, event.clone())
+                        .await
+                    {
+                        log_trace!("dropping emulator client {id}: {err}");
+                        messengers.lock().unwrap().remove(&id);
+                    }
+                }
+            }
+        });
     }
 
-    pub fn interface(self: Arc<Server>) -> Interface<Arc<Server>, (), EmulatorOps> {
-        let mut interface = Interface::<Arc<Server>, (), EmulatorOps>::new(self);
+    pub fn interface(self: Arc<Server>) -> Interface<Arc<Server>, Id, EmulatorOps> {
+        let mut interface = Interface::<Arc<Server>, Id, EmulatorOps>::new(self);
 
         interface.method(
             EmulatorOps::Lookup,
@@ -130,16 +163,22 @@ impl Server {
 // impl RpcHandlerBorsh<EmulatorOps> for Server
 // impl RpcHandler<EmulatorOps> for Server {
 impl RpcHandler for Server {
-    type Context = ();
+    type Context = Id;
 
     async fn handshake(
         self: Arc<Self>,
         _peer: &SocketAddr,
         _sender: &mut WebSocketSender,
         _receiver: &mut WebSocketReceiver,
-        _messenger: Arc<Messenger>,
+        messenger: Arc<Messenger>,
     ) -> WebSocketResult<Self::Context> {
-        Ok(())
+        let id = Id::new();
+        self.messengers.lock().unwrap().insert(id, messenger);
+        Ok(id)
+    }
+
+    async fn disconnect(self: Arc<Self>, id: Self::Context, _result: WebSocketResult<()>) {
+        self.messengers.lock().unwrap().remove(&id);
     }
     /*
         async fn handle_request(self: Arc<Self>, op: EmulatorOps, data: &[u8]) -> RpcResult {
@@ -177,4 +177,8 @@ impl EmulatorInterface for Simulator {
     async fn configure(&self, config: EmulatorConfig) -> Result<()> {
         self.emulator.configure(config).await
     }
+
+    fn notifier(&self) -> &store::Notifier {
+        self.store.notifier()
+    }
 }
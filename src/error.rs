@@ -166,6 +166,28 @@ pub enum ErrorCode {
     DataType,
     TransactionAlreadyCompleted,
     Web3js,
+
+    StoreEncryptionFailure,
+    StoreDecryptionFailure,
+    StoreEncryptionKeyMismatch,
+    StoreRecordNotEncrypted,
+    StoreRecordIsEncrypted,
+    StoreRecordVersionMismatch,
+    StoreRecordIntegrity,
+    ContainerVersionMismatch,
+    ContainerMigrationMissing,
+    Utf8StringInvalid,
+    Utf8StringTooLong,
+    SegmentStoreCorrupted,
+    ContainerChecksumMismatch,
+    AccountCollectionCandidateNotFound,
+    CollectionIndexCollision,
+    CollectionIndexRecordNotFound,
+    AccountCollectionInvalidCapacity,
+    IdentityLastAuthority,
+    IdentityApprovalThreshold,
+    IdentityInvalidPolicy,
+    IdentityProposalMismatch,
 }
 
 #[derive(Debug)]
@@ -3,8 +3,10 @@
 //!
 
 use borsh::*;
+use kaizen::error::*;
 use kaizen::prelude::*;
 use kaizen::result::Result;
+use solana_program::hash::Hash;
 use solana_program::pubkey::Pubkey;
 use workflow_log::*;
 
@@ -112,3 +114,218 @@ pub async fn create_identity_for_unit_tests(
 
     Ok(identity.pubkey)
 }
+
+/// Binds `new_authority` to the identity of the `authority`. The resulting
+/// transaction must be signed by both wallets. `approvals` (co-signing
+/// authorities or approved proposals) are required if the identity has
+/// an approval threshold exceeding the weight of the `authority`.
+pub async fn bind_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    new_authority: &Pubkey,
+    interface_id: usize,
+    handler_id: usize,
+    approvals: &[AccountMeta],
+) -> Result<TransactionList> {
+    let (proxy, bump) = find_identity_proxy_pda(program_id, new_authority)?;
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_system_program_account()
+        .with_handler_accounts(&[
+            AccountMeta::new_readonly(*new_authority, true),
+            AccountMeta::new(proxy, false),
+        ])
+        .with_handler_accounts(approvals)
+        .with_instruction_data(&[bump])
+        .seal()?;
+
+    let transaction = Transaction::new_with_accounts(
+        format!("Binding authority {new_authority} to identity {identity}").as_str(),
+        vec![proxy],
+        builder.try_into()?,
+    );
+
+    Ok(TransactionList::new(vec![transaction]))
+}
+
+/// Revokes `revoked_authority` from the identity of the `authority`
+pub async fn revoke_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    revoked_authority: &Pubkey,
+    interface_id: usize,
+    handler_id: usize,
+    approvals: &[AccountMeta],
+) -> Result<TransactionList> {
+    let (proxy, bump) = find_identity_proxy_pda(program_id, revoked_authority)?;
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_handler_accounts(&[
+            AccountMeta::new_readonly(*revoked_authority, false),
+            AccountMeta::new(proxy, false),
+        ])
+        .with_handler_accounts(approvals)
+        .with_instruction_data(&[bump])
+        .seal()?;
+
+    let transaction = Transaction::new_with_accounts(
+        format!("Revoking authority {revoked_authority} from identity {identity}").as_str(),
+        vec![proxy],
+        builder.try_into()?,
+    );
+
+    Ok(TransactionList::new(vec![transaction]))
+}
+
+/// Replaces the `authority` with `new_authority` within its identity.
+/// The resulting transaction must be signed by both wallets.
+pub async fn rotate_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    new_authority: &Pubkey,
+    interface_id: usize,
+    handler_id: usize,
+    approvals: &[AccountMeta],
+) -> Result<TransactionList> {
+    let (new_proxy, new_bump) = find_identity_proxy_pda(program_id, new_authority)?;
+    let (proxy, bump) = find_identity_proxy_pda(program_id, authority)?;
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_system_program_account()
+        .with_handler_accounts(&[
+            AccountMeta::new_readonly(*new_authority, true),
+            AccountMeta::new(new_proxy, false),
+            AccountMeta::new(proxy, false),
+        ])
+        .with_handler_accounts(approvals)
+        .with_instruction_data(&[new_bump, bump])
+        .seal()?;
+
+    let transaction = Transaction::new_with_accounts(
+        format!("Rotating authority {authority} of identity {identity}").as_str(),
+        vec![new_proxy, proxy],
+        builder.try_into()?,
+    );
+
+    Ok(TransactionList::new(vec![transaction]))
+}
+
+/// Updates the approval threshold and authority weights of the identity
+pub async fn set_identity_policy(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    policy: &PolicyArgs,
+    interface_id: usize,
+    handler_id: usize,
+    approvals: &[AccountMeta],
+) -> Result<TransactionList> {
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_handler_accounts(approvals)
+        .with_instruction_data(&policy.try_to_vec()?)
+        .seal()?;
+
+    let transaction = Transaction::new_with_accounts(
+        format!("Updating policy of identity {identity}").as_str(),
+        vec![*identity],
+        builder.try_into()?,
+    );
+
+    Ok(TransactionList::new(vec![transaction]))
+}
+
+/// Creates a proposal for an operation identified by its `payload`
+/// (see [`proposal_payload_with_keys`]). Returns the proposal address
+/// along with the transaction creating it.
+pub async fn create_proposal(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    payload: &Hash,
+    interface_id: usize,
+    handler_id: usize,
+) -> Result<(Pubkey, TransactionList)> {
+    let transport = kaizen::transport::Transport::global()?;
+    let sequence = match transport.lookup(identity).await? {
+        Some(reference) => {
+            let mut account_data = reference.account_data.lock()?;
+            let account_info = account_data.into_account_info();
+            let identity = Identity::try_load(&account_info)?;
+            identity.pda_sequence()?
+        }
+        None => return Err(error!("Identity: missing identity account {identity}")),
+    };
+
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_generic_account_templates(1)
+        .with_sequence(sequence)
+        .with_instruction_data(payload.as_ref())
+        .seal()?;
+
+    let proposal = builder.generic_template_accounts()[0].pubkey;
+    let transaction = Transaction::new_with_accounts(
+        format!("Creating proposal {proposal} for identity {identity}").as_str(),
+        vec![proposal],
+        builder.try_into()?,
+    );
+
+    Ok((proposal, TransactionList::new(vec![transaction])))
+}
+
+/// Approves the `proposal` on behalf of the `authority`
+pub async fn approve_proposal(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    proposal: &Pubkey,
+    interface_id: usize,
+    handler_id: usize,
+) -> Result<TransactionList> {
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_handler_accounts(&[AccountMeta::new(*proposal, false)])
+        .seal()?;
+
+    let transaction = Transaction::new_with_accounts(
+        format!("Approving proposal {proposal}").as_str(),
+        vec![*proposal],
+        builder.try_into()?,
+    );
+
+    Ok(TransactionList::new(vec![transaction]))
+}
+
+/// Closes the `proposal` created by the `authority`
+pub async fn cancel_proposal(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    identity: &Pubkey,
+    proposal: &Pubkey,
+    interface_id: usize,
+    handler_id: usize,
+) -> Result<TransactionList> {
+    let builder = InstructionBuilder::new(program_id, interface_id, handler_id as u16)
+        .with_authority(authority)
+        .with_identity(identity)
+        .with_handler_accounts(&[AccountMeta::new(*proposal, false)])
+        .seal()?;
+
+    let transaction = Transaction::new_with_accounts(
+        format!("Cancelling proposal {proposal}").as_str(),
+        vec![*proposal],
+        builder.try_into()?,
+    );
+
+    Ok(TransactionList::new(vec![transaction]))
+}
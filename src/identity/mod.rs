@@ -4,6 +4,8 @@
 //! User Identity allows:
 //!     - Binding multiple user wallets to the same user account
 //!     - Track various user properties via a single identity account
+//!     - Requiring approvals of several bound wallets (weighted M-of-N
+//!       policies) for sensitive operations
 //!
 
 pub mod program;
@@ -8,6 +8,7 @@ use kaizen::error::*;
 use kaizen::prelude::*;
 use kaizen::result::Result;
 use serde::*;
+use solana_program::hash::{hashv, Hash};
 use solana_program::pubkey::Pubkey;
 use std::cell::RefCell;
 
@@ -33,6 +34,63 @@ impl<'info, 'refs> IdentityProxy<'info, 'refs> {
     }
 }
 
+#[derive(Meta)]
+#[repr(packed)]
+pub struct IdentityProposalMeta {
+    version: u32,
+    identity_pubkey: Pubkey,
+    proposer: Pubkey,
+    payload: Hash,
+}
+
+/// Pending operation collecting approvals of identity authorities
+/// over time (see [`Identity::try_authorize`])
+#[container(Containers::IdentityProposal)]
+pub struct IdentityProposal<'info, 'refs> {
+    pub meta: RefCell<&'info mut IdentityProposalMeta>,
+    pub store: SegmentStore<'info, 'refs>,
+    // ---
+    pub approvals: Array<'info, 'refs, Pubkey>,
+}
+
+impl<'info, 'refs> IdentityProposal<'info, 'refs> {
+    pub fn init(&self, identity: &Pubkey, proposer: &Pubkey, payload: &Hash) -> Result<()> {
+        let mut meta = self.meta.borrow_mut();
+        meta.set_version(1);
+        meta.set_identity_pubkey(*identity);
+        meta.set_proposer(*proposer);
+        meta.set_payload(*payload);
+        Ok(())
+    }
+
+    pub fn identity(&self) -> Pubkey {
+        self.meta.borrow().get_identity_pubkey()
+    }
+
+    pub fn proposer(&self) -> Pubkey {
+        self.meta.borrow().get_proposer()
+    }
+
+    pub fn payload(&self) -> Hash {
+        self.meta.borrow().get_payload()
+    }
+
+    pub fn has_approval(&self, authority: &Pubkey) -> bool {
+        self.approvals.iter().any(|pubkey| *pubkey == *authority)
+    }
+
+    /// Verifies that the proposal belongs to the `identity` and approves the `payload`
+    pub fn try_verify(&self, identity: &Identity, payload: &Hash) -> Result<()> {
+        if self.identity() != *identity.pubkey() {
+            return Err(program_error_code!(ErrorCode::IdentityAccess));
+        }
+        if self.payload() != *payload {
+            return Err(program_error_code!(ErrorCode::IdentityProposalMismatch));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 #[repr(u32)]
 pub enum DataType {
@@ -42,6 +100,10 @@ pub enum DataType {
 }
 
 const FLAG_READONLY: u32 = 0x00000001;
+/// Approval weight of an authority record is kept in the second byte of
+/// the record flags (records created without a weight carry a weight of 1)
+const FLAG_WEIGHT_MASK: u32 = 0x0000ff00;
+const FLAG_WEIGHT_SHIFT: u32 = 8;
 
 #[derive(Meta, Copy, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct IdentityRecordStore {
@@ -76,6 +138,14 @@ pub enum Op {
     CreateCollections(Vec<(u32, Option<u32>)>),
 }
 
+/// Arguments of [`Identity::set_policy`]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct PolicyArgs {
+    pub threshold: u32,
+    /// Authority weights to update
+    pub weights: Vec<(Pubkey, u8)>,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub enum Instr {
     Ops(Vec<Op>),
@@ -105,6 +175,20 @@ pub struct IdentityRecord {
     pub pubkey: Pubkey,
 }
 
+impl IdentityRecord {
+    /// Approval weight of the record (applicable to authority records)
+    pub fn weight(&self) -> u32 {
+        match (self.flags & FLAG_WEIGHT_MASK) >> FLAG_WEIGHT_SHIFT {
+            0 => 1,
+            weight => weight,
+        }
+    }
+
+    pub fn set_weight(&mut self, weight: u8) {
+        self.flags = (self.flags & !FLAG_WEIGHT_MASK) | ((weight as u32) << FLAG_WEIGHT_SHIFT);
+    }
+}
+
 impl PartialEq for IdentityRecord {
     fn eq(&self, other: &Self) -> bool {
         self.data_type == other.data_type && self.pubkey == other.pubkey
@@ -116,7 +200,9 @@ impl PartialEq for IdentityRecord {
 pub struct IdentityMeta {
     pub version: u32,
     pub pda_sequence: u64,
-    pub reserved_for_future_flags: u32,
+    /// Combined weight of authorities required to approve
+    /// sensitive operations (a threshold of 0 is treated as 1)
+    pub approval_threshold: u32,
     pub referrer: Pubkey,
     pub creation_date: Date,
 }
@@ -142,7 +228,7 @@ impl<'info, 'refs> Identity<'info, 'refs> {
         let mut meta = self.meta.try_borrow_mut()?;
         meta.set_version(1);
         meta.set_pda_sequence(0);
-        meta.set_reserved_for_future_flags(0);
+        meta.set_approval_threshold(0);
         Ok(())
     }
 
@@ -164,8 +250,129 @@ impl<'info, 'refs> Identity<'info, 'refs> {
         Ok(meta.referrer)
     }
 
+    /// Combined authority weight required to approve sensitive operations
+    pub fn approval_threshold(&self) -> Result<u32> {
+        let meta = self.meta.try_borrow()?;
+        Ok(meta.get_approval_threshold().max(1))
+    }
+
+    pub fn set_approval_threshold(&self, threshold: u32) -> Result<()> {
+        let mut meta = self.meta.try_borrow_mut()?;
+        meta.set_approval_threshold(threshold);
+        Ok(())
+    }
+
+    /// Approval weight of the authority (0 if the authority is not bound to the identity)
+    pub fn authority_weight(&self, pubkey: &Pubkey) -> u32 {
+        self.records
+            .iter()
+            .find(|entry| {
+                entry.data_type == (DataType::Authority as u32) && entry.pubkey == *pubkey
+            })
+            .map(|entry| entry.weight())
+            .unwrap_or(0)
+    }
+
+    /// Combined approval weight of all authorities bound to the identity
+    pub fn total_authority_weight(&self) -> u32 {
+        self.records
+            .iter()
+            .filter(|entry| entry.data_type == (DataType::Authority as u32))
+            .map(|entry| entry.weight())
+            .sum()
+    }
+
+    /// Verifies that the operation executed by the current handler is approved
+    /// by authorities meeting the approval threshold of the identity.
+    ///
+    /// Approvals are supplied as handler accounts following the first
+    /// `handler_accounts_len` accounts used by the handler itself. Each approval
+    /// account is either a bound authority co-signing the transaction or an
+    /// [`IdentityProposal`] (collecting approvals over time) created for the
+    /// [`proposal_payload`] of this operation. Proposals are closed once used,
+    /// with their lamports refunded to the proposer. Unless the proposer is the
+    /// authority executing the operation, the proposer account must be supplied
+    /// among the approval accounts (as a co-signer or a writable account). Other
+    /// accounts not owned by the program are rejected.
+    pub fn try_authorize(
+        &self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        handler_accounts_len: usize,
+    ) -> Result<()> {
+        let approval_accounts = ctx
+            .handler_accounts
+            .get(handler_accounts_len..)
+            .unwrap_or_default();
+
+        let mut approvers = vec![*ctx.authority.key];
+        let mut proposals = Vec::new();
+        let mut recipients = vec![ctx.authority];
+        let mut foreign_accounts = Vec::new();
+        for account in approval_accounts.iter() {
+            if account.is_signer {
+                approvers.push(*account.key);
+                recipients.push(account);
+                continue;
+            }
+
+            if account.owner != ctx.program_id {
+                foreign_accounts.push(account);
+                continue;
+            }
+            let proposal = IdentityProposal::try_load(account)?;
+            let payload = proposal_payload(
+                ctx.interface_id,
+                ctx.handler_id,
+                &ctx.handler_accounts[..handler_accounts_len],
+                ctx.instruction_data,
+            );
+            proposal.try_verify(self, &payload)?;
+            approvers.extend(proposal.approvals.iter().copied());
+            proposals.push((account, proposal.proposer()));
+        }
+
+        // accounts not owned by the program are only accepted as
+        // writable proposers receiving refunds of closed proposals
+        for account in foreign_accounts {
+            let is_proposer = proposals
+                .iter()
+                .any(|(_, proposer)| proposer == account.key);
+            if !account.is_writable || !is_proposer {
+                return Err(error_code!(ErrorCode::AccountOwnership).with_account(account.key));
+            }
+            recipients.push(account);
+        }
+
+        approvers.sort();
+        approvers.dedup();
+        let weight: u32 = approvers
+            .iter()
+            .map(|pubkey| self.authority_weight(pubkey))
+            .sum();
+        if weight < self.approval_threshold()? {
+            return Err(program_error_code!(ErrorCode::IdentityApprovalThreshold));
+        }
+
+        for (account, proposer) in proposals {
+            let recipient = match recipients
+                .iter()
+                .find(|recipient| *recipient.key == proposer)
+            {
+                Some(recipient) => *recipient,
+                None => {
+                    return Err(error_code!(ErrorCode::NotEnoughAccounts)
+                        .with_account(&proposer)
+                        .with_message("missing proposer account of the closed proposal"))
+                }
+            };
+            ctx.try_close_account(account, recipient)?;
+        }
+
+        Ok(())
+    }
+
     // Insert Authority Pubkey as IdentityEntry into the entry list
-    pub fn try_insert_authority(&mut self, pubkey: &Pubkey) -> Result<()> {
+    pub fn try_insert_authority(&self, pubkey: &Pubkey) -> Result<()> {
         let record = IdentityRecord {
             data_type: DataType::Authority as u32,
             flags: 0,
@@ -179,20 +386,36 @@ impl<'info, 'refs> Identity<'info, 'refs> {
     /// This function may shift the underlying account memory buffer
     /// as such any references to account data should be considered
     /// invalid after its use.
-    pub unsafe fn try_remove_entry(&mut self, target: &IdentityRecord) -> Result<()> {
+    pub unsafe fn try_remove_entry(&self, target: &IdentityRecord) -> Result<()> {
         for idx in 0..self.records.len() {
             let entry = self.records.get_at(idx);
             if entry == target {
                 if entry.flags & FLAG_READONLY != 0 {
                     return Err(program_error_code!(ErrorCode::ReadOnlyAccess));
                 }
-                self.records.try_remove_at(idx, true)?;
+                return self.records.try_remove_at(idx, true);
             }
         }
 
         Err(program_error_code!(ErrorCode::EntryNotFound))
     }
 
+    /// Returns the primary (first registered) authority of the identity
+    pub fn primary_authority(&self) -> Option<Pubkey> {
+        self.records
+            .iter()
+            .find(|entry| entry.data_type == (DataType::Authority as u32))
+            .map(|entry| entry.pubkey)
+    }
+
+    /// Returns the number of authorities bound to the identity
+    pub fn authority_count(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|entry| entry.data_type == (DataType::Authority as u32))
+            .count()
+    }
+
     /// Check if identity has an authority pubkey in the list
     pub fn try_has_authority(&self, pubkey: &Pubkey) -> Result<bool> {
         for entry in self.records.iter() {
@@ -311,38 +534,340 @@ impl<'info, 'refs> Identity<'info, 'refs> {
         None
     }
 
-    // TODO: testing sandbox
-    /// Register a separate authority with an identity and create a new proxy account for the authority being registered
-    pub fn try_register_authority_with_identity(ctx: &ContextReference) -> Result<()> {
+    /// Creates an identity proxy for the `authority` (at the address derived
+    /// from the authority wallet) pointing to this identity
+    fn try_create_proxy(
+        &self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        authority: &Pubkey,
+        bump: u8,
+        proxy_account: &'refs AccountInfo<'info>,
+    ) -> Result<()> {
+        let allocation_args =
+            AccountAllocationArgs::new_with_payer(AddressDomain::None, AllocationPayer::Authority);
+        ctx.try_create_pda_with_args(
+            IdentityProxy::initial_data_len(),
+            &allocation_args,
+            &[authority.as_ref(), b"proxy", &[bump]],
+            proxy_account,
+            true,
+        )?;
+        let proxy = IdentityProxy::try_create(proxy_account)?;
+        proxy.init(self.pubkey())?;
+        Ok(())
+    }
+
+    /// Verifies that `proxy_account` is the identity proxy of the `authority`
+    /// pointing to this identity
+    fn try_verify_proxy(
+        &self,
+        ctx: &ContextReference<'info, 'refs, '_, '_>,
+        authority: &Pubkey,
+        bump: u8,
+        proxy_account: &'refs AccountInfo<'info>,
+    ) -> Result<()> {
+        let pda = Pubkey::create_program_address(
+            &[authority.as_ref(), b"proxy", &[bump]],
+            ctx.program_id,
+        )?;
+        if pda != *proxy_account.key {
+            return Err(program_error_code!(ErrorCode::IdentityAccess));
+        }
+        let proxy = IdentityProxy::try_load(proxy_account)?;
+        let identity_pubkey = proxy.meta.borrow().get_identity_pubkey();
+        if identity_pubkey != *self.pubkey() {
+            return Err(program_error_code!(ErrorCode::IdentityAccess));
+        }
+        Ok(())
+    }
+
+    /// Binds an additional authority (wallet) to the identity and creates
+    /// the identity proxy for it. The new authority must co-sign the transaction.
+    ///
+    /// Handler accounts: `[new authority (signer), new authority proxy, approvals..]`
+    /// Instruction data: `[new authority proxy bump]`
+    pub fn bind_authority(ctx: &ContextReference) -> ProgramResult {
         let identity = ctx.try_identity()?;
+        if ctx.handler_accounts.len() < 2 || ctx.instruction_data.is_empty() {
+            return Err(program_error_code!(
+                ErrorCode::IdentityMissingForeignAuthority
+            ));
+        }
+        identity.try_authorize(ctx, 2)?;
+
+        let authority = &ctx.handler_accounts[0];
+        if !authority.is_signer {
+            return Err(program_error_code!(ErrorCode::AuthorityMustSign));
+        }
+        if identity.try_has_authority(authority.key)? {
+            return Err(program_error_code!(ErrorCode::IdentityCollision));
+        }
+
+        identity.try_create_proxy(
+            ctx,
+            authority.key,
+            ctx.instruction_data[0],
+            &ctx.handler_accounts[1],
+        )?;
+        identity.try_insert_authority(authority.key)?;
+        // the authority record grows the identity account
+        ctx.sync_rent(identity.account(), &RentCollector::default())?;
+
+        Ok(())
+    }
+
+    /// Revokes an authority bound to the identity and closes its identity proxy
+    /// (the last remaining authority of the identity can not be revoked).
+    ///
+    /// Handler accounts: `[revoked authority, revoked authority proxy, approvals..]`
+    /// Instruction data: `[revoked authority proxy bump]`
+    pub fn revoke_authority(ctx: &ContextReference) -> ProgramResult {
+        let identity = ctx.try_identity()?;
+        if ctx.handler_accounts.len() < 2 || ctx.instruction_data.is_empty() {
+            return Err(program_error_code!(
+                ErrorCode::IdentityMissingForeignAuthority
+            ));
+        }
+        identity.try_authorize(ctx, 2)?;
+
+        let authority = ctx.handler_accounts[0].key;
+        let proxy_account = &ctx.handler_accounts[1];
+        if !identity.try_has_authority(authority)? {
+            return Err(program_error_code!(ErrorCode::EntryNotFound));
+        }
+        if identity.authority_count() < 2 {
+            return Err(program_error_code!(ErrorCode::IdentityLastAuthority));
+        }
+        // remaining authorities must be able to meet the approval threshold
+        if identity.total_authority_weight() - identity.authority_weight(authority)
+            < identity.approval_threshold()?
+        {
+            return Err(program_error_code!(ErrorCode::IdentityInvalidPolicy));
+        }
+        identity.try_verify_proxy(ctx, authority, ctx.instruction_data[0], proxy_account)?;
 
-        // the incoming PDA should have 0 sequence derivation from the target wallet
-        let _foreign_wallet_address = if ctx.handler_accounts.len() != 1 {
+        let record = IdentityRecord {
+            data_type: DataType::Authority as u32,
+            flags: 0,
+            pubkey: *authority,
+        };
+        unsafe {
+            identity.try_remove_entry(&record)?;
+        }
+        ctx.try_close_account(proxy_account, ctx.authority)?;
+
+        Ok(())
+    }
+
+    /// Replaces the authority signing the transaction with a new authority
+    /// (retaining the position of the authority record, i.e. the primary
+    /// authority remains primary). The new authority must co-sign the transaction.
+    ///
+    /// Handler accounts: `[new authority (signer), new authority proxy, current authority proxy, approvals..]`
+    /// Instruction data: `[new authority proxy bump, current authority proxy bump]`
+    pub fn rotate_authority(ctx: &ContextReference) -> ProgramResult {
+        let identity = ctx.try_identity()?;
+        if ctx.handler_accounts.len() < 3 || ctx.instruction_data.len() < 2 {
             return Err(program_error_code!(
                 ErrorCode::IdentityMissingForeignAuthority
             ));
-        } else {
-            &ctx.handler_accounts[0]
+        }
+        identity.try_authorize(ctx, 3)?;
+
+        let authority = &ctx.handler_accounts[0];
+        if !authority.is_signer {
+            return Err(program_error_code!(ErrorCode::AuthorityMustSign));
+        }
+        if identity.try_has_authority(authority.key)? {
+            return Err(program_error_code!(ErrorCode::IdentityCollision));
+        }
+
+        let current_proxy_account = &ctx.handler_accounts[2];
+        identity.try_verify_proxy(
+            ctx,
+            ctx.authority.key,
+            ctx.instruction_data[1],
+            current_proxy_account,
+        )?;
+
+        let record = match identity.records.iter_mut().find(|entry| {
+            entry.data_type == (DataType::Authority as u32) && entry.pubkey == *ctx.authority.key
+        }) {
+            Some(record) => record,
+            None => return Err(program_error_code!(ErrorCode::EntryNotFound)),
         };
+        if record.flags & FLAG_READONLY != 0 {
+            return Err(program_error_code!(ErrorCode::ReadOnlyAccess));
+        }
+        record.pubkey = *authority.key;
 
-        let allocation_args = AccountAllocationArgs::new(AddressDomain::Authority);
-        let proxy_account =
-            ctx.try_create_pda(IdentityProxy::initial_data_len(), &allocation_args)?;
-        let proxy = IdentityProxy::try_create(proxy_account)?;
+        identity.try_create_proxy(
+            ctx,
+            authority.key,
+            ctx.instruction_data[0],
+            &ctx.handler_accounts[1],
+        )?;
+        ctx.try_close_account(current_proxy_account, ctx.authority)?;
 
-        proxy.init(identity.pubkey())?;
+        Ok(())
+    }
+
+    /// Updates the approval threshold and authority weights of the identity.
+    /// Requires approval under the current policy; the resulting threshold
+    /// must be attainable by the bound authorities.
+    ///
+    /// Handler accounts: `[approvals..]`
+    /// Instruction data: [`PolicyArgs`]
+    pub fn set_policy(ctx: &ContextReference) -> ProgramResult {
+        let identity = ctx.try_identity()?;
+        identity.try_authorize(ctx, 0)?;
+
+        let args = PolicyArgs::try_from_slice(ctx.instruction_data)?;
+        for (pubkey, weight) in args.weights.iter() {
+            if *weight == 0 {
+                return Err(program_error_code!(ErrorCode::IdentityInvalidPolicy));
+            }
+            let record = match identity.records.iter_mut().find(|entry| {
+                entry.data_type == (DataType::Authority as u32) && entry.pubkey == *pubkey
+            }) {
+                Some(record) => record,
+                None => return Err(program_error_code!(ErrorCode::EntryNotFound)),
+            };
+            record.set_weight(*weight);
+        }
+
+        if args.threshold == 0 || args.threshold > identity.total_authority_weight() {
+            return Err(program_error_code!(ErrorCode::IdentityInvalidPolicy));
+        }
+        identity.set_approval_threshold(args.threshold)?;
+
+        Ok(())
+    }
+
+    /// Creates an [`IdentityProposal`] for an operation (identified by its
+    /// [`proposal_payload`]) approved by the proposing authority.
+    ///
+    /// Instruction data: `[payload (32 bytes)]`
+    pub fn propose(ctx: &ContextReference) -> ProgramResult {
+        let identity = ctx.try_identity()?;
+        if ctx.instruction_data.len() != std::mem::size_of::<Hash>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let payload = Hash::new(ctx.instruction_data);
+
+        let allocation_args = AccountAllocationArgs::new_with_payer(
+            AddressDomain::Identity,
+            AllocationPayer::Authority,
+        );
+        let proposal =
+            IdentityProposal::try_allocate(ctx, &allocation_args, std::mem::size_of::<Pubkey>())?;
+        proposal.init(identity.pubkey(), ctx.authority.key, &payload)?;
+        unsafe {
+            proposal.approvals.try_insert(ctx.authority.key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Records approval of an [`IdentityProposal`] by the authority
+    ///
+    /// Handler accounts: `[proposal]`
+    pub fn approve(ctx: &ContextReference) -> ProgramResult {
+        let identity = ctx.try_identity()?;
+        let account = match ctx.handler_accounts.first() {
+            Some(account) => account,
+            None => return Err(program_error_code!(ErrorCode::NotEnoughAccounts)),
+        };
+        let proposal = IdentityProposal::try_load(account)?;
+        if proposal.identity() != *identity.pubkey() {
+            return Err(program_error_code!(ErrorCode::IdentityAccess));
+        }
+        if proposal.has_approval(ctx.authority.key) {
+            return Err(program_error_code!(ErrorCode::IdentityCollision));
+        }
+        unsafe {
+            proposal.approvals.try_insert(ctx.authority.key)?;
+        }
+        // the approval grows the proposal account
+        ctx.sync_rent(proposal.account(), &RentCollector::default())?;
+
+        Ok(())
+    }
+
+    /// Closes an abandoned [`IdentityProposal`] (only the proposing authority
+    /// can cancel the proposal)
+    ///
+    /// Handler accounts: `[proposal]`
+    pub fn cancel_proposal(ctx: &ContextReference) -> ProgramResult {
+        let identity = ctx.try_identity()?;
+        let account = match ctx.handler_accounts.first() {
+            Some(account) => account,
+            None => return Err(program_error_code!(ErrorCode::NotEnoughAccounts)),
+        };
+        let proposal = IdentityProposal::try_load(account)?;
+        if proposal.identity() != *identity.pubkey() || proposal.proposer() != *ctx.authority.key {
+            return Err(program_error_code!(ErrorCode::IdentityAccess));
+        }
+        // the proposal is refunded to the proposer (the authority)
+        ctx.try_close_account(account, ctx.authority)?;
 
         Ok(())
     }
 }
 
-/// Returns pubkey of the identity proxy given program_id and the authority (wallet address)
+// Returns pubkey of the identity proxy given program_id and the authority (wallet address)
 pub fn find_identity_proxy_pubkey(program_id: &Pubkey, authority: &Pubkey) -> Result<Pubkey> {
+    let (address, _bump_seed) = find_identity_proxy_pda(program_id, authority)?;
+    Ok(address)
+}
+
+/// Returns pubkey and bump seed of the identity proxy given program_id and the authority (wallet address)
+pub fn find_identity_proxy_pda(program_id: &Pubkey, authority: &Pubkey) -> Result<(Pubkey, u8)> {
     let bytes = "proxy".as_bytes();
     let seed_suffix = bytes.to_vec();
     let seeds = vec![authority.as_ref(), seed_suffix.as_ref()];
-    let (address, _bump_seed) = Pubkey::find_program_address(&seeds[..], program_id);
-    Ok(address)
+    Ok(Pubkey::find_program_address(&seeds[..], program_id))
+}
+
+/// Digest identifying an operation approved by an [`IdentityProposal`]: the
+/// handler, accounts used by the handler (excluding approvals) and instruction data
+pub fn proposal_payload(
+    interface_id: usize,
+    handler_id: usize,
+    handler_accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> Hash {
+    let keys = handler_accounts
+        .iter()
+        .map(|account| *account.key)
+        .collect::<Vec<_>>();
+    proposal_payload_with_keys(interface_id, handler_id, &keys, instruction_data)
+}
+
+pub fn proposal_payload_with_keys(
+    interface_id: usize,
+    handler_id: usize,
+    handler_keys: &[Pubkey],
+    instruction_data: &[u8],
+) -> Hash {
+    let interface_id = (interface_id as u64).to_le_bytes();
+    let handler_id = (handler_id as u64).to_le_bytes();
+    let mut data: Vec<&[u8]> = vec![&interface_id, &handler_id];
+    data.extend(handler_keys.iter().map(|key| key.as_ref()));
+    data.push(instruction_data);
+    hashv(&data)
 }
 
-declare_handlers!(Identity::<'info, 'refs>, [Identity::create]);
+declare_handlers!(
+    Identity::<'info, 'refs>,
+    [
+        Identity::create,
+        Identity::bind_authority,
+        Identity::revoke_authority,
+        Identity::rotate_authority,
+        Identity::set_policy,
+        Identity::propose,
+        Identity::approve,
+        Identity::cancel_proposal
+    ]
+);
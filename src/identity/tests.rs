@@ -4,6 +4,8 @@
 #[cfg(test)]
 mod tests {
     use crate::identity::program::*;
+    use borsh::*;
+    use kaizen::emulator::interface::EmulatorInterface;
     use kaizen::emulator::Simulator;
     use kaizen::prelude::*;
     use kaizen::result::Result;
@@ -66,4 +68,343 @@ mod tests {
 
         Ok(())
     }
+
+    async fn load_authorities(simulator: &Simulator, identity: &Pubkey) -> Result<Vec<Pubkey>> {
+        let reference = simulator
+            .lookup(identity)
+            .await?
+            .expect("missing identity account");
+        let mut account_data = reference.account_data.lock()?;
+        let account_info = account_data.into_account_info();
+        let identity = Identity::try_load(&account_info)?;
+        Ok(identity
+            .records
+            .iter()
+            .filter(|entry| entry.data_type == DataType::Authority as u32)
+            .map(|entry| entry.pubkey)
+            .collect())
+    }
+
+    async fn load_lamports(simulator: &Simulator, pubkey: &Pubkey) -> Result<u64> {
+        let reference = simulator.lookup(pubkey).await?.expect("missing account");
+        let lamports = reference.account_data.lock()?.lamports;
+        Ok(lamports)
+    }
+
+    #[async_std::test]
+    async fn identity_authority_binding() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+        let identity = crate::identity::client::create_identity_for_unit_tests(
+            &simulator,
+            &simulator.authority(),
+            &simulator.program_id(),
+        )
+        .await?;
+
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_identity(&identity);
+
+        // bind a second wallet
+        let wallet = generate_random_pubkey();
+        let (proxy, bump) = find_identity_proxy_pda(&simulator.program_id(), &wallet)?;
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_system_program_account()
+            .with_handler_accounts(&[
+                AccountMeta::new_readonly(wallet, true),
+                AccountMeta::new(proxy, false),
+            ])
+            .with_instruction_data(&[bump])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::bind_authority(ctx)?;
+                Ok(())
+            })
+            .await?;
+        assert!(simulator.lookup(&proxy).await?.is_some());
+        assert_eq!(
+            load_authorities(&simulator, &identity).await?,
+            vec![simulator.authority(), wallet]
+        );
+        // the identity account remains rent-exempt after growing
+        {
+            let reference = simulator
+                .lookup(&identity)
+                .await?
+                .expect("missing identity account");
+            let account_data = reference.account_data.lock()?;
+            let minimum_balance =
+                solana_program::rent::Rent::default().minimum_balance(account_data.data_len());
+            assert!(account_data.lamports >= minimum_balance);
+        }
+
+        // rotate the primary authority to a new wallet
+        let rotated = generate_random_pubkey();
+        let (rotated_proxy, rotated_bump) =
+            find_identity_proxy_pda(&simulator.program_id(), &rotated)?;
+        let (primary_proxy, primary_bump) =
+            find_identity_proxy_pda(&simulator.program_id(), &simulator.authority())?;
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_system_program_account()
+            .with_handler_accounts(&[
+                AccountMeta::new_readonly(rotated, true),
+                AccountMeta::new(rotated_proxy, false),
+                AccountMeta::new(primary_proxy, false),
+            ])
+            .with_instruction_data(&[rotated_bump, primary_bump])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::rotate_authority(ctx)?;
+                Ok(())
+            })
+            .await?;
+        assert!(simulator.lookup(&primary_proxy).await?.is_none());
+        assert!(simulator.lookup(&rotated_proxy).await?.is_some());
+        // the rotated authority remains primary
+        assert_eq!(
+            load_authorities(&simulator, &identity).await?,
+            vec![rotated, wallet]
+        );
+
+        // revoke the second wallet using the rotated authority
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&rotated)
+            .with_identity(&identity);
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_handler_accounts(&[
+                AccountMeta::new_readonly(wallet, false),
+                AccountMeta::new(proxy, false),
+            ])
+            .with_instruction_data(&[bump])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::revoke_authority(ctx)?;
+                Ok(())
+            })
+            .await?;
+        assert!(simulator.lookup(&proxy).await?.is_none());
+        assert_eq!(
+            load_authorities(&simulator, &identity).await?,
+            vec![rotated]
+        );
+
+        // the last authority can not be revoked
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_handler_accounts(&[
+                AccountMeta::new_readonly(rotated, false),
+                AccountMeta::new(rotated_proxy, false),
+            ])
+            .with_instruction_data(&[rotated_bump])
+            .seal()?;
+        let result = simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::revoke_authority(ctx)?;
+                Ok(())
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn identity_approval_threshold() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+        let identity = crate::identity::client::create_identity_for_unit_tests(
+            &simulator,
+            &simulator.authority(),
+            &simulator.program_id(),
+        )
+        .await?;
+
+        let bind = |config: &InstructionBuilderConfig,
+                    wallet: &Pubkey,
+                    approvals: &[AccountMeta]|
+         -> Result<Arc<InstructionBuilder>> {
+            let (proxy, bump) = find_identity_proxy_pda(&simulator.program_id(), wallet)?;
+            InstructionBuilder::new_with_config_for_testing(config)
+                .with_system_program_account()
+                .with_handler_accounts(&[
+                    AccountMeta::new_readonly(*wallet, true),
+                    AccountMeta::new(proxy, false),
+                ])
+                .with_handler_accounts(approvals)
+                .with_instruction_data(&[bump])
+                .seal()
+        };
+
+        // bind two more wallets and require approval of 2 out of 3 authorities
+        let config = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&simulator.authority())
+            .with_identity(&identity);
+        let wallets = [generate_random_pubkey(), generate_random_pubkey()];
+        for wallet in wallets.iter() {
+            simulator
+                .execute_handler(bind(&config, wallet, &[])?, |ctx: &ContextReference| {
+                    Identity::bind_authority(ctx)?;
+                    Ok(())
+                })
+                .await?;
+        }
+
+        let policy = PolicyArgs {
+            threshold: 2,
+            weights: vec![],
+        };
+        let builder = InstructionBuilder::new_with_config_for_testing(&config)
+            .with_instruction_data(&policy.try_to_vec()?)
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::set_policy(ctx)?;
+                assert_eq!(ctx.try_identity()?.approval_threshold()?, 2);
+                Ok(())
+            })
+            .await?;
+
+        // a single authority is no longer sufficient
+        let wallet = generate_random_pubkey();
+        let result = simulator
+            .execute_handler(bind(&config, &wallet, &[])?, |ctx: &ContextReference| {
+                Identity::bind_authority(ctx)?;
+                Ok(())
+            })
+            .await;
+        assert!(result.is_err());
+
+        // ... while a co-signing authority meets the threshold
+        let cosigner = AccountMeta::new_readonly(wallets[0], true);
+        simulator
+            .execute_handler(
+                bind(&config, &wallet, &[cosigner])?,
+                |ctx: &ContextReference| {
+                    Identity::bind_authority(ctx)?;
+                    Ok(())
+                },
+            )
+            .await?;
+
+        // bound wallets (system accounts) pay for the proposal and its rent
+        for wallet in wallets.iter() {
+            simulator
+                .fund(
+                    wallet,
+                    &solana_program::system_program::id(),
+                    crate::utils::u64sol_to_lamports(1),
+                )
+                .await?;
+        }
+
+        // collect approvals for revoking the bound wallet using a proposal
+        let (proxy, bump) = find_identity_proxy_pda(&simulator.program_id(), &wallet)?;
+        let payload = proposal_payload_with_keys(0, 0, &[wallet, proxy], &[bump]);
+        let proposer = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&wallets[0])
+            .with_identity(&identity)
+            .with_sequence(0);
+        let builder = InstructionBuilder::new_with_config_for_testing(&proposer)
+            .with_generic_account_templates(1)
+            .with_instruction_data(payload.as_ref())
+            .seal()?;
+        let proposal = builder.generic_template_accounts()[0].pubkey;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::propose(ctx)?;
+                Ok(())
+            })
+            .await?;
+
+        let revoke = |config: &InstructionBuilderConfig,
+                      approvals: &[AccountMeta]|
+         -> Result<Arc<InstructionBuilder>> {
+            InstructionBuilder::new_with_config_for_testing(config)
+                .with_handler_accounts(&[
+                    AccountMeta::new_readonly(wallet, false),
+                    AccountMeta::new(proxy, false),
+                ])
+                .with_handler_accounts(approvals)
+                .with_instruction_data(&[bump])
+                .seal()
+        };
+        let result = simulator
+            .execute_handler(
+                revoke(&config, &[AccountMeta::new(proposal, false)])?,
+                |ctx: &ContextReference| {
+                    Identity::revoke_authority(ctx)?;
+                    Ok(())
+                },
+            )
+            .await;
+        assert!(result.is_err());
+
+        let approver = InstructionBuilderConfig::new(simulator.program_id())
+            .with_authority(&wallets[1])
+            .with_identity(&identity);
+        let builder = InstructionBuilder::new_with_config_for_testing(&approver)
+            .with_handler_accounts(&[AccountMeta::new(proposal, false)])
+            .seal()?;
+        simulator
+            .execute_handler(builder, |ctx: &ContextReference| {
+                Identity::approve(ctx)?;
+                Ok(())
+            })
+            .await?;
+
+        // accounts not owned by the program are only accepted as writable proposers
+        for account in [
+            AccountMeta::new(generate_random_pubkey(), false),
+            AccountMeta::new_readonly(wallets[0], false),
+        ] {
+            let result = simulator
+                .execute_handler(
+                    revoke(&approver, &[AccountMeta::new(proposal, false), account])?,
+                    |ctx: &ContextReference| {
+                        Identity::revoke_authority(ctx)?;
+                        Ok(())
+                    },
+                )
+                .await;
+            assert!(result.is_err());
+        }
+
+        // the approving authority executes the operation; the used proposal
+        // is refunded to the proposer (supplied following the proposal)
+        let proposal_lamports = load_lamports(&simulator, &proposal).await?;
+        let proposer_lamports = load_lamports(&simulator, &wallets[0]).await?;
+        simulator
+            .execute_handler(
+                revoke(
+                    &approver,
+                    &[
+                        AccountMeta::new(proposal, false),
+                        AccountMeta::new(wallets[0], false),
+                    ],
+                )?,
+                |ctx: &ContextReference| {
+                    Identity::revoke_authority(ctx)?;
+                    Ok(())
+                },
+            )
+            .await?;
+        assert!(simulator.lookup(&proposal).await?.is_none());
+        assert_eq!(
+            load_lamports(&simulator, &wallets[0]).await?,
+            proposer_lamports + proposal_lamports
+        );
+        assert_eq!(
+            load_authorities(&simulator, &identity).await?,
+            vec![simulator.authority(), wallets[0], wallets[1]]
+        );
+
+        Ok(())
+    }
 }
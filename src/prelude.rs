@@ -23,9 +23,12 @@ pub use crate::address::AddressDomain;
 pub use crate::container::array::Array;
 pub use crate::container::collection::{
     PdaCollection, PdaCollectionInterface, PdaCollectionMeta, PdaCollectionReference,
-    PdaProxyCollection, PdaProxyCollectionInterface, PdaProxyCollectionReference, PubkeyCollection,
-    PubkeyCollectionMeta, PubkeyCollectionReference, PubkeyCollectionStore,
+    PdaProxyCollection, PdaProxyCollectionInterface, PdaProxyCollectionReference,
+    PdaRingCollection, PdaRingCollectionInterface, PdaRingCollectionMeta,
+    PdaRingCollectionReference, PubkeyCollection, PubkeyCollectionMeta, PubkeyCollectionReference,
+    PubkeyCollectionStore,
 };
+pub use crate::container::index::{CollectionIndex, IndexKey};
 pub use crate::container::segment::{Layout, Segment, SegmentStore};
 pub use crate::container::ContainerHeader;
 pub use crate::context::{
@@ -0,0 +1,175 @@
+//!
+//! Encryption-at-rest for [`FileStore`](super::FileStore) records.
+//!
+//! Each [`AccountDataStore`](crate::accounts::AccountDataStore) record is sealed using
+//! XChaCha20-Poly1305 with a random nonce. The record header carries a fingerprint
+//! of the key used, allowing records encrypted with previous keys to be read
+//! during key rotation and a wrong key to be reported as such. The account pubkey
+//! is bound to the record as associated data, preventing records from being swapped
+//! between files.
+//!
+//! Record layout: `magic (4) | key fingerprint (8) | nonce (24) | ciphertext`
+//!
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use kaizen::error::*;
+use kaizen::result::Result;
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+
+const MAGIC: [u8; 4] = *b"KZE\x01";
+const FINGERPRINT_LEN: usize = 8;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + FINGERPRINT_LEN + NONCE_LEN;
+
+pub type KeyFingerprint = [u8; FINGERPRINT_LEN];
+
+/// 256-bit store encryption key.
+#[derive(Clone)]
+pub struct StoreKey {
+    key: [u8; 32],
+    fingerprint: KeyFingerprint,
+}
+
+impl StoreKey {
+    pub fn new(key: [u8; 32]) -> StoreKey {
+        let hash = hashv(&[b"kaizen-store-key", &key]).to_bytes();
+        let mut fingerprint = [0u8; FINGERPRINT_LEN];
+        fingerprint.copy_from_slice(&hash[0..FINGERPRINT_LEN]);
+        StoreKey { key, fingerprint }
+    }
+
+    /// Derives the key from a passphrase using Argon2id. The salt must be
+    /// at least 8 bytes long and should be unique to the application.
+    pub fn try_from_passphrase(passphrase: &str, salt: &[u8]) -> Result<StoreKey> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|err| error!("unable to derive store key: {err}"))?;
+        Ok(StoreKey::new(key))
+    }
+
+    pub fn fingerprint(&self) -> &KeyFingerprint {
+        &self.fingerprint
+    }
+}
+
+impl std::fmt::Debug for StoreKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "StoreKey({:02x?})", self.fingerprint)
+    }
+}
+
+/// Encryption settings: the current key used for all writes, and
+/// previous keys that remain accepted for reads until records are rotated.
+#[derive(Debug, Clone)]
+pub struct Encryption {
+    key: StoreKey,
+    previous: Vec<StoreKey>,
+}
+
+impl Encryption {
+    pub fn new(key: StoreKey) -> Encryption {
+        Encryption {
+            key,
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn with_previous_keys(mut self, keys: &[StoreKey]) -> Self {
+        self.previous.extend_from_slice(keys);
+        self
+    }
+
+    pub fn key(&self) -> &StoreKey {
+        &self.key
+    }
+
+    pub fn is_encrypted(record: &[u8]) -> bool {
+        record.len() >= HEADER_LEN && record[0..MAGIC.len()] == MAGIC
+    }
+
+    fn find_key(&self, fingerprint: &[u8]) -> Option<&StoreKey> {
+        std::iter::once(&self.key)
+            .chain(self.previous.iter())
+            .find(|key| key.fingerprint[..] == *fingerprint)
+    }
+
+    pub fn encrypt(&self, pubkey: &Pubkey, data: &[u8]) -> Result<Vec<u8>> {
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&self.key.key));
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: data,
+                    aad: pubkey.as_ref(),
+                },
+            )
+            .map_err(|_| error_code!(ErrorCode::StoreEncryptionFailure).with_account(pubkey))?;
+
+        let mut record = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        record.extend_from_slice(&MAGIC);
+        record.extend_from_slice(&self.key.fingerprint);
+        record.extend_from_slice(&nonce);
+        record.extend_from_slice(&ciphertext);
+        Ok(record)
+    }
+
+    pub fn decrypt(&self, pubkey: &Pubkey, record: &[u8]) -> Result<Vec<u8>> {
+        if !Self::is_encrypted(record) {
+            return Err(error_code!(ErrorCode::StoreRecordNotEncrypted).with_account(pubkey));
+        }
+
+        let fingerprint = &record[MAGIC.len()..MAGIC.len() + FINGERPRINT_LEN];
+        let key = self.find_key(fingerprint).ok_or_else(|| {
+            error_code!(ErrorCode::StoreEncryptionKeyMismatch)
+                .with_account(pubkey)
+                .with_message(&format!(
+                    "record is encrypted with an unknown key {fingerprint:02x?}"
+                ))
+        })?;
+
+        let nonce = XNonce::from_slice(&record[MAGIC.len() + FINGERPRINT_LEN..HEADER_LEN]);
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key.key));
+        cipher
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: &record[HEADER_LEN..],
+                    aad: pubkey.as_ref(),
+                },
+            )
+            .map_err(|_| error_code!(ErrorCode::StoreDecryptionFailure).with_account(pubkey))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubkey::generate_random_pubkey;
+
+    #[test]
+    fn store_encryption_roundtrip_and_rotation() -> Result<()> {
+        let pubkey = generate_random_pubkey();
+        let old = StoreKey::try_from_passphrase("old passphrase", b"kaizen-test-salt")?;
+        let new = StoreKey::try_from_passphrase("new passphrase", b"kaizen-test-salt")?;
+
+        let record = Encryption::new(old.clone()).encrypt(&pubkey, b"account data")?;
+        assert!(Encryption::is_encrypted(&record));
+
+        // wrong key is reported as a key mismatch
+        assert!(Encryption::new(new.clone())
+            .decrypt(&pubkey, &record)
+            .is_err());
+
+        // previous keys remain readable after rotation
+        let rotated = Encryption::new(new).with_previous_keys(&[old]);
+        assert_eq!(rotated.decrypt(&pubkey, &record)?, b"account data");
+
+        // records are bound to their pubkey
+        assert!(rotated.decrypt(&generate_random_pubkey(), &record).is_err());
+        Ok(())
+    }
+}
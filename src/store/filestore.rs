@@ -1,10 +1,12 @@
 //!
-//! File-store (used by the Emulator in multi-user testing)
+//! File-store (used by the Emulator in multi-user testing and as
+//! the native [`PersistentStorage`] backend of the [`PersistentCache`])
 //!
 
 use crate::accounts::AccountDataStore;
 use crate::accounts::AccountDescriptor;
 
+use super::encryption::Encryption;
 use super::*;
 use async_std::fs;
 use async_std::path::Path;
@@ -13,15 +15,41 @@ use async_trait::async_trait;
 use borsh::*;
 use kaizen::accounts::AccountData;
 use kaizen::cache::Cache;
+use kaizen::error::*;
 use kaizen::result::Result;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 use workflow_log::log_error;
 use workflow_log::*;
 
+#[derive(Default)]
+struct EncryptionState {
+    /// Settings used to write records (`None` for plaintext)
+    encryption: Option<Encryption>,
+    /// Settings replaced by rotations that did not complete. These remain
+    /// accepted for reads as long as records written using them may remain
+    /// on disk, and are released once a rotation re-writes all records.
+    retired: Vec<Option<Encryption>>,
+}
+
+fn open_with(encryption: &Option<Encryption>, pubkey: &Pubkey, data: &[u8]) -> Result<Vec<u8>> {
+    match encryption {
+        Some(encryption) => encryption.decrypt(pubkey, data),
+        None if Encryption::is_encrypted(data) => {
+            Err(error_code!(ErrorCode::StoreRecordIsEncrypted).with_account(pubkey))
+        }
+        None => Ok(data.to_vec()),
+    }
+}
+
 #[derive(Clone)]
 pub struct FileStore {
     data_folder: PathBuf,
     cache: Option<Arc<Cache>>,
+    encryption: Arc<RwLock<EncryptionState>>,
+    /// Serializes encryption rotation (exclusive) with record updates (shared)
+    rotation: Arc<async_std::sync::RwLock<()>>,
+    notifier: Notifier,
 }
 
 impl FileStore {
@@ -30,6 +58,11 @@ impl FileStore {
         Path::new(&home_dir).join("workflow").join("accounts")
     }
 
+    pub fn default_cache_folder() -> PathBuf {
+        let home_dir: PathBuf = home::home_dir().unwrap().into();
+        Path::new(&home_dir).join("workflow").join("cache")
+    }
+
     pub fn try_new() -> Result<FileStore> {
         Self::try_new_with_folder_and_cache(None, None)
     }
@@ -50,13 +83,52 @@ impl FileStore {
         );
         std::fs::create_dir_all(&data_folder)?;
 
-        Ok(FileStore { data_folder, cache })
+        Ok(FileStore {
+            data_folder,
+            cache,
+            encryption: Arc::new(RwLock::new(EncryptionState::default())),
+            rotation: Arc::new(async_std::sync::RwLock::new(())),
+            notifier: Notifier::new(),
+        })
     }
-}
 
-#[async_trait]
-impl Store for FileStore {
-    async fn list(&self) -> Result<AccountDescriptorList> {
+    /// Enables encryption of account records stored by this instance.
+    /// Existing plaintext records can be encrypted using [`FileStore::rotate_encryption`].
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = Arc::new(RwLock::new(EncryptionState {
+            encryption: Some(encryption),
+            retired: Vec::new(),
+        }));
+        self
+    }
+
+    fn open(&self, pubkey: &Pubkey, data: Vec<u8>) -> Result<Vec<u8>> {
+        let state = self.encryption.read()?;
+        match open_with(&state.encryption, pubkey, &data) {
+            Ok(data) => Ok(data),
+            Err(err) => state
+                .retired
+                .iter()
+                .find_map(|retired| open_with(retired, pubkey, &data).ok())
+                .ok_or(err),
+        }
+    }
+
+    fn decode(&self, pubkey: &Pubkey, data: Vec<u8>) -> Result<AccountDataStore> {
+        let data = self.open(pubkey, data)?;
+        Ok(AccountDataStore::try_from_slice(&data)?)
+    }
+
+    fn encode(&self, pubkey: &Pubkey, data: Vec<u8>) -> Result<Vec<u8>> {
+        match &self.encryption.read()?.encryption {
+            Some(encryption) => encryption.encrypt(pubkey, &data),
+            None => Ok(data),
+        }
+    }
+
+    /// Returns store records; files not named after a pubkey (such as
+    /// temporary files left by an interrupted rotation) are skipped.
+    fn entries(&self) -> Result<Vec<(Pubkey, std::path::PathBuf)>> {
         let mut entries = std::fs::read_dir(&self.data_folder)?
             .map(|res| res.map(|e| e.path()))
             .collect::<std::result::Result<Vec<_>, std::io::Error>>()?;
@@ -66,10 +138,90 @@ impl Store for FileStore {
 
         entries.sort();
 
+        Ok(entries
+            .into_iter()
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                let pubkey = path
+                    .file_name()
+                    .and_then(|filename| filename.to_str())
+                    .and_then(|filename| Pubkey::from_str(filename).ok())?;
+                Some((pubkey, path))
+            })
+            .collect())
+    }
+
+    /// Re-writes all records using the supplied encryption settings (or as plaintext if `None`).
+    /// Records are read using the current settings; plaintext records are accepted as well,
+    /// allowing an existing unencrypted store to be migrated. Returns the number of records
+    /// processed.
+    ///
+    /// Re-encrypted records are written to temporary files that replace the records only
+    /// once all of them have been written. Record updates are suspended for the duration
+    /// of the rotation. The replaced settings remain accepted for reads until a rotation
+    /// completes, so records that were not replaced due to a failure remain readable (and
+    /// are re-written by a subsequent rotation).
+    pub async fn rotate_encryption(&self, encryption: Option<Encryption>) -> Result<usize> {
+        let _rotation = self.rotation.write().await;
+
+        let entries = self.entries()?;
+        let mut records = Vec::with_capacity(entries.len());
+        for (pubkey, path) in entries.iter() {
+            let data = fs::read(path).await?;
+            let data = if Encryption::is_encrypted(&data) {
+                self.open(pubkey, data)?
+            } else {
+                data
+            };
+            // ensure the record is valid before re-writing it
+            AccountDataStore::try_from_slice(&data)?;
+            records.push((pubkey, path, data));
+        }
+
+        let mut temp_files = Vec::with_capacity(records.len());
+        for (pubkey, path, data) in records.iter() {
+            let data = match &encryption {
+                Some(encryption) => encryption.encrypt(pubkey, data)?,
+                None => data.clone(),
+            };
+            let temp_file = path.with_extension("rotate");
+            if let Err(err) = fs::write(&temp_file, data).await {
+                for temp_file in temp_files.iter().chain(std::iter::once(&temp_file)) {
+                    fs::remove_file(temp_file).await.ok();
+                }
+                return Err(err.into());
+            }
+            temp_files.push(temp_file);
+        }
+
+        {
+            let mut state = self.encryption.write()?;
+            let retired = std::mem::replace(&mut state.encryption, encryption);
+            state.retired.push(retired);
+        }
+
+        for ((_, path, _), temp_file) in records.iter().zip(temp_files.iter()) {
+            fs::rename(temp_file, path).await?;
+        }
+
+        // all records are now written using the current settings
+        self.encryption.write()?.retired.clear();
+
+        if let Some(cache) = &self.cache {
+            cache.purge(None)?;
+        }
+
+        Ok(records.len())
+    }
+}
+
+#[async_trait]
+impl Store for FileStore {
+    async fn list(&self) -> Result<AccountDescriptorList> {
         let mut account_descriptors = Vec::new();
-        for entry in entries {
+        for (pubkey, entry) in self.entries()? {
             let data = fs::read(entry).await?;
-            let account_data_store = AccountDataStore::try_from_slice(&data)?;
+            let account_data_store = self.decode(&pubkey, data)?;
             let account_data = AccountData::from(&account_data_store);
             let descriptor: AccountDescriptor = account_data.into();
             account_descriptors.push(descriptor);
@@ -95,7 +247,7 @@ impl Store for FileStore {
         let filename = self.data_folder.join(pubkey.to_string());
         if filename.exists().await {
             let data = fs::read(&self.data_folder.join(pubkey.to_string())).await?;
-            let account_data_store = AccountDataStore::try_from_slice(&data)?;
+            let account_data_store = self.decode(pubkey, data)?;
             let account_data = AccountData::from(&account_data_store);
 
             // log_trace!("~~~ load data {}",account_data.key);
@@ -134,17 +286,36 @@ impl Store for FileStore {
         data.log_trace()?;
         //trace_hex(&data_vec);
 
+        let _rotation = self.rotation.read().await;
+        let data_vec = self.encode(&reference.key, data_vec)?;
         fs::write(&self.data_folder.join(reference.key.to_string()), data_vec).await?;
+        self.notifier.notify(StoreEvent::stored(reference)?);
         Ok(())
     }
     async fn purge(&self, pubkey: &Pubkey) -> Result<()> {
+        let _rotation = self.rotation.read().await;
         if let Some(cache) = &self.cache {
             cache.purge(Some(pubkey))?;
         }
 
         let filename = self.data_folder.join(pubkey.to_string());
+        // container type is read from the record directly to avoid re-populating the cache
+        let container_type = match fs::read(&filename).await {
+            Ok(data) => self
+                .decode(pubkey, data)
+                .ok()
+                .and_then(|store| AccountData::from(&store).container_type()),
+            Err(_) => None,
+        };
+
         match fs::remove_file(&filename).await {
-            Ok(_) => Ok(()),
+            Ok(_) => {
+                self.notifier.notify(StoreEvent::Purged {
+                    pubkey: *pubkey,
+                    container_type,
+                });
+                Ok(())
+            }
             Err(e) => {
                 log_error!(
                     "unable to remove file '{}': {}",
@@ -155,4 +326,140 @@ impl Store for FileStore {
             }
         }
     }
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+}
+
+#[async_trait]
+impl PersistentStorage for FileStore {
+    async fn load_record(&self, pubkey: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let filename = self.data_folder.join(pubkey.to_string());
+        if filename.exists().await {
+            let data = fs::read(&filename).await?;
+            Ok(Some(self.open(pubkey, data)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn store_record(&self, pubkey: &Pubkey, record: Vec<u8>) -> Result<()> {
+        let _rotation = self.rotation.read().await;
+        let data = self.encode(pubkey, record)?;
+        fs::write(&self.data_folder.join(pubkey.to_string()), data).await?;
+        Ok(())
+    }
+
+    async fn remove_record(&self, pubkey: &Pubkey) -> Result<()> {
+        let _rotation = self.rotation.read().await;
+        let filename = self.data_folder.join(pubkey.to_string());
+        if filename.exists().await {
+            fs::remove_file(&filename).await?;
+        }
+        Ok(())
+    }
+
+    async fn clear_records(&self) -> Result<()> {
+        let _rotation = self.rotation.read().await;
+        for (_, path) in self.entries()? {
+            fs::remove_file(&path).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubkey::generate_random_pubkey;
+    use crate::store::encryption::StoreKey;
+
+    #[async_std::test]
+    async fn filestore_encryption_rotation() -> Result<()> {
+        let folder = std::env::temp_dir().join(format!("kaizen-{}", generate_random_pubkey()));
+        let store = FileStore::try_new_with_folder_and_cache(Some(folder.clone().into()), None)?;
+
+        let pubkey = generate_random_pubkey();
+        let account_data =
+            AccountData::new_allocated_for_program(pubkey, generate_random_pubkey(), 8);
+        store
+            .store(&Arc::new(AccountDataReference::new(account_data)))
+            .await?;
+        // files not named after a pubkey are ignored
+        std::fs::write(folder.join("README"), b"notes")?;
+
+        let old = StoreKey::new([1; 32]);
+        let new = StoreKey::new([2; 32]);
+        assert_eq!(
+            store
+                .rotate_encryption(Some(Encryption::new(old.clone())))
+                .await?,
+            1
+        );
+        let record = std::fs::read(folder.join(pubkey.to_string()))?;
+        assert!(Encryption::is_encrypted(&record));
+
+        store.rotate_encryption(Some(Encryption::new(new))).await?;
+        assert!(store.lookup(&pubkey).await?.is_some());
+
+        // replaced settings are released once a rotation re-writes all records
+        std::fs::write(folder.join(pubkey.to_string()), record)?;
+        assert!(store.lookup(&pubkey).await.is_err());
+
+        // a record left under the replaced key by an interrupted rotation remains
+        // readable and is re-written by a subsequent rotation
+        store
+            .encryption
+            .write()?
+            .retired
+            .push(Some(Encryption::new(old)));
+        assert!(store.lookup(&pubkey).await?.is_some());
+        assert_eq!(store.list().await?.list.len(), 1);
+        store
+            .rotate_encryption(Some(Encryption::new(StoreKey::new([3; 32]))))
+            .await?;
+        assert!(store.encryption.read()?.retired.is_empty());
+        assert!(store.lookup(&pubkey).await?.is_some());
+
+        std::fs::remove_dir_all(&folder)?;
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn filestore_purge_notification() -> Result<()> {
+        let folder = std::env::temp_dir().join(format!("kaizen-{}", generate_random_pubkey()));
+        let cache = Arc::new(Cache::new_with_default_capacity());
+        let store = FileStore::try_new_with_folder_and_cache(
+            Some(folder.clone().into()),
+            Some(cache.clone()),
+        )?;
+
+        let pubkey = generate_random_pubkey();
+        let account_data = AccountData::new_static_with_args(
+            pubkey,
+            generate_random_pubkey(),
+            1_000,
+            &5u32.to_le_bytes(),
+            0,
+        );
+        store
+            .store(&Arc::new(AccountDataReference::new(account_data)))
+            .await?;
+        cache.purge(None)?;
+
+        let (_, receiver) = store.notifier().register_event_channel();
+        store.purge(&pubkey).await?;
+        assert_eq!(
+            receiver.try_recv().ok(),
+            Some(StoreEvent::Purged {
+                pubkey,
+                container_type: Some(5)
+            })
+        );
+        // the purged record is not brought back into the cache
+        assert!(cache.lookup(&pubkey)?.is_none());
+
+        std::fs::remove_dir_all(&folder)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,122 @@
+//!
+//! IndexedDB record storage (browser backend of the [`PersistentCache`](super::PersistentCache))
+//!
+
+use super::PersistentStorage;
+use js_sys::{Promise, Uint8Array};
+use kaizen::error::*;
+use kaizen::result::Result;
+use solana_program::pubkey::Pubkey;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+use workflow_core::workflow_async_trait;
+use workflow_log::log_trace;
+
+const OBJECT_STORE: &str = "accounts";
+const DATABASE_VERSION: u32 = 1;
+
+pub struct IndexedDbStorage {
+    db: IdbDatabase,
+}
+
+// IndexedDB handles are only accessed from the browser thread
+unsafe impl Send for IndexedDbStorage {}
+unsafe impl Sync for IndexedDbStorage {}
+
+/// Resolves the result of an [`IdbRequest`]
+async fn request_result(request: &IdbRequest) -> Result<JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let success_request = request.clone();
+        let onsuccess = Closure::once_into_js(move || {
+            let result = success_request.result().unwrap_or(JsValue::UNDEFINED);
+            resolve.call1(&JsValue::UNDEFINED, &result).ok();
+        });
+        let error_request = request.clone();
+        let onerror = Closure::once_into_js(move || {
+            let error = error_request
+                .error()
+                .ok()
+                .flatten()
+                .map(JsValue::from)
+                .unwrap_or_else(|| JsValue::from_str("IndexedDB request failed"));
+            reject.call1(&JsValue::UNDEFINED, &error).ok();
+        });
+        request.set_onsuccess(Some(onsuccess.unchecked_ref()));
+        request.set_onerror(Some(onerror.unchecked_ref()));
+    });
+    Ok(JsFuture::from(promise).await?)
+}
+
+impl IndexedDbStorage {
+    pub async fn try_new(name: &str) -> Result<IndexedDbStorage> {
+        log_trace!("init IndexedDbStorage '{name}'");
+        let factory = web_sys::window()
+            .ok_or_else(|| error!("IndexedDB: missing window object"))?
+            .indexed_db()?
+            .ok_or_else(|| error!("IndexedDB is not available"))?;
+
+        let open_request = factory.open_with_u32(name, DATABASE_VERSION)?;
+        let upgrade_request = open_request.clone();
+        let onupgradeneeded = Closure::once_into_js(move || {
+            if let Ok(db) = upgrade_request.result() {
+                let db: IdbDatabase = db.unchecked_into();
+                if !db.object_store_names().contains(OBJECT_STORE) {
+                    if let Err(err) = db.create_object_store(OBJECT_STORE) {
+                        log_trace!("IndexedDB: unable to create object store: {err:?}");
+                    }
+                }
+            }
+        });
+        open_request.set_onupgradeneeded(Some(onupgradeneeded.unchecked_ref()));
+
+        let db = request_result(&open_request).await?.unchecked_into();
+        Ok(IndexedDbStorage { db })
+    }
+
+    fn object_store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore> {
+        let transaction = self.db.transaction_with_str_and_mode(OBJECT_STORE, mode)?;
+        Ok(transaction.object_store(OBJECT_STORE)?)
+    }
+}
+
+#[workflow_async_trait]
+impl PersistentStorage for IndexedDbStorage {
+    async fn load_record(&self, pubkey: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let request = self
+            .object_store(IdbTransactionMode::Readonly)?
+            .get(&JsValue::from(pubkey.to_string()))?;
+        let value = request_result(&request).await?;
+        if value.is_undefined() {
+            Ok(None)
+        } else {
+            Ok(Some(Uint8Array::new(&value).to_vec()))
+        }
+    }
+
+    async fn store_record(&self, pubkey: &Pubkey, record: Vec<u8>) -> Result<()> {
+        let request = self
+            .object_store(IdbTransactionMode::Readwrite)?
+            .put_with_key(
+                &Uint8Array::from(record.as_slice()),
+                &JsValue::from(pubkey.to_string()),
+            )?;
+        request_result(&request).await?;
+        Ok(())
+    }
+
+    async fn remove_record(&self, pubkey: &Pubkey) -> Result<()> {
+        let request = self
+            .object_store(IdbTransactionMode::Readwrite)?
+            .delete(&JsValue::from(pubkey.to_string()))?;
+        request_result(&request).await?;
+        Ok(())
+    }
+
+    async fn clear_records(&self) -> Result<()> {
+        let request = self.object_store(IdbTransactionMode::Readwrite)?.clear()?;
+        request_result(&request).await?;
+        Ok(())
+    }
+}
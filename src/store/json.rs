@@ -0,0 +1,207 @@
+//!
+//! Import/export of account sets in the JSON format produced by
+//! `solana account --output json` and consumed by `solana-test-validator --account`
+//! (or `--account-dir`).
+//!
+
+use super::*;
+use crate::accounts::AccountData;
+use cfg_if::cfg_if;
+use kaizen::error::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+const ENCODING_BASE64: &str = "base64";
+
+/// Account payload as found under the `account` property of the JSON record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountJsonData {
+    pub lamports: u64,
+    /// `[ <encoded data>, <encoding> ]`
+    pub data: (String, String),
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub space: Option<u64>,
+}
+
+/// Single account record in the `solana account --output json` format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountJson {
+    pub pubkey: String,
+    pub account: AccountJsonData,
+}
+
+impl AccountJson {
+    pub fn try_from_str(json: &str) -> Result<AccountJson> {
+        serde_json::from_str(json).map_err(|err| error!("unable to parse account json: {err}"))
+    }
+
+    pub fn try_to_string(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| error!("unable to serialize account json: {err}"))
+    }
+
+    pub fn pubkey(&self) -> Result<Pubkey> {
+        Ok(Pubkey::from_str(&self.pubkey)?)
+    }
+}
+
+impl From<&AccountData> for AccountJson {
+    fn from(account_data: &AccountData) -> Self {
+        let data = account_data.data();
+        AccountJson {
+            pubkey: account_data.key.to_string(),
+            account: AccountJsonData {
+                lamports: account_data.lamports,
+                data: (base64::encode(data), ENCODING_BASE64.to_string()),
+                owner: account_data.owner.to_string(),
+                executable: account_data.executable,
+                rent_epoch: account_data.rent_epoch,
+                space: Some(data.len() as u64),
+            },
+        }
+    }
+}
+
+impl TryFrom<&AccountJson> for AccountData {
+    type Error = Error;
+    fn try_from(json: &AccountJson) -> Result<AccountData> {
+        let key = json.pubkey()?;
+        let owner = Pubkey::from_str(&json.account.owner)?;
+        let (encoded, encoding) = &json.account.data;
+        let data = match encoding.as_str() {
+            ENCODING_BASE64 => base64::decode(encoded)
+                .map_err(|err| error!("account {key}: invalid base64 data: {err}"))?,
+            _ => {
+                return Err(error!(
+                    "account {key}: unsupported data encoding '{encoding}' (expecting '{ENCODING_BASE64}')"
+                ))
+            }
+        };
+
+        let mut account_data = AccountData::new_static_with_args(
+            key,
+            owner,
+            json.account.lamports,
+            &data,
+            json.account.rent_epoch,
+        );
+        account_data.executable = json.account.executable;
+
+        Ok(account_data)
+    }
+}
+
+/// Imports a set of account records into the supplied store, returning the number of imported accounts.
+pub async fn import_accounts(store: &dyn Store, accounts: &[AccountJson]) -> Result<usize> {
+    for json in accounts.iter() {
+        let account_data = AccountData::try_from(json)?;
+        store
+            .store(&Arc::new(AccountDataReference::new(account_data)))
+            .await?;
+    }
+    Ok(accounts.len())
+}
+
+/// Exports all accounts held by the supplied store.
+pub async fn export_accounts(store: &dyn Store) -> Result<Vec<AccountJson>> {
+    let mut accounts = Vec::new();
+    for descriptor in store.list().await?.list.iter() {
+        if let Some(reference) = store.lookup(&descriptor.key).await? {
+            accounts.push(AccountJson::from(&*reference.account_data.lock()?));
+        }
+    }
+    Ok(accounts)
+}
+
+cfg_if! {
+    if #[cfg(not(target_arch = "wasm32"))] {
+        use async_std::fs;
+        use async_std::path::Path;
+        use futures::stream::StreamExt;
+
+        /// Imports all `*.json` account files from the given folder (the layout accepted
+        /// by `solana-test-validator --account-dir`). Returns the number of imported accounts.
+        pub async fn import_from_folder(store: &dyn Store, folder: &Path) -> Result<usize> {
+            let mut files = Vec::new();
+            let mut entries = fs::read_dir(folder).await?;
+            while let Some(entry) = entries.next().await {
+                let path = entry?.path();
+                if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+                    files.push(path);
+                }
+            }
+            files.sort();
+
+            let mut accounts = Vec::new();
+            for file in files {
+                let json = fs::read_to_string(&file).await?;
+                accounts.push(AccountJson::try_from_str(&json)?);
+            }
+
+            import_accounts(store, &accounts).await
+        }
+
+        /// Exports all store accounts into the given folder as `<pubkey>.json` files,
+        /// suitable for `solana-test-validator --account-dir`. Returns the number of exported accounts.
+        pub async fn export_to_folder(store: &dyn Store, folder: &Path) -> Result<usize> {
+            fs::create_dir_all(folder).await?;
+            let accounts = export_accounts(store).await?;
+            for account in accounts.iter() {
+                let filename = folder.join(format!("{}.json", account.pubkey));
+                fs::write(&filename, account.try_to_string()?).await?;
+            }
+            Ok(accounts.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubkey::generate_random_pubkey;
+
+    #[test]
+    fn account_json_roundtrip() -> Result<()> {
+        let key = generate_random_pubkey();
+        let owner = generate_random_pubkey();
+        let account_data =
+            AccountData::new_static_with_args(key, owner, 1_000_000, &[1, 2, 3, 4, 5, 6], 361);
+
+        let text = AccountJson::from(&account_data).try_to_string()?;
+        let json = AccountJson::try_from_str(&text)?;
+        assert_eq!(json.account.data.1, ENCODING_BASE64);
+        assert_eq!(json.account.space, Some(6));
+
+        let imported = AccountData::try_from(&json)?;
+        assert_eq!(imported.key, key);
+        assert_eq!(imported.owner, owner);
+        assert_eq!(imported.lamports, 1_000_000);
+        assert_eq!(imported.rent_epoch, 361);
+        assert_eq!(imported.data(), &[1, 2, 3, 4, 5, 6]);
+        Ok(())
+    }
+
+    #[test]
+    fn account_json_validator_format() -> Result<()> {
+        let text = r#"{
+            "pubkey": "11111111111111111111111111111111",
+            "account": {
+                "lamports": 1,
+                "data": ["AQID", "base64"],
+                "owner": "NativeLoader1111111111111111111111111111111",
+                "executable": true,
+                "rentEpoch": 18446744073709551615,
+                "space": 3
+            }
+        }"#;
+        let account_data = AccountData::try_from(&AccountJson::try_from_str(text)?)?;
+        assert!(account_data.executable);
+        assert_eq!(account_data.rent_epoch, u64::MAX);
+        assert_eq!(account_data.data(), &[1, 2, 3]);
+        Ok(())
+    }
+}
@@ -12,6 +12,7 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct MemoryStore {
     map: Arc<RwLock<AHashMap<Pubkey, Arc<AccountDataReference>>>>,
+    notifier: Notifier,
 }
 
 static mut STORE: Option<MemoryStore> = None;
@@ -24,6 +25,7 @@ impl MemoryStore {
         }
         let store = MemoryStore {
             map: Arc::new(RwLock::new(AHashMap::default())),
+            notifier: Notifier::new(),
         };
         unsafe {
             STORE = Some(store.clone());
@@ -42,6 +44,7 @@ impl MemoryStore {
     pub fn new_local() -> Result<MemoryStore> {
         let store = MemoryStore {
             map: Arc::new(RwLock::new(AHashMap::default())),
+            notifier: Notifier::new(),
         };
 
         Ok(store)
@@ -69,10 +72,16 @@ impl Store for MemoryStore {
             .write()
             .await
             .insert(*reference.key, reference.clone());
+        self.notifier.notify(StoreEvent::stored(reference)?);
         Ok(())
     }
     async fn purge(&self, pubkey: &Pubkey) -> Result<()> {
-        self.map.write().await.remove(pubkey);
+        let reference = self.map.write().await.remove(pubkey);
+        self.notifier
+            .notify(StoreEvent::purged(pubkey, reference.as_ref())?);
         Ok(())
     }
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
 }
@@ -2,26 +2,231 @@
 //! Account stores used by the Emulator and client-side application account caching mechanisms.
 //!
 
+use ahash::AHashMap;
 use async_trait::async_trait;
+use borsh::{BorshDeserialize, BorshSerialize};
 use cfg_if::cfg_if;
 use kaizen::accounts::{AccountDataReference, AccountDescriptorList};
 use kaizen::result::Result;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use workflow_core::channel::{unbounded, Receiver, Sender};
+use workflow_core::id::Id;
 
+pub mod json;
 mod memorystore;
 pub use memorystore::MemoryStore;
+mod persistent;
+pub use persistent::*;
 cfg_if! {
-    if #[cfg(not(target_arch = "wasm32"))] {
+    if #[cfg(target_arch = "wasm32")] {
+        mod indexeddb;
+        pub use indexeddb::IndexedDbStorage;
+    } else {
+        pub mod encryption;
         mod filestore;
         pub use filestore::FileStore;
     }
 }
 
+/// Account change notification posted by a [`Store`]
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum StoreEvent {
+    Stored {
+        pubkey: Pubkey,
+        container_type: Option<u32>,
+    },
+    Purged {
+        pubkey: Pubkey,
+        container_type: Option<u32>,
+    },
+}
+
+impl StoreEvent {
+    pub fn stored(reference: &Arc<AccountDataReference>) -> Result<StoreEvent> {
+        Ok(StoreEvent::Stored {
+            pubkey: *reference.key,
+            container_type: reference.account_data.lock()?.container_type(),
+        })
+    }
+
+    pub fn purged(
+        pubkey: &Pubkey,
+        reference: Option<&Arc<AccountDataReference>>,
+    ) -> Result<StoreEvent> {
+        let container_type = match reference {
+            Some(reference) => reference.account_data.lock()?.container_type(),
+            None => None,
+        };
+        Ok(StoreEvent::Purged {
+            pubkey: *pubkey,
+            container_type,
+        })
+    }
+
+    pub fn pubkey(&self) -> &Pubkey {
+        match self {
+            StoreEvent::Stored { pubkey, .. } => pubkey,
+            StoreEvent::Purged { pubkey, .. } => pubkey,
+        }
+    }
+
+    pub fn container_type(&self) -> Option<u32> {
+        match self {
+            StoreEvent::Stored { container_type, .. } => *container_type,
+            StoreEvent::Purged { container_type, .. } => *container_type,
+        }
+    }
+}
+
+/// Relays [`StoreEvent`] notifications to registered event channels.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    channels: Arc<Mutex<AHashMap<Id, Sender<StoreEvent>>>>,
+}
+
+impl Notifier {
+    pub fn new() -> Notifier {
+        Notifier::default()
+    }
+
+    pub fn register_event_channel(&self) -> (Id, Receiver<StoreEvent>) {
+        let (sender, receiver) = unbounded();
+        let id = Id::new();
+        self.channels.lock().unwrap().insert(id, sender);
+        (id, receiver)
+    }
+
+    pub fn unregister_event_channel(&self, id: Id) {
+        self.channels.lock().unwrap().remove(&id);
+    }
+
+    pub fn notify(&self, event: StoreEvent) {
+        // channels whose receivers have been dropped are discarded
+        self.channels
+            .lock()
+            .unwrap()
+            .retain(|_, sender| sender.try_send(event.clone()).is_ok());
+    }
+}
+
 #[async_trait]
 pub trait Store: Sync + Send {
     async fn list(&self) -> Result<AccountDescriptorList>;
     async fn lookup(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>>;
     async fn store(&self, reference: &Arc<AccountDataReference>) -> Result<()>;
     async fn purge(&self, pubkey: &Pubkey) -> Result<()>;
+    /// Notifier posting [`StoreEvent`] for each `store()` and `purge()` operation.
+    fn notifier(&self) -> &Notifier;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::AccountData;
+    use crate::emulator::interface::EmulatorInterface;
+    use crate::emulator::Simulator;
+    use crate::pubkey::generate_random_pubkey;
+
+    fn reference_with_container_type(container_type: u32) -> Arc<AccountDataReference> {
+        let account_data = AccountData::new_static_with_args(
+            generate_random_pubkey(),
+            generate_random_pubkey(),
+            1_000,
+            &container_type.to_le_bytes(),
+            0,
+        );
+        Arc::new(AccountDataReference::new(account_data))
+    }
+
+    #[test]
+    fn store_notifier_channels() -> Result<()> {
+        let notifier = Notifier::new();
+        let (first, first_receiver) = notifier.register_event_channel();
+        let (_, second_receiver) = notifier.register_event_channel();
+
+        let reference = reference_with_container_type(7);
+        let event = StoreEvent::stored(&reference)?;
+        assert_eq!(event.pubkey(), &*reference.key);
+        assert_eq!(event.container_type(), Some(7));
+
+        notifier.notify(event.clone());
+        assert_eq!(first_receiver.try_recv().ok(), Some(event.clone()));
+        assert_eq!(second_receiver.try_recv().ok(), Some(event.clone()));
+
+        notifier.unregister_event_channel(first);
+        notifier.notify(StoreEvent::purged(&reference.key, None)?);
+        assert!(first_receiver.try_recv().is_err());
+        assert_eq!(
+            second_receiver.try_recv().ok(),
+            Some(StoreEvent::Purged {
+                pubkey: *reference.key,
+                container_type: None
+            })
+        );
+
+        // channels with dropped receivers are discarded on the next notification
+        drop(second_receiver);
+        notifier.notify(event);
+        assert!(notifier.channels.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn memory_store_notifications() -> Result<()> {
+        let store = MemoryStore::new_local()?;
+        let (_, receiver) = store.notifier().register_event_channel();
+
+        let reference = reference_with_container_type(3);
+        store.store(&reference).await?;
+        store.purge(&reference.key).await?;
+        // purging an unknown account carries no container type
+        let unknown = generate_random_pubkey();
+        store.purge(&unknown).await?;
+
+        assert_eq!(
+            receiver.try_recv().ok(),
+            Some(StoreEvent::Stored {
+                pubkey: *reference.key,
+                container_type: Some(3)
+            })
+        );
+        assert_eq!(
+            receiver.try_recv().ok(),
+            Some(StoreEvent::Purged {
+                pubkey: *reference.key,
+                container_type: Some(3)
+            })
+        );
+        assert_eq!(
+            receiver.try_recv().ok(),
+            Some(StoreEvent::Purged {
+                pubkey: unknown,
+                container_type: None
+            })
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn simulator_notifications() -> Result<()> {
+        let program_id = generate_random_pubkey();
+        let simulator = Simulator::try_new_for_testing()?
+            .with_mock_accounts(program_id, None)
+            .await?;
+        let (_, receiver) = simulator.notifier().register_event_channel();
+
+        let key = generate_random_pubkey();
+        simulator.fund(&key, &program_id, 1_000).await?;
+
+        let mut stored = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            assert!(matches!(event, StoreEvent::Stored { .. }));
+            stored.push(*event.pubkey());
+        }
+        assert!(stored.contains(&Pubkey::default()));
+        assert!(stored.contains(&key));
+        Ok(())
+    }
 }
@@ -0,0 +1,236 @@
+//!
+//! Persistent client-side account cache tier (offline-first operation).
+//!
+//! Account data fetched from the network is written to a [`PersistentStorage`] backend
+//! ([`FileStore`](super::FileStore) on native, `IndexedDbStorage` in the browser), allowing
+//! applications to start from the last known state and reconcile in the background.
+//! Records carry a format version, an application-defined schema version and a checksum
+//! of the account data; records failing any of these checks are discarded. Restored data
+//! is marked via [`AccountDataReference::is_restored`] until refreshed from the network.
+//!
+//! Record layout: `magic (4) | header (borsh) | account data store (borsh)`
+//!
+
+use crate::accounts::{
+    AccountData, AccountDataReference, AccountDataStore, Commitment, FetchStamp,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use cfg_if::cfg_if;
+use kaizen::error::*;
+use kaizen::result::Result;
+use kaizen::time::Instant;
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+use std::sync::Arc;
+use workflow_core::workflow_async_trait;
+use workflow_log::log_trace;
+
+const MAGIC: [u8; 4] = *b"KZC\x01";
+
+/// Version of the persistent record format
+pub const PERSISTENT_CACHE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct PersistentCacheConfig {
+    /// Cache name (folder name on native, IndexedDB database name in the browser)
+    pub name: String,
+    /// Application-defined schema version; records written under
+    /// a different version are discarded when loaded
+    pub schema_version: u32,
+}
+
+impl PersistentCacheConfig {
+    pub fn new(name: &str, schema_version: u32) -> PersistentCacheConfig {
+        PersistentCacheConfig {
+            name: name.to_string(),
+            schema_version,
+        }
+    }
+}
+
+/// Raw record storage backing the [`PersistentCache`]
+#[workflow_async_trait]
+pub trait PersistentStorage: Sync + Send {
+    async fn load_record(&self, pubkey: &Pubkey) -> Result<Option<Vec<u8>>>;
+    async fn store_record(&self, pubkey: &Pubkey, record: Vec<u8>) -> Result<()>;
+    async fn remove_record(&self, pubkey: &Pubkey) -> Result<()>;
+    async fn clear_records(&self) -> Result<()>;
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct RecordHeader {
+    format_version: u32,
+    schema_version: u32,
+    slot: Option<u64>,
+    commitment: Option<Commitment>,
+    timestamp: Instant,
+    checksum: [u8; 32],
+}
+
+fn checksum(pubkey: &Pubkey, data: &[u8]) -> [u8; 32] {
+    hashv(&[b"kaizen-persistent-cache", pubkey.as_ref(), data]).to_bytes()
+}
+
+fn encode(schema_version: u32, reference: &AccountDataReference) -> Result<Vec<u8>> {
+    let data = AccountDataStore::from(&*reference.account_data.lock()?).try_to_vec()?;
+    let (slot, commitment, timestamp) = match reference.fetch_stamp {
+        Some(fetch_stamp) => (
+            fetch_stamp.slot,
+            Some(fetch_stamp.commitment),
+            fetch_stamp.timestamp,
+        ),
+        None => (None, None, Instant::now()?),
+    };
+    let header = RecordHeader {
+        format_version: PERSISTENT_CACHE_FORMAT_VERSION,
+        schema_version,
+        slot,
+        commitment,
+        timestamp,
+        checksum: checksum(&reference.key, &data),
+    };
+
+    let mut record = MAGIC.to_vec();
+    record.extend_from_slice(&header.try_to_vec()?);
+    record.extend_from_slice(&data);
+    Ok(record)
+}
+
+fn decode(schema_version: u32, pubkey: &Pubkey, record: &[u8]) -> Result<AccountDataReference> {
+    if record.len() < MAGIC.len() || record[0..MAGIC.len()] != MAGIC {
+        return Err(error_code!(ErrorCode::StoreRecordIntegrity)
+            .with_account(pubkey)
+            .with_message("invalid record signature"));
+    }
+
+    let mut data = &record[MAGIC.len()..];
+    let header = RecordHeader::deserialize(&mut data)?;
+    if header.format_version != PERSISTENT_CACHE_FORMAT_VERSION
+        || header.schema_version != schema_version
+    {
+        return Err(error_code!(ErrorCode::StoreRecordVersionMismatch)
+            .with_account(pubkey)
+            .with_message(&format!(
+                "record version {}.{} (expected {}.{})",
+                header.format_version,
+                header.schema_version,
+                PERSISTENT_CACHE_FORMAT_VERSION,
+                schema_version
+            )));
+    }
+    if header.checksum != checksum(pubkey, data) {
+        return Err(error_code!(ErrorCode::StoreRecordIntegrity)
+            .with_account(pubkey)
+            .with_message("checksum mismatch"));
+    }
+
+    let account_data_store = AccountDataStore::try_from_slice(data)?;
+    if account_data_store.key != *pubkey {
+        return Err(error_code!(ErrorCode::StoreRecordIntegrity)
+            .with_account(pubkey)
+            .with_message("record key mismatch"));
+    }
+
+    let mut reference =
+        AccountDataReference::new(AccountData::from(&account_data_store)).with_restored(true);
+    if let Some(commitment) = header.commitment {
+        reference = reference.with_fetch_stamp(FetchStamp {
+            slot: header.slot,
+            commitment,
+            timestamp: header.timestamp,
+        });
+    }
+    Ok(reference)
+}
+
+/// Persistent tier of the client-side account cache.
+pub struct PersistentCache {
+    storage: Arc<dyn PersistentStorage>,
+    config: PersistentCacheConfig,
+}
+
+impl PersistentCache {
+    pub fn new(storage: Arc<dyn PersistentStorage>, config: PersistentCacheConfig) -> Self {
+        PersistentCache { storage, config }
+    }
+
+    /// Creates the cache using the default storage backend of the platform.
+    pub async fn try_new(config: PersistentCacheConfig) -> Result<PersistentCache> {
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let storage = super::IndexedDbStorage::try_new(&config.name).await?;
+                Ok(Self::new(Arc::new(storage), config))
+            } else {
+                let folder = super::FileStore::default_cache_folder().join(&config.name);
+                let storage = super::FileStore::try_new_with_folder_and_cache(Some(folder), None)?;
+                Ok(Self::new(Arc::new(storage), config))
+            }
+        }
+    }
+
+    pub fn config(&self) -> &PersistentCacheConfig {
+        &self.config
+    }
+
+    /// Loads account data restored from the persistent storage. Records that are
+    /// outdated or fail integrity checks are removed and reported as missing.
+    pub async fn load(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
+        let record = match self.storage.load_record(pubkey).await? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        match decode(self.config.schema_version, pubkey, &record) {
+            Ok(reference) => Ok(Some(Arc::new(reference))),
+            Err(err) => {
+                log_trace!("discarding persistent cache record {pubkey}: {err}");
+                self.storage.remove_record(pubkey).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn store(&self, reference: &Arc<AccountDataReference>) -> Result<()> {
+        let record = encode(self.config.schema_version, reference)?;
+        self.storage.store_record(&reference.key, record).await
+    }
+
+    pub async fn remove(&self, pubkey: &Pubkey) -> Result<()> {
+        self.storage.remove_record(pubkey).await
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        self.storage.clear_records().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubkey::generate_random_pubkey;
+
+    #[test]
+    fn persistent_cache_record_checks() -> Result<()> {
+        let key = generate_random_pubkey();
+        let owner = generate_random_pubkey();
+        let account_data = AccountData::new_static_with_args(key, owner, 1_000, &[1, 2, 3, 4], 0);
+        let fetch_stamp = FetchStamp::try_new(Some(42), Commitment::Confirmed)?;
+        let reference = AccountDataReference::new(account_data).with_fetch_stamp(fetch_stamp);
+
+        let record = encode(3, &reference)?;
+        let restored = decode(3, &key, &record)?;
+        assert!(restored.is_restored());
+        assert_eq!(restored.fetch_stamp, Some(fetch_stamp));
+        assert_eq!(restored.account_data.lock()?.data(), &[1, 2, 3, 4]);
+
+        // schema version change invalidates records
+        assert!(decode(4, &key, &record).is_err());
+        // records are bound to their pubkey
+        assert!(decode(3, &generate_random_pubkey(), &record).is_err());
+        // corrupted data fails the checksum
+        let mut corrupted = record.clone();
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        assert!(decode(3, &key, &corrupted).is_err());
+        Ok(())
+    }
+}
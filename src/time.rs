@@ -46,8 +46,10 @@ impl Instant {
         }
     }
 
+    /// Time elapsed from this instant until `timestamp` (zero if this
+    /// instant is ahead of `timestamp`, e.g. due to clock skew)
     pub fn elapsed_since(&self, timestamp: &Instant) -> Duration {
-        Duration(timestamp.0 - self.0)
+        Duration(timestamp.0.saturating_sub(self.0))
     }
 
     pub fn elapsed(&self) -> Result<Duration> {
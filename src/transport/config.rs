@@ -2,6 +2,8 @@
 //! Transport interface configuration
 //!
 
+use kaizen::cache::CacheConfig;
+use kaizen::store::PersistentCacheConfig;
 use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
 use std::time::Duration;
@@ -25,6 +27,9 @@ pub struct TransportConfig {
     pub timeout: Duration,
     pub confirm_transaction_initial_timeout: Duration,
     pub retries: usize,
+    pub cache: CacheConfig,
+    /// Optional persistent cache tier restoring account data across application restarts
+    pub persistent_cache: Option<PersistentCacheConfig>,
 }
 
 impl TransportConfig {
@@ -39,9 +44,21 @@ impl TransportConfig {
             timeout,
             confirm_transaction_initial_timeout,
             retries,
+            cache: CacheConfig::default(),
+            persistent_cache: None,
         }
     }
 
+    pub fn with_cache_config(mut self, cache: CacheConfig) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_persistent_cache(mut self, persistent_cache: PersistentCacheConfig) -> Self {
+        self.persistent_cache = Some(persistent_cache);
+        self
+    }
+
     pub fn default_with_root(root: Pubkey) -> TransportConfig {
         TransportConfig {
             root,
@@ -57,6 +74,8 @@ impl Default for TransportConfig {
             timeout: Duration::from_secs(60u64),
             confirm_transaction_initial_timeout: Duration::from_secs(5u64),
             retries: 2,
+            cache: CacheConfig::default(),
+            persistent_cache: None,
         }
     }
 }
@@ -15,15 +15,18 @@ use crate::transport::TransportConfig;
 use crate::transport::TransportMode;
 use crate::transport::{reflector, Reflector};
 use crate::wallet::*;
+use ahash::HashSet;
 use async_std::path::Path;
 use async_std::sync::RwLock;
 use async_trait::async_trait;
-use kaizen::cache::Cache;
+use kaizen::accounts::{Commitment, FetchStamp};
+use kaizen::cache::{Cache, CacheLookup, CacheStats};
+use kaizen::store::PersistentCache;
 use solana_program::account_info::IntoAccountInfo;
 use solana_program::instruction::Instruction;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::account::Account;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 use std::time::SystemTime;
 use std::*;
@@ -49,8 +52,12 @@ pub struct Transport {
     pub wallet: Arc<dyn foreign::WalletInterface>,
     pub config: Arc<RwLock<TransportConfig>>,
     pub cache: Arc<Cache>,
+    pub persistent_cache: Option<Arc<PersistentCache>>,
     pub queue: Arc<TransactionQueue>,
     pub lookup_handler: LookupHandler<Pubkey, Arc<AccountDataReference>>,
+    /// Accounts currently being refreshed in the background
+    revalidations: Arc<Mutex<HashSet<Pubkey>>>,
+    this: Weak<Transport>,
     pub custom_authority: Arc<Mutex<Option<Pubkey>>>,
     pub reflector: Reflector,
 }
@@ -147,25 +154,36 @@ impl Transport {
 
         // TODO implement transaction queue support
         let queue = Arc::new(TransactionQueue::new());
-        let cache = Arc::new(Cache::new_with_default_capacity());
+        let cache = Arc::new(Cache::new_with_config(config.cache.clone()));
+        let persistent_cache = match &config.persistent_cache {
+            Some(persistent_cache_config) => Some(Arc::new(
+                PersistentCache::try_new(persistent_cache_config.clone()).await?,
+            )),
+            None => None,
+        };
+        if let Some(emulator) = &emulator {
+            cache.bind_store_notifier(emulator.notifier());
+        }
         let config = Arc::new(RwLock::new(config));
         let lookup_handler = LookupHandler::new();
         let reflector = Reflector::new();
 
-        let transport = Transport {
+        let transport = Arc::new_cyclic(|this| Transport {
             mode,
             emulator,
             wallet,
             rpc_client,
             config,
             cache,
+            persistent_cache,
             queue,
             lookup_handler,
+            revalidations: Arc::new(Mutex::new(HashSet::default())),
+            this: this.clone(),
             reflector,
             custom_authority: Arc::new(Mutex::new(None)),
-        };
+        });
 
-        let transport = Arc::new(transport);
         unsafe {
             TRANSPORT = Some(transport.clone());
         }
@@ -215,6 +233,71 @@ impl Transport {
         self.emulator.as_ref()
     }
 
+    pub fn cache_stats(&self) -> Result<CacheStats> {
+        self.cache.stats()
+    }
+
+    /// Posts current cache statistics to the [`Reflector`] event channels.
+    /// Statistics are not posted automatically; applications interested in
+    /// cache diagnostics should call this periodically.
+    pub fn reflect_cache_stats(&self) {
+        match self.cache.stats() {
+            Ok(stats) => self.reflector.reflect(reflector::Event::CacheStats(stats)),
+            Err(err) => log_error!("Unable to obtain cache stats: {}", err),
+        }
+    }
+
+    /// Restores account data from the persistent cache tier into the in-memory cache.
+    async fn lookup_persistent(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<Option<Arc<AccountDataReference>>> {
+        match &self.persistent_cache {
+            Some(persistent_cache) => match persistent_cache.load(pubkey).await? {
+                Some(reference) => {
+                    self.cache.store(&reference)?;
+                    Ok(Some(reference))
+                }
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Mirrors the result of a network lookup to the persistent cache tier.
+    async fn persist(&self, pubkey: &Pubkey, reference: Option<&Arc<AccountDataReference>>) {
+        if let Some(persistent_cache) = &self.persistent_cache {
+            let result = match reference {
+                Some(reference) => persistent_cache.store(reference).await,
+                None => persistent_cache.remove(pubkey).await,
+            };
+            if let Err(err) = result {
+                log_error!("Unable to update persistent cache for {}: {}", pubkey, err);
+            }
+        }
+    }
+
+    /// Refreshes a stale cache entry in the background. Requests for an
+    /// account that is already being refreshed are ignored.
+    fn revalidate(&self, pubkey: &Pubkey) {
+        let transport = match self.this.upgrade() {
+            Some(transport) => transport,
+            None => return,
+        };
+        if !self.revalidations.lock().unwrap().insert(*pubkey) {
+            return;
+        }
+        let pubkey = *pubkey;
+        workflow_core::task::spawn(async move {
+            if let Err(err) =
+                <Transport as super::Interface>::lookup_remote(&transport, &pubkey).await
+            {
+                log_error!("Unable to revalidate account {}: {}", pubkey, err);
+            }
+            transport.revalidations.lock().unwrap().remove(&pubkey);
+        });
+    }
+
     pub fn simulator(&self) -> Arc<Simulator> {
         let simulator = self
             .emulator
@@ -334,8 +417,6 @@ impl Transport {
         &self,
         pubkey: &Pubkey,
     ) -> Result<Option<Arc<AccountDataReference>>> {
-        self.cache.purge(Some(pubkey))?;
-
         match self.mode {
             TransportMode::Inproc | TransportMode::Emulator => {
                 let reference = self
@@ -348,7 +429,10 @@ impl Transport {
                         self.cache.store(&reference)?;
                         Ok(Some(reference))
                     }
-                    None => Ok(None),
+                    None => {
+                        self.cache.purge(Some(pubkey))?;
+                        Ok(None)
+                    }
                 }
             }
             TransportMode::Validator => {
@@ -359,11 +443,20 @@ impl Transport {
                     Some(mut account) => {
                         let account_info = (pubkey, &mut account).into_account_info();
                         let account_data = AccountData::clone_from_account_info(&account_info);
-                        let reference = Arc::new(AccountDataReference::new(account_data));
+                        let fetch_stamp = FetchStamp::try_new(
+                            Some(response.context.slot),
+                            Commitment::Processed,
+                        )?;
+                        let reference = Arc::new(
+                            AccountDataReference::new(account_data).with_fetch_stamp(fetch_stamp),
+                        );
                         self.cache.store(&reference)?;
                         Ok(Some(reference))
                     }
-                    None => Ok(None),
+                    None => {
+                        self.cache.purge(Some(pubkey))?;
+                        Ok(None)
+                    }
                 }
             }
         }
@@ -462,15 +555,27 @@ impl super::Interface for Transport {
     }
 
     async fn lookup(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
-        let account_data = self.lookup_local(pubkey).await?;
-        match account_data {
-            Some(account_data) => Ok(Some(account_data)),
-            None => Ok(self.lookup_remote(pubkey).await?),
+        match self.cache.lookup_with_freshness(pubkey)? {
+            CacheLookup::Fresh(reference) => Ok(Some(reference)),
+            CacheLookup::Stale(reference) => {
+                self.revalidate(pubkey);
+                Ok(Some(reference))
+            }
+            CacheLookup::Miss => match self.lookup_persistent(pubkey).await? {
+                Some(reference) => {
+                    self.revalidate(pubkey);
+                    Ok(Some(reference))
+                }
+                None => Ok(self.lookup_remote(pubkey).await?),
+            },
         }
     }
 
     async fn lookup_local(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
-        Ok(self.cache.lookup(pubkey)?)
+        match self.cache.lookup(pubkey)? {
+            Some(reference) => Ok(Some(reference)),
+            None => self.lookup_persistent(pubkey).await,
+        }
     }
 
     async fn lookup_remote(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
@@ -482,6 +587,9 @@ impl super::Interface for Transport {
                     .reflect(reflector::Event::PendingLookups(lookup_handler.pending()));
 
                 let response = self.lookup_remote_impl(pubkey).await;
+                if let Ok(reference) = &response {
+                    self.persist(pubkey, reference.as_ref()).await;
+                }
                 lookup_handler.complete(pubkey, response).await;
                 receiver.recv().await?
             }
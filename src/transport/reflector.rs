@@ -1,6 +1,7 @@
 //!
 //! Client-side Transport activity tracker (for transactions, wallet and emulator updates).
 //!
+use crate::cache::CacheStats;
 use crate::error::Error;
 use crate::result::Result;
 use ahash::HashMap;
@@ -25,6 +26,7 @@ pub enum Event {
     WalletRefresh(String, Pubkey),
     WalletBalance(String, Pubkey, u64),
     EmulatorLogs(Vec<String>),
+    CacheStats(CacheStats),
     Halt,
 }
 
@@ -60,6 +62,23 @@ impl TryFrom<&Event> for JsValue {
                     .collect::<Vec<JsValue>>();
                 object.set_vec("logs", &logs)?;
             }
+            Event::CacheStats(stats) => {
+                object.set("event", &"cache-stats".into())?;
+                object.set("hits", &JsValue::from_f64(stats.hits as f64))?;
+                object.set("staleHits", &JsValue::from_f64(stats.stale_hits as f64))?;
+                object.set("misses", &JsValue::from_f64(stats.misses as f64))?;
+                object.set("expirations", &JsValue::from_f64(stats.expirations as f64))?;
+                object.set("inserts", &JsValue::from_f64(stats.inserts as f64))?;
+                object.set(
+                    "invalidations",
+                    &JsValue::from_f64(stats.invalidations as f64),
+                )?;
+                object.set("entries", &JsValue::from_f64(stats.entries as f64))?;
+                object.set(
+                    "weightedSize",
+                    &JsValue::from_f64(stats.weighted_size as f64),
+                )?;
+            }
             Event::Halt => {
                 object.set("event", &"halt".into())?;
             }
@@ -18,11 +18,14 @@ use crate::utils::pubkey_from_slice;
 use crate::wallet::*;
 use workflow_core::id::Id;
 // use crate::wasm::*;
+use ahash::HashSet;
 use async_std::sync::RwLock;
 use async_trait::async_trait;
 use js_sys::*;
 use kaizen::{
-    cache::Cache,
+    accounts::{Commitment, FetchStamp},
+    cache::{Cache, CacheLookup, CacheStats},
+    store::PersistentCache,
     wasm::{solana, workflow},
 };
 // use rand::*;
@@ -33,7 +36,7 @@ use solana_program::pubkey::Pubkey;
 use solana_sdk::account::Account;
 //use solana_rpc_client_api::RpcProgramAccountsConfig;
 use std::convert::From;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 use std::*;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::future_to_promise;
@@ -193,10 +196,14 @@ pub struct Transport {
     pub wallet: Arc<dyn foreign::WalletInterface>,
     pub queue: Arc<TransactionQueue>,
     cache: Arc<Cache>,
+    persistent_cache: Option<Arc<PersistentCache>>,
     pub config: Arc<RwLock<TransportConfig>>,
     pub custom_authority: Arc<Mutex<Option<Pubkey>>>,
     connection: Option<Connection>,
     pub lookup_handler: LookupHandler<Pubkey, Arc<AccountDataReference>>,
+    /// Accounts currently being refreshed in the background
+    revalidations: Arc<Mutex<HashSet<Pubkey>>>,
+    this: Weak<Transport>,
     pub reflector: Reflector,
 }
 
@@ -455,12 +462,21 @@ impl Transport {
         let wallet = Arc::new(foreign::Wallet::try_new()?);
 
         let queue = Arc::new(TransactionQueue::new());
-        let cache = Arc::new(Cache::new_with_default_capacity());
+        let cache = Arc::new(Cache::new_with_config(config.cache.clone()));
+        let persistent_cache = match &config.persistent_cache {
+            Some(persistent_cache_config) => Some(Arc::new(
+                PersistentCache::try_new(persistent_cache_config.clone()).await?,
+            )),
+            None => None,
+        };
+        if let Some(emulator) = &emulator {
+            cache.bind_store_notifier(emulator.notifier());
+        }
         let config = Arc::new(RwLock::new(config));
         let lookup_handler = LookupHandler::new();
         let reflector = Reflector::new();
 
-        let transport = Transport {
+        let transport = Arc::new_cyclic(|this| Transport {
             mode,
             emulator,
             config,
@@ -468,12 +484,14 @@ impl Transport {
             wallet,
             queue,
             cache,
+            persistent_cache,
             lookup_handler,
+            revalidations: Arc::new(Mutex::new(HashSet::default())),
+            this: this.clone(),
             reflector,
             custom_authority: Arc::new(Mutex::new(None)),
-        };
+        });
 
-        let transport = Arc::new(transport);
         unsafe {
             TRANSPORT = Some(transport.clone());
         }
@@ -496,12 +514,75 @@ impl Transport {
         self.emulator.as_ref()
     }
 
-    pub async fn lookup_remote_impl(
+    pub fn cache_stats(&self) -> Result<CacheStats> {
+        self.cache.stats()
+    }
+
+    /// Posts current cache statistics to the [`Reflector`] event channels.
+    /// Statistics are not posted automatically; applications interested in
+    /// cache diagnostics should call this periodically.
+    pub fn reflect_cache_stats(&self) {
+        match self.cache.stats() {
+            Ok(stats) => self.reflector.reflect(reflector::Event::CacheStats(stats)),
+            Err(err) => log_error!("Unable to obtain cache stats: {}", err),
+        }
+    }
+
+    /// Restores account data from the persistent cache tier into the in-memory cache.
+    async fn lookup_persistent(
         &self,
         pubkey: &Pubkey,
     ) -> Result<Option<Arc<AccountDataReference>>> {
-        self.cache.purge(Some(pubkey))?;
+        match &self.persistent_cache {
+            Some(persistent_cache) => match persistent_cache.load(pubkey).await? {
+                Some(reference) => {
+                    self.cache.store(&reference)?;
+                    Ok(Some(reference))
+                }
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
 
+    /// Mirrors the result of a network lookup to the persistent cache tier.
+    async fn persist(&self, pubkey: &Pubkey, reference: Option<&Arc<AccountDataReference>>) {
+        if let Some(persistent_cache) = &self.persistent_cache {
+            let result = match reference {
+                Some(reference) => persistent_cache.store(reference).await,
+                None => persistent_cache.remove(pubkey).await,
+            };
+            if let Err(err) = result {
+                log_error!("Unable to update persistent cache for {}: {}", pubkey, err);
+            }
+        }
+    }
+
+    /// Refreshes a stale cache entry in the background. Requests for an
+    /// account that is already being refreshed are ignored.
+    fn revalidate(&self, pubkey: &Pubkey) {
+        let transport = match self.this.upgrade() {
+            Some(transport) => transport,
+            None => return,
+        };
+        if !self.revalidations.lock().unwrap().insert(*pubkey) {
+            return;
+        }
+        let pubkey = *pubkey;
+        workflow_core::task::spawn(async move {
+            if let Err(err) =
+                <Transport as super::Interface>::lookup_remote(&transport, &pubkey).await
+            {
+                log_error!("Unable to revalidate account {}: {}", pubkey, err);
+            }
+            transport.revalidations.lock().unwrap().remove(&pubkey);
+        });
+    }
+
+    pub async fn lookup_remote_impl(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<Option<Arc<AccountDataReference>>> {
         match self.mode {
             TransportMode::Inproc | TransportMode::Emulator => {
                 // let delay: u64 = rand::thread_rng().gen_range(500..1500);
@@ -517,7 +598,10 @@ impl Transport {
                         self.cache.store(&reference)?;
                         Ok(Some(reference))
                     }
-                    None => Ok(None),
+                    None => {
+                        self.cache.purge(Some(pubkey))?;
+                        Ok(None)
+                    }
                 }
             }
             TransportMode::Validator => {
@@ -525,7 +609,11 @@ impl Transport {
 
                 //log_trace!("get_account_info ({}) response: {:#?}", pubkey, account);
 
-                let reference = Arc::new(AccountDataReference::from((*pubkey, account)));
+                // connection is created with the "confirmed" commitment
+                let fetch_stamp = FetchStamp::try_new(None, Commitment::Confirmed)?;
+                let reference = Arc::new(
+                    AccountDataReference::from((*pubkey, account)).with_fetch_stamp(fetch_stamp),
+                );
                 self.cache.store(&reference)?;
                 Ok(Some(reference))
 
@@ -680,16 +768,27 @@ impl super::Interface for Transport {
     }
 
     async fn lookup(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
-        let reference = self.lookup_local(pubkey).await?;
-        match reference {
-            Some(reference) => Ok(Some(reference)),
-            None => Ok(self.lookup_remote(pubkey).await?),
+        match self.cache.lookup_with_freshness(pubkey)? {
+            CacheLookup::Fresh(reference) => Ok(Some(reference)),
+            CacheLookup::Stale(reference) => {
+                self.revalidate(pubkey);
+                Ok(Some(reference))
+            }
+            CacheLookup::Miss => match self.lookup_persistent(pubkey).await? {
+                Some(reference) => {
+                    self.revalidate(pubkey);
+                    Ok(Some(reference))
+                }
+                None => Ok(self.lookup_remote(pubkey).await?),
+            },
         }
     }
 
     async fn lookup_local(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
-        let pubkey = Arc::new(*pubkey);
-        Ok(self.cache.lookup(&pubkey)?)
+        match self.cache.lookup(pubkey)? {
+            Some(reference) => Ok(Some(reference)),
+            None => self.lookup_persistent(pubkey).await,
+        }
     }
 
     async fn lookup_remote(&self, pubkey: &Pubkey) -> Result<Option<Arc<AccountDataReference>>> {
@@ -700,6 +799,9 @@ impl super::Interface for Transport {
                 self.reflector
                     .reflect(reflector::Event::PendingLookups(lookup_handler.pending()));
                 let response = self.lookup_remote_impl(pubkey).await;
+                if let Ok(reference) = &response {
+                    self.persist(pubkey, reference.as_ref()).await;
+                }
                 lookup_handler.complete(pubkey, response).await;
                 receiver.recv().await?
             }